@@ -0,0 +1,207 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt;
+use std::fs;
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+use crate::LocCounterError;
+
+/// The numbers of one function in a `Baseline`.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FunctionBaseline {
+    pub lines: usize,
+    pub cognitive: usize,
+}
+
+/// `Baseline` 📌 - A snapshot of the project to ratchet against, usually kept in
+/// `countroo-baseline.json` next to `Cargo.toml`.
+///
+/// A legacy codebase rarely meets a hard budget, but it can promise not to get worse. Compared
+/// with a later snapshot, the baseline reports only regressions: a file or function that grew, a
+/// function that got more complex, a new `unsafe` block or a new dependency. New files and
+/// functions are not regressions in themselves.
+///
+/// - **files**: Lines per file, keyed by the path relative to the project, with `/` separators.
+/// - **unsafe_blocks**: `unsafe` blocks per file; files without any are left out.
+/// - **functions**: Lines and cognitive complexity per function, keyed by `<file>::<name>`.
+/// - **dependencies**: The names of every crate the project depends on.
+///
+/// ## Example Usage
+/// ```rust
+/// use countroo::prelude::*;
+/// let mut before = Baseline::default();
+/// before.files.insert("src/lib.rs".to_string(), 100);
+/// let mut after = before.clone();
+/// after.files.insert("src/lib.rs".to_string(), 120);
+/// after.dependencies.insert("serde".to_string());
+/// assert_eq!(before.compare(&after).len(), 2);
+/// assert!(before.tighten(&after).compare(&after).len() == 2);
+/// ```
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Baseline {
+    #[serde(default)]
+    pub files: BTreeMap<String, usize>,
+    #[serde(default)]
+    pub unsafe_blocks: BTreeMap<String, usize>,
+    #[serde(default)]
+    pub functions: BTreeMap<String, FunctionBaseline>,
+    #[serde(default)]
+    pub dependencies: BTreeSet<String>,
+}
+
+impl Baseline {
+    /// Reads a baseline file, or `None` when there is none yet.
+    #[cfg(feature = "ratchet")]
+    pub fn load(path: impl AsRef<Path>) -> Result<Option<Self>, LocCounterError> {
+        let path = path.as_ref();
+        if !path.is_file() {
+            return Ok(None);
+        }
+        let contents = fs::read_to_string(path).map_err(LocCounterError::IoError)?;
+        serde_json::from_str(&contents)
+            .map(Some)
+            .map_err(|err| LocCounterError::JsonError(format!("{}: {}", path.display(), err)))
+    }
+
+    #[cfg(feature = "ratchet")]
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), LocCounterError> {
+        let contents = serde_json::to_string_pretty(self).map_err(|err| LocCounterError::JsonError(err.to_string()))?;
+        fs::write(path, contents + "\n").map_err(LocCounterError::IoError)
+    }
+
+    /// Everything that got worse in `current` compared with this baseline.
+    pub fn compare(&self, current: &Baseline) -> Vec<Regression> {
+        let mut regressions = Vec::new();
+        for (file, &lines) in &current.files {
+            if let Some(&baseline) = self.files.get(file).filter(|&&baseline| lines > baseline) {
+                regressions.push(Regression { metric: Metric::FileLines, subject: file.clone(), baseline, current: lines });
+            }
+        }
+        for (file, &blocks) in &current.unsafe_blocks {
+            let baseline = self.unsafe_blocks.get(file).copied().unwrap_or(0);
+            if blocks > baseline {
+                regressions.push(Regression { metric: Metric::UnsafeBlocks, subject: file.clone(), baseline, current: blocks });
+            }
+        }
+        for (function, now) in &current.functions {
+            let Some(before) = self.functions.get(function) else { continue };
+            if now.lines > before.lines {
+                regressions.push(Regression { metric: Metric::FunctionLines, subject: function.clone(), baseline: before.lines, current: now.lines });
+            }
+            if now.cognitive > before.cognitive {
+                regressions.push(Regression { metric: Metric::CognitiveComplexity, subject: function.clone(), baseline: before.cognitive, current: now.cognitive });
+            }
+        }
+        for dependency in current.dependencies.difference(&self.dependencies) {
+            regressions.push(Regression { metric: Metric::Dependency, subject: dependency.clone(), baseline: 0, current: 1 });
+        }
+        regressions
+    }
+
+    /// Lowers the baseline to wherever `current` improved on it, keeping it where `current` got
+    /// worse. New files and functions are taken in as they are; new unsafe blocks and new
+    /// dependencies are not, so they keep showing up until they are dealt with.
+    pub fn tighten(&self, current: &Baseline) -> Baseline {
+        Baseline {
+            files: current.files.iter()
+                .map(|(file, &lines)| (file.clone(), self.files.get(file).map_or(lines, |&baseline| baseline.min(lines))))
+                .collect(),
+            unsafe_blocks: current.unsafe_blocks.iter()
+                .map(|(file, &blocks)| (file.clone(), self.unsafe_blocks.get(file).copied().unwrap_or(0).min(blocks)))
+                .filter(|(_, blocks)| *blocks > 0)
+                .collect(),
+            functions: current.functions.iter()
+                .map(|(function, &now)| {
+                    let tightened = self.functions.get(function).map_or(now, |before| FunctionBaseline {
+                        lines: before.lines.min(now.lines),
+                        cognitive: before.cognitive.min(now.cognitive),
+                    });
+                    (function.clone(), tightened)
+                })
+                .collect(),
+            dependencies: self.dependencies.intersection(&current.dependencies).cloned().collect(),
+        }
+    }
+}
+
+/// What a `Regression` is about.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "json-output", derive(schemars::JsonSchema))]
+#[serde(rename_all = "snake_case")]
+pub enum Metric {
+    FileLines,
+    UnsafeBlocks,
+    FunctionLines,
+    CognitiveComplexity,
+    Dependency,
+}
+
+/// Something that got worse since the baseline was taken.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "json-output", derive(schemars::JsonSchema))]
+pub struct Regression {
+    pub metric: Metric,
+    /// The file, function or dependency that regressed.
+    pub subject: String,
+    pub baseline: usize,
+    pub current: usize,
+}
+
+impl fmt::Display for Regression {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let what = match self.metric {
+            Metric::FileLines => "lines in",
+            Metric::UnsafeBlocks => "unsafe blocks in",
+            Metric::FunctionLines => "lines in function",
+            Metric::CognitiveComplexity => "cognitive complexity of",
+            Metric::Dependency => return write!(f, "new dependency: {}", self.subject),
+        };
+        write!(f, "{} {} went from {} to {}", what, self.subject, self.baseline, self.current)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_baseline_reports_regressions_and_tightens() {
+        let function = |lines, cognitive| FunctionBaseline { lines, cognitive };
+        let baseline = Baseline {
+            files: BTreeMap::from([("src/a.rs".to_string(), 100), ("src/b.rs".to_string(), 50)]),
+            unsafe_blocks: BTreeMap::from([("src/a.rs".to_string(), 2)]),
+            functions: BTreeMap::from([("src/a.rs::f".to_string(), function(30, 5))]),
+            dependencies: BTreeSet::from(["rayon".to_string(), "syn".to_string()]),
+        };
+        let current = Baseline {
+            files: BTreeMap::from([("src/a.rs".to_string(), 90), ("src/b.rs".to_string(), 60), ("src/new.rs".to_string(), 500)]),
+            unsafe_blocks: BTreeMap::from([("src/a.rs".to_string(), 1), ("src/new.rs".to_string(), 1)]),
+            functions: BTreeMap::from([("src/a.rs::f".to_string(), function(25, 7))]),
+            dependencies: BTreeSet::from(["serde".to_string(), "syn".to_string()]),
+        };
+
+        let regressions = baseline.compare(&current).iter().map(ToString::to_string).collect::<Vec<_>>();
+        assert_eq!(regressions, vec![
+            "lines in src/b.rs went from 50 to 60",
+            "unsafe blocks in src/new.rs went from 0 to 1",
+            "cognitive complexity of src/a.rs::f went from 5 to 7",
+            "new dependency: serde",
+        ]);
+
+        let tightened = baseline.tighten(&current);
+        assert_eq!(tightened.files["src/a.rs"], 90);
+        assert_eq!(tightened.files["src/b.rs"], 50);
+        assert_eq!(tightened.files["src/new.rs"], 500);
+        assert_eq!(tightened.unsafe_blocks, BTreeMap::from([("src/a.rs".to_string(), 1)]));
+        assert_eq!(tightened.functions["src/a.rs::f"], function(25, 5));
+        assert_eq!(tightened.dependencies, BTreeSet::from(["syn".to_string()]));
+        assert_eq!(tightened.compare(&current).len(), 4);
+
+        let path = std::env::temp_dir().join(format!("countroo-baseline-{}.json", std::process::id()));
+        tightened.save(&path).unwrap();
+        assert_eq!(Baseline::load(&path).unwrap(), Some(tightened));
+        fs::remove_file(&path).unwrap();
+        assert_eq!(Baseline::load(&path).unwrap(), None);
+    }
+}
@@ -0,0 +1,238 @@
+use std::fmt;
+use std::path::PathBuf;
+
+use serde::{Deserialize, Serialize};
+
+use crate::complexity::FunctionComplexity;
+
+/// `Budgets` 💰 - Limits a project should stay within, checked after every analysis.
+///
+/// Every budget is optional; the ones left out are not checked.
+/// - **max_total_lines**: Lines across every counted file.
+/// - **max_file_lines**: Lines in any single file.
+/// - **max_function_lines**: Lines spanned by any single Rust function.
+/// - **max_unsafe_blocks**: `unsafe` blocks across the project.
+/// - **min_comment_ratio**: Comment lines per line of code, e.g. `0.1` for one comment line in
+///   every ten lines of code.
+/// - **max_dependencies**: Crates the project depends on, transitive ones included.
+/// - **strict**: Whether going over a budget fails the build instead of warning about it.
+///
+/// ## Example Usage
+/// ```rust
+/// use countroo::prelude::*;
+/// let mut budgets = Budgets::default();
+/// assert!(budgets.parse_directive("max_function_lines: 80").unwrap());
+/// assert!(!budgets.parse_directive("rs").unwrap());
+/// assert_eq!(budgets.max_function_lines, Some(80));
+/// ```
+#[derive(Debug, Default, Clone, PartialEq, Eq, Deserialize)]
+pub struct Budgets {
+    #[serde(default)]
+    pub max_total_lines: Option<usize>,
+    #[serde(default)]
+    pub max_file_lines: Option<usize>,
+    #[serde(default)]
+    pub max_function_lines: Option<usize>,
+    #[serde(default)]
+    pub max_unsafe_blocks: Option<usize>,
+    #[serde(default)]
+    pub min_comment_ratio: Option<CommentRatio>,
+    #[serde(default)]
+    pub max_dependencies: Option<usize>,
+    #[serde(default)]
+    pub strict: bool,
+}
+
+/// `CommentRatio` 📏 - Comment lines per line of code, always a finite, non-negative number.
+///
+/// `NaN` would never compare as met, so it cannot be built: `CommentRatio::new` and
+/// deserialization both turn it away, which also makes `Eq` hold.
+///
+/// ## Example Usage
+/// ```rust
+/// use countroo::prelude::*;
+/// assert_eq!(CommentRatio::new(0.25).unwrap().get(), 0.25);
+/// assert!(CommentRatio::new(f64::NAN).is_err());
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, PartialOrd, Deserialize)]
+#[serde(try_from = "f64")]
+pub struct CommentRatio(f64);
+
+impl CommentRatio {
+    pub fn new(ratio: f64) -> Result<Self, String> {
+        if ratio.is_finite() && ratio >= 0.0 {
+            Ok(CommentRatio(ratio))
+        } else {
+            Err(format!("`min_comment_ratio` must be a non-negative number, found `{}`", ratio))
+        }
+    }
+
+    pub fn get(self) -> f64 {
+        self.0
+    }
+}
+
+impl Eq for CommentRatio {}
+
+impl TryFrom<f64> for CommentRatio {
+    type Error = String;
+
+    fn try_from(ratio: f64) -> Result<Self, String> {
+        CommentRatio::new(ratio)
+    }
+}
+
+impl fmt::Display for CommentRatio {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+impl Budgets {
+    /// Applies a `max_file_lines: 500` style line of a newline-separated config, returning whether
+    /// the line was a budget at all. `strict_budgets: true` turns on strict mode.
+    pub fn parse_directive(&mut self, line: &str) -> Result<bool, String> {
+        let Some((name, value)) = line.split_once(':') else { return Ok(false) };
+        let value = value.trim();
+        let limit = || value.parse::<usize>().map_err(|_| format!("`{}` must be a whole number, found `{}`", name, value));
+        match name.trim() {
+            "max_total_lines" => self.max_total_lines = Some(limit()?),
+            "max_file_lines" => self.max_file_lines = Some(limit()?),
+            "max_function_lines" => self.max_function_lines = Some(limit()?),
+            "max_unsafe_blocks" => self.max_unsafe_blocks = Some(limit()?),
+            "max_dependencies" => self.max_dependencies = Some(limit()?),
+            "min_comment_ratio" => {
+                let ratio = value.parse::<f64>().map_err(|_| format!("`min_comment_ratio` must be a non-negative number, found `{}`", value))?;
+                self.min_comment_ratio = Some(CommentRatio::new(ratio)?);
+            }
+            "strict_budgets" => {
+                self.strict = value.parse().map_err(|_| format!("`strict_budgets` must be true or false, found `{}`", value))?;
+            }
+            _ => return Ok(false),
+        }
+        Ok(true)
+    }
+
+    /// Whether there is any budget to check.
+    pub fn is_empty(&self) -> bool {
+        self.max_total_lines.is_none() && self.max_file_lines.is_none() && self.max_function_lines.is_none()
+            && self.max_unsafe_blocks.is_none() && self.min_comment_ratio.is_none() && self.max_dependencies.is_none()
+    }
+
+    /// Every budget the measurements go over, one violation per offending file or function.
+    pub fn check(&self, measurements: &Measurements) -> Vec<BudgetViolation> {
+        let mut violations = Vec::new();
+        let mut over = |budget: &str, offender: String, actual: usize, limit: Option<usize>| {
+            if let Some(limit) = limit.filter(|limit| actual > *limit) {
+                violations.push(BudgetViolation { budget: budget.to_string(), offender, actual: actual.to_string(), limit: limit.to_string() });
+            }
+        };
+
+        over("max_total_lines", "the project".to_string(), measurements.file_lines.iter().map(|(_, lines)| lines).sum(), self.max_total_lines);
+        let mut files = measurements.file_lines.iter().collect::<Vec<_>>();
+        files.sort_by_key(|(_, lines)| std::cmp::Reverse(*lines));
+        for (file, lines) in files {
+            over("max_file_lines", file.display().to_string(), *lines, self.max_file_lines);
+        }
+        let mut functions = measurements.functions.clone();
+        functions.sort_by_key(|function| std::cmp::Reverse(function.lines));
+        for function in functions {
+            let offender = format!("{} ({}:{})", function.name, function.file.display(), function.line);
+            over("max_function_lines", offender, function.lines, self.max_function_lines);
+        }
+        over("max_unsafe_blocks", "the project".to_string(), measurements.unsafe_blocks, self.max_unsafe_blocks);
+        over("max_dependencies", "the project".to_string(), measurements.dependencies, self.max_dependencies);
+
+        if let Some(min) = self.min_comment_ratio {
+            let ratio = if measurements.code_lines == 0 { 0.0 } else { measurements.comment_lines as f64 / measurements.code_lines as f64 };
+            if ratio < min.get() {
+                violations.push(BudgetViolation { budget: "min_comment_ratio".to_string(), offender: "the project".to_string(), actual: format!("{:.3}", ratio), limit: min.to_string() });
+            }
+        }
+        violations
+    }
+}
+
+/// The numbers budgets are checked against, taken from a finished analysis.
+#[derive(Debug, Default, Clone)]
+pub struct Measurements<'a> {
+    /// Every counted file with its lines.
+    pub file_lines: Vec<(PathBuf, usize)>,
+    pub functions: Vec<&'a FunctionComplexity>,
+    pub unsafe_blocks: usize,
+    pub code_lines: usize,
+    pub comment_lines: usize,
+    pub dependencies: usize,
+}
+
+/// A budget that was gone over, and by whom.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "json-output", derive(schemars::JsonSchema))]
+pub struct BudgetViolation {
+    pub budget: String,
+    pub offender: String,
+    pub actual: String,
+    pub limit: String,
+}
+
+impl fmt::Display for BudgetViolation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let comparison = if self.budget.starts_with("min_") { "below" } else { "over" };
+        write!(f, "{}: {} is at {}, {} the budget of {}", self.budget, self.offender, self.actual, comparison, self.limit)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_budgets_are_parsed_and_checked() {
+        let mut budgets = Budgets::default();
+        for line in ["max_total_lines: 100", "max_file_lines: 60", "max_function_lines: 20", "max_unsafe_blocks: 0", "min_comment_ratio: 0.5", "max_dependencies: 3", "strict_budgets: true"] {
+            assert_eq!(budgets.parse_directive(line), Ok(true));
+        }
+        assert!(budgets.strict);
+        assert!(budgets.parse_directive("max_file_lines: lots").is_err());
+        assert!(budgets.parse_directive("min_comment_ratio: NaN").is_err());
+        assert_eq!(budgets.parse_directive("exclude: target/**"), Ok(false));
+
+        let long = FunctionComplexity { name: "long".to_string(), file: PathBuf::from("src/a.rs"), line: 3, lines: 25, max_nesting: 0, cyclomatic: 1, cognitive: 0 };
+        let short = FunctionComplexity { name: "short".to_string(), lines: 5, ..long.clone() };
+        let measurements = Measurements {
+            file_lines: vec![(PathBuf::from("src/a.rs"), 70), (PathBuf::from("src/b.rs"), 40)],
+            functions: vec![&short, &long],
+            unsafe_blocks: 1,
+            code_lines: 100,
+            comment_lines: 10,
+            dependencies: 3,
+        };
+        let violations = budgets.check(&measurements).iter().map(ToString::to_string).collect::<Vec<_>>();
+        assert_eq!(violations, vec![
+            "max_total_lines: the project is at 110, over the budget of 100",
+            "max_file_lines: src/a.rs is at 70, over the budget of 60",
+            "max_function_lines: long (src/a.rs:3) is at 25, over the budget of 20",
+            "max_unsafe_blocks: the project is at 1, over the budget of 0",
+            "min_comment_ratio: the project is at 0.100, below the budget of 0.5",
+        ]);
+        assert!(Budgets::default().check(&measurements).is_empty());
+    }
+
+    #[test]
+    fn test_comment_ratios_must_be_finite_and_non_negative() {
+        assert!(CommentRatio::new(f64::NAN).is_err());
+        assert!(CommentRatio::new(f64::INFINITY).is_err());
+        assert!(CommentRatio::new(-0.5).is_err());
+        assert_eq!(CommentRatio::new(0.0).map(CommentRatio::get), Ok(0.0));
+        assert_eq!(CommentRatio::new(0.25).map(CommentRatio::get), Ok(0.25));
+    }
+
+    #[test]
+    #[cfg(feature = "toml-config")]
+    fn test_deserialized_comment_ratios_are_checked() {
+        assert_eq!(toml::from_str::<Budgets>("min_comment_ratio = 0.25").unwrap().min_comment_ratio, CommentRatio::new(0.25).ok());
+        assert!(toml::from_str::<Budgets>("min_comment_ratio = nan").is_err());
+        assert!(toml::from_str::<Budgets>("min_comment_ratio = -1.0").is_err());
+        assert_eq!(toml::from_str::<Budgets>("strict = true").unwrap().min_comment_ratio, None);
+    }
+}
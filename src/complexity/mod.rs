@@ -0,0 +1,360 @@
+use std::fs;
+use std::mem::{self, Discriminant};
+use std::path::{Path, PathBuf};
+
+use syn::visit::{self, Visit};
+use syn::{BinOp, Block, Expr, ExprBinary, ExprBreak, ExprClosure, ExprContinue, ExprForLoop, ExprIf, ExprLoop, ExprMatch, ExprTry, ExprWhile, ImplItemFn, Item, ItemFn, ItemImpl, ItemMod, ItemTrait, Signature, TraitItemFn, Type};
+
+use crate::LocCounterError;
+
+/// `FunctionComplexity` 🌀 - How hard one function or method is to follow.
+///
+/// - **name**: The function name, qualified with the type, trait and inline modules it sits in
+///   within its file (`Parser::next_token`).
+/// - **line**: The line of the `fn` keyword.
+/// - **lines**: How many lines the function spans, from the `fn` keyword to its closing brace.
+/// - **max_nesting**: How deep its blocks go: every `if`, `else`, `match`, loop and closure
+///   nests what is inside it one level deeper.
+/// - **cyclomatic**: 1, plus one for every `if`/`else if`, every `match` arm past the first and
+///   every arm guard, every loop, every `?` and every `&&`/`||`.
+/// - **cognitive**: One for every `if`, `else if`, `else`, `match` and loop, plus one more for each
+///   level they are nested at; one per run of identical boolean operators and per labelled
+///   `break`/`continue`. Closures are part of the function they are written in, and nest what is
+///   inside them.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FunctionComplexity {
+    pub name: String,
+    pub file: PathBuf,
+    pub line: usize,
+    pub lines: usize,
+    pub max_nesting: usize,
+    pub cyclomatic: usize,
+    pub cognitive: usize,
+}
+
+/// The functions of one file, with their maxima and averages.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct FileComplexity {
+    pub file: PathBuf,
+    pub functions: Vec<FunctionComplexity>,
+}
+
+impl FileComplexity {
+    /// Parses a Rust source and measures every function in it, nested ones included. Sources
+    /// that do not parse have no functions.
+    pub fn from_source(contents: &str, file: impl AsRef<Path>) -> Self {
+        let file = file.as_ref().to_path_buf();
+        let Ok(parsed) = syn::parse_file(contents) else { return FileComplexity { file, functions: Vec::new() } };
+        let mut collector = FunctionCollector { file: &file, scope: Vec::new(), functions: Vec::new() };
+        collector.visit_file(&parsed);
+        FileComplexity { functions: collector.functions, file }
+    }
+
+    pub fn from_file(path: &Path) -> Result<Self, LocCounterError> {
+        Ok(Self::from_source(&fs::read_to_string(path).map_err(LocCounterError::IoError)?, path))
+    }
+
+    pub fn max_cyclomatic(&self) -> usize {
+        self.functions.iter().map(|function| function.cyclomatic).max().unwrap_or(0)
+    }
+
+    pub fn max_cognitive(&self) -> usize {
+        self.functions.iter().map(|function| function.cognitive).max().unwrap_or(0)
+    }
+
+    pub fn average_cyclomatic(&self) -> f64 {
+        average(self.functions.iter().map(|function| function.cyclomatic))
+    }
+
+    pub fn average_cognitive(&self) -> f64 {
+        average(self.functions.iter().map(|function| function.cognitive))
+    }
+}
+
+/// `ComplexityReport` 🌀 - The complexity of every Rust function in the project, file by file.
+///
+/// ## Example Usage
+/// ```rust
+/// use countroo::prelude::*;
+/// let file = FileComplexity::from_source("fn f(x: bool) { if x { for _ in 0..3 {} } }", "f.rs");
+/// let report = ComplexityReport { files: vec![file] };
+/// let worst = &report.top(1)[0];
+/// assert_eq!((worst.name.as_str(), worst.cyclomatic, worst.cognitive), ("f", 3, 3));
+/// ```
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct ComplexityReport {
+    pub files: Vec<FileComplexity>,
+}
+
+impl ComplexityReport {
+    pub fn functions(&self) -> impl Iterator<Item = &FunctionComplexity> {
+        self.files.iter().flat_map(|file| file.functions.iter())
+    }
+
+    /// The `n` most complex functions, by cognitive complexity and then cyclomatic complexity.
+    pub fn top(&self, n: usize) -> Vec<&FunctionComplexity> {
+        let mut functions = self.functions().collect::<Vec<_>>();
+        functions.sort_by(|a, b| (b.cognitive, b.cyclomatic).cmp(&(a.cognitive, a.cyclomatic)).then_with(|| a.name.cmp(&b.name)));
+        functions.truncate(n);
+        functions
+    }
+
+    pub fn max_cyclomatic(&self) -> usize {
+        self.files.iter().map(FileComplexity::max_cyclomatic).max().unwrap_or(0)
+    }
+
+    pub fn max_cognitive(&self) -> usize {
+        self.files.iter().map(FileComplexity::max_cognitive).max().unwrap_or(0)
+    }
+
+    pub fn average_cyclomatic(&self) -> f64 {
+        average(self.functions().map(|function| function.cyclomatic))
+    }
+
+    pub fn average_cognitive(&self) -> f64 {
+        average(self.functions().map(|function| function.cognitive))
+    }
+}
+
+fn average(values: impl Iterator<Item = usize>) -> f64 {
+    let (count, sum) = values.fold((0, 0), |(count, sum), value| (count + 1, sum + value));
+    if count == 0 {
+        return 0.0;
+    }
+    sum as f64 / count as f64
+}
+
+/// Finds the functions of a file, keeping track of the types, traits and modules around them.
+struct FunctionCollector<'a> {
+    file: &'a Path,
+    scope: Vec<String>,
+    functions: Vec<FunctionComplexity>,
+}
+
+impl FunctionCollector<'_> {
+    fn measure(&mut self, sig: &Signature, block: &Block) {
+        let mut counter = ComplexityCounter { cyclomatic: 1, cognitive: 0, nesting: 0, max_nesting: 0, logical_parent: None };
+        counter.visit_block(block);
+        let line = sig.fn_token.span.start().line;
+        let name = self.scope.iter().cloned().chain(std::iter::once(sig.ident.to_string())).collect::<Vec<_>>().join("::");
+        self.functions.push(FunctionComplexity {
+            name,
+            file: self.file.to_path_buf(),
+            line,
+            lines: block.brace_token.span.close().end().line.saturating_sub(line) + 1,
+            max_nesting: counter.max_nesting,
+            cyclomatic: counter.cyclomatic,
+            cognitive: counter.cognitive,
+        });
+    }
+
+    fn scoped(&mut self, name: String, visit: impl FnOnce(&mut Self)) {
+        self.scope.push(name);
+        visit(self);
+        self.scope.pop();
+    }
+}
+
+impl<'ast> Visit<'ast> for FunctionCollector<'_> {
+    fn visit_item_fn(&mut self, node: &'ast ItemFn) {
+        self.measure(&node.sig, &node.block);
+        self.scoped(node.sig.ident.to_string(), |collector| visit::visit_item_fn(collector, node));
+    }
+
+    fn visit_impl_item_fn(&mut self, node: &'ast ImplItemFn) {
+        self.measure(&node.sig, &node.block);
+        self.scoped(node.sig.ident.to_string(), |collector| visit::visit_impl_item_fn(collector, node));
+    }
+
+    fn visit_trait_item_fn(&mut self, node: &'ast TraitItemFn) {
+        if let Some(block) = &node.default {
+            self.measure(&node.sig, block);
+        }
+        self.scoped(node.sig.ident.to_string(), |collector| visit::visit_trait_item_fn(collector, node));
+    }
+
+    fn visit_item_impl(&mut self, node: &'ast ItemImpl) {
+        let name = match &*node.self_ty {
+            Type::Path(path) => path.path.segments.last().map(|segment| segment.ident.to_string()).unwrap_or_default(),
+            _ => "impl".to_string(),
+        };
+        self.scoped(name, |collector| visit::visit_item_impl(collector, node));
+    }
+
+    fn visit_item_trait(&mut self, node: &'ast ItemTrait) {
+        self.scoped(node.ident.to_string(), |collector| visit::visit_item_trait(collector, node));
+    }
+
+    fn visit_item_mod(&mut self, node: &'ast ItemMod) {
+        self.scoped(node.ident.to_string(), |collector| visit::visit_item_mod(collector, node));
+    }
+}
+
+/// Adds up the complexity of a single function body.
+struct ComplexityCounter {
+    cyclomatic: usize,
+    cognitive: usize,
+    nesting: usize,
+    max_nesting: usize,
+    /// The boolean operator of the expression directly around the one being visited, so that
+    /// `a && b && c` counts as one run.
+    logical_parent: Option<Discriminant<BinOp>>,
+}
+
+impl ComplexityCounter {
+    fn nested(&mut self, visit: impl FnOnce(&mut Self)) {
+        self.nesting += 1;
+        self.max_nesting = self.max_nesting.max(self.nesting);
+        visit(self);
+        self.nesting -= 1;
+    }
+
+    fn structural(&mut self) {
+        self.cyclomatic += 1;
+        self.cognitive += 1 + self.nesting;
+    }
+}
+
+fn is_logical(op: &BinOp) -> bool {
+    matches!(op, BinOp::And(_) | BinOp::Or(_))
+}
+
+impl<'ast> Visit<'ast> for ComplexityCounter {
+    fn visit_expr_if(&mut self, node: &'ast ExprIf) {
+        self.structural();
+        self.visit_expr(&node.cond);
+        self.nested(|counter| counter.visit_block(&node.then_branch));
+        let mut else_branch = node.else_branch.as_ref().map(|(_, expr)| &**expr);
+        while let Some(expr) = else_branch {
+            if let Expr::If(else_if) = expr {
+                self.cyclomatic += 1;
+                self.cognitive += 1;
+                self.visit_expr(&else_if.cond);
+                self.nested(|counter| counter.visit_block(&else_if.then_branch));
+                else_branch = else_if.else_branch.as_ref().map(|(_, expr)| &**expr);
+            } else {
+                self.cognitive += 1;
+                self.nested(|counter| counter.visit_expr(expr));
+                else_branch = None;
+            }
+        }
+    }
+
+    fn visit_expr_match(&mut self, node: &'ast ExprMatch) {
+        self.cyclomatic += node.arms.len().saturating_sub(1);
+        self.cyclomatic += node.arms.iter().filter(|arm| arm.guard.is_some()).count();
+        self.cognitive += 1 + self.nesting;
+        self.visit_expr(&node.expr);
+        self.nested(|counter| node.arms.iter().for_each(|arm| counter.visit_arm(arm)));
+    }
+
+    fn visit_expr_while(&mut self, node: &'ast ExprWhile) {
+        self.structural();
+        self.visit_expr(&node.cond);
+        self.nested(|counter| counter.visit_block(&node.body));
+    }
+
+    fn visit_expr_for_loop(&mut self, node: &'ast ExprForLoop) {
+        self.structural();
+        self.visit_expr(&node.expr);
+        self.nested(|counter| counter.visit_block(&node.body));
+    }
+
+    fn visit_expr_loop(&mut self, node: &'ast ExprLoop) {
+        self.structural();
+        self.nested(|counter| counter.visit_block(&node.body));
+    }
+
+    fn visit_expr_closure(&mut self, node: &'ast ExprClosure) {
+        self.nested(|counter| visit::visit_expr_closure(counter, node));
+    }
+
+    fn visit_expr_try(&mut self, node: &'ast ExprTry) {
+        self.cyclomatic += 1;
+        visit::visit_expr_try(self, node);
+    }
+
+    fn visit_expr_binary(&mut self, node: &'ast ExprBinary) {
+        let parent = self.logical_parent.take();
+        if !is_logical(&node.op) {
+            visit::visit_expr_binary(self, node);
+            return;
+        }
+        self.cyclomatic += 1;
+        if parent != Some(mem::discriminant(&node.op)) {
+            self.cognitive += 1;
+        }
+        for operand in [&node.left, &node.right] {
+            if let Expr::Binary(_) = **operand {
+                self.logical_parent = Some(mem::discriminant(&node.op));
+            }
+            self.visit_expr(operand);
+        }
+    }
+
+    fn visit_expr_break(&mut self, node: &'ast ExprBreak) {
+        if node.label.is_some() {
+            self.cognitive += 1;
+        }
+        visit::visit_expr_break(self, node);
+    }
+
+    fn visit_expr_continue(&mut self, node: &'ast ExprContinue) {
+        if node.label.is_some() {
+            self.cognitive += 1;
+        }
+        visit::visit_expr_continue(self, node);
+    }
+
+    /// Functions declared inside a body are measured on their own.
+    fn visit_item(&mut self, _: &'ast Item) {}
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_function_complexity_is_measured() {
+        let source = r#"
+            fn straight() -> u8 { 1 }
+
+            struct Parser;
+            impl Parser {
+                fn next(&self, input: &str) -> Result<u8, String> {
+                    let first = input.parse::<u8>().map_err(|e| e.to_string())?;
+                    if first > 10 && first < 20 && first != 15 {
+                        for c in input.chars() {
+                            match c {
+                                'a' => return Ok(1),
+                                'b' if first == 12 => return Ok(2),
+                                _ => {}
+                            }
+                        }
+                    } else if first == 0 || first == 1 {
+                        return Ok(0);
+                    } else {
+                        'outer: loop { break 'outer; }
+                    }
+                    fn helper() -> bool { true || false }
+                    Ok(first)
+                }
+            }
+        "#;
+        let file = FileComplexity::from_source(source, "parser.rs");
+        let names: Vec<&str> = file.functions.iter().map(|f| f.name.as_str()).collect();
+        assert_eq!(names, vec!["straight", "Parser::next", "Parser::next::helper"]);
+        let complexities: Vec<(usize, usize)> = file.functions.iter().map(|f| (f.cyclomatic, f.cognitive)).collect();
+        // next: `?`, if, 2 &&, for, 2 extra arms, 1 guard, else if, ||, loop.
+        // Cognitive: if 1, && run 1, for 2, match 3, else if 1, || 1, else 1, loop 2, labelled break 1.
+        assert_eq!(complexities, vec![(1, 0), (12, 13), (2, 1)]);
+        assert_eq!(file.functions[1].line, 6);
+        let shapes: Vec<(usize, usize)> = file.functions.iter().map(|f| (f.lines, f.max_nesting)).collect();
+        assert_eq!(shapes, vec![(1, 0), (18, 3), (1, 0)]);
+        assert_eq!(file.max_cognitive(), 13);
+        assert_eq!(file.average_cyclomatic(), 5.0);
+
+        let report = ComplexityReport { files: vec![file] };
+        assert_eq!(report.top(1)[0].name, "Parser::next");
+    }
+}
@@ -0,0 +1,636 @@
+use std::collections::btree_map::Entry;
+use std::collections::{BTreeMap, BTreeSet, VecDeque};
+use std::{env, fmt};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+use cargo_toml_workspace::cargo_toml::{Dependency, Manifest, Value};
+use serde::{Deserialize, Serialize};
+
+use crate::line_classifier::LineCounts;
+use crate::unsafe_code::UnsafeStats;
+use crate::LocCounterError;
+
+/// One `[[package]]` entry of a `Cargo.lock`.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct LockedPackage {
+    pub name: String,
+    pub version: String,
+    /// `registry+https://...` or `git+https://...`; absent for path and workspace packages.
+    pub source: Option<String>,
+    /// Entries of the form `name`, `name version` or `name version (source)`.
+    #[serde(default)]
+    pub dependencies: Vec<String>,
+}
+
+/// `Lockfile` 🔒 - The resolved dependency graph cargo wrote into `Cargo.lock`.
+#[derive(Debug, Default, Clone, PartialEq, Eq, Deserialize)]
+pub struct Lockfile {
+    #[serde(default, rename = "package")]
+    pub packages: Vec<LockedPackage>,
+}
+
+impl Lockfile {
+    pub fn from_path(path: impl AsRef<Path>) -> Result<Self, LocCounterError> {
+        fs::read_to_string(path.as_ref()).map_err(LocCounterError::IoError)?.parse()
+    }
+
+    /// The index of the package a `dependencies` entry points at.
+    pub fn resolve(&self, entry: &str) -> Option<usize> {
+        let mut parts = entry.split_whitespace();
+        let name = parts.next()?;
+        let version = parts.next();
+        self.packages.iter().position(|package| package.name == name && version.is_none_or(|v| package.version == v))
+    }
+
+    /// The indices of the direct dependencies of a package.
+    pub fn dependencies_of(&self, index: usize) -> Vec<usize> {
+        self.packages[index].dependencies.iter().filter_map(|entry| self.resolve(entry)).collect()
+    }
+}
+
+impl FromStr for Lockfile {
+    type Err = LocCounterError;
+
+    fn from_str(contents: &str) -> Result<Self, Self::Err> {
+        contents.parse::<Value>()
+            .and_then(|value| value.try_into())
+            .map_err(|err| LocCounterError::TomlError(format!("Invalid Cargo.lock: {}", err)))
+    }
+}
+
+/// How many dependencies the manifests declare, per kind. Entries under `[target.'cfg(..)'.*]`
+/// tables count as target specific only, whatever their kind.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "json-output", derive(schemars::JsonSchema))]
+pub struct DependencyKinds {
+    pub normal: usize,
+    pub dev: usize,
+    pub build: usize,
+    pub target_specific: usize,
+}
+
+impl DependencyKinds {
+    pub fn total(&self) -> usize {
+        self.normal + self.dev + self.build + self.target_specific
+    }
+}
+
+/// Which section of a manifest a dependency is declared in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "json-output", derive(schemars::JsonSchema))]
+#[serde(rename_all = "snake_case")]
+pub enum DependencyKind {
+    Normal,
+    Dev,
+    Build,
+}
+
+/// Where a dependency comes from, after `workspace = true` has been resolved.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DependencySource {
+    /// crates.io, or the alternative `registry` when one is named.
+    Registry { version: String, registry: Option<String> },
+    /// A git repository, with the `branch`, `tag` or `rev` it is pinned to.
+    Git { url: String, reference: Option<String> },
+    /// A local folder, relative to the manifest that declares it.
+    Path { path: PathBuf },
+    /// `workspace = true` without a matching `[workspace.dependencies]` entry.
+    Unresolved,
+}
+
+impl fmt::Display for DependencySource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DependencySource::Registry { version, registry: Some(registry) } => write!(f, "{} ({})", version, registry),
+            DependencySource::Registry { version, registry: None } => write!(f, "{}", version),
+            DependencySource::Git { url, reference: Some(reference) } => write!(f, "git {} @ {}", url, reference),
+            DependencySource::Git { url, reference: None } => write!(f, "git {}", url),
+            DependencySource::Path { path } => write!(f, "path {}", path.display()),
+            DependencySource::Unresolved => write!(f, "unresolved workspace dependency"),
+        }
+    }
+}
+
+/// A single entry of a `[dependencies]`-like table of one of the project's manifests.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DeclaredDependency {
+    /// The key in the manifest, which is the crate name unless the dependency is renamed.
+    pub name: String,
+    /// The package that declares the dependency.
+    pub member: String,
+    pub kind: DependencyKind,
+    /// The `cfg(...)` or target triple of a `[target.*]` table.
+    pub target: Option<String>,
+    pub source: DependencySource,
+    /// Declared with `workspace = true` and resolved against `[workspace.dependencies]`.
+    pub workspace_inherited: bool,
+}
+
+/// Declared dependencies per source. `workspace_inherited` counts the `workspace = true`
+/// entries, which are also counted under the source they resolved to.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "json-output", derive(schemars::JsonSchema))]
+pub struct SourceCounts {
+    pub registry: usize,
+    pub git: usize,
+    pub path: usize,
+    pub workspace_inherited: usize,
+}
+
+/// A crate that ends up in the build at more than one version.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "json-output", derive(schemars::JsonSchema))]
+pub struct DuplicateCrate {
+    pub name: String,
+    pub versions: Vec<String>,
+}
+
+/// `DependencyReport` 📦 - What a project really pulls in.
+///
+/// - **declared**: Every dependency the manifests declare, with its resolved source.
+/// - **kinds**: Declared dependencies per kind.
+/// - **sources**: Declared dependencies per source: registry, git, path and workspace-inherited.
+/// - **direct**: Distinct crates the project's own packages depend on in `Cargo.lock`.
+/// - **transitive**: Crates that are only pulled in by other dependencies.
+/// - **duplicates**: Crates resolved at several versions at once.
+/// - **max_depth**: The length of the longest chain from the project to a dependency, where the
+///   direct dependencies sit at depth 1.
+/// - **resolved**: The locked packages behind `direct` and `transitive`.
+///
+/// Without a `Cargo.lock` only the declared `kinds` are known.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct DependencyReport {
+    pub declared: Vec<DeclaredDependency>,
+    pub kinds: DependencyKinds,
+    pub sources: SourceCounts,
+    pub has_lockfile: bool,
+    pub direct: usize,
+    pub transitive: usize,
+    pub duplicates: Vec<DuplicateCrate>,
+    pub max_depth: usize,
+    /// Every package of the resolved graph except the project's own, in `Cargo.lock` order.
+    pub resolved: Vec<LockedPackage>,
+}
+
+impl DependencyReport {
+    /// Analyses the packages in `manifest_dirs` (one per workspace member) within the workspace
+    /// at `workspace_root`, whose `[workspace.dependencies]` resolve `workspace = true` entries
+    /// and whose `Cargo.lock`, when there is one, holds the resolved graph. For a single package,
+    /// the workspace root is the package itself.
+    pub fn analyze(manifest_dirs: &[PathBuf], workspace_root: &Path) -> Result<Self, LocCounterError> {
+        let root_manifest = read_raw_manifest(workspace_root)?;
+        let workspace_dependencies = root_manifest.workspace.map(|workspace| workspace.dependencies).unwrap_or_default();
+
+        let mut report = DependencyReport::default();
+        let mut roots = BTreeSet::new();
+        for dir in manifest_dirs {
+            let manifest = read_raw_manifest(dir)?;
+            let member = manifest.package.as_ref().map(|package| package.name.clone()).unwrap_or_default();
+            let mut tables = vec![
+                (DependencyKind::Normal, None, &manifest.dependencies),
+                (DependencyKind::Dev, None, &manifest.dev_dependencies),
+                (DependencyKind::Build, None, &manifest.build_dependencies),
+            ];
+            for (target, deps) in &manifest.target {
+                tables.push((DependencyKind::Normal, Some(target), &deps.dependencies));
+                tables.push((DependencyKind::Dev, Some(target), &deps.dev_dependencies));
+                tables.push((DependencyKind::Build, Some(target), &deps.build_dependencies));
+            }
+            for (kind, target, deps) in tables {
+                for (name, dependency) in deps {
+                    let (source, workspace_inherited) = match dependency {
+                        Dependency::Inherited(_) => match workspace_dependencies.get(name) {
+                            Some(inherited) => (classify_source(inherited, workspace_root), true),
+                            None => (DependencySource::Unresolved, true),
+                        },
+                        _ => (classify_source(dependency, dir), false),
+                    };
+                    report.record(DeclaredDependency { name: name.clone(), member: member.clone(), kind, target: target.cloned(), source, workspace_inherited });
+                }
+            }
+            if manifest.package.is_some() {
+                roots.insert(member);
+            }
+        }
+
+        let lock_path = workspace_root.join("Cargo.lock");
+        if lock_path.is_file() {
+            report.add_lockfile(&Lockfile::from_path(lock_path)?, &roots);
+        }
+        Ok(report)
+    }
+
+    fn record(&mut self, dependency: DeclaredDependency) {
+        match (dependency.target.is_some(), dependency.kind) {
+            (true, _) => self.kinds.target_specific += 1,
+            (false, DependencyKind::Normal) => self.kinds.normal += 1,
+            (false, DependencyKind::Dev) => self.kinds.dev += 1,
+            (false, DependencyKind::Build) => self.kinds.build += 1,
+        }
+        match dependency.source {
+            DependencySource::Registry { .. } => self.sources.registry += 1,
+            DependencySource::Git { .. } => self.sources.git += 1,
+            DependencySource::Path { .. } => self.sources.path += 1,
+            DependencySource::Unresolved => {}
+        }
+        if dependency.workspace_inherited {
+            self.sources.workspace_inherited += 1;
+        }
+        self.declared.push(dependency);
+    }
+
+    /// The git, path and unresolved dependencies, which reviewers want to keep an eye on.
+    pub fn non_registry(&self) -> Vec<&DeclaredDependency> {
+        self.declared.iter().filter(|dependency| !matches!(dependency.source, DependencySource::Registry { .. })).collect()
+    }
+
+    fn add_lockfile(&mut self, lockfile: &Lockfile, root_names: &BTreeSet<String>) {
+        self.has_lockfile = true;
+        let roots = lockfile.packages.iter().enumerate()
+            .filter(|(_, package)| package.source.is_none() && root_names.contains(&package.name))
+            .map(|(index, _)| index)
+            .collect::<Vec<_>>();
+
+        // Breadth first, so every crate gets the depth of its shortest path from the project.
+        let mut depths = BTreeMap::new();
+        let mut queue = VecDeque::new();
+        for &root in &roots {
+            depths.insert(root, 0);
+            queue.push_back(root);
+        }
+        while let Some(index) = queue.pop_front() {
+            let depth = depths[&index];
+            for dependency in lockfile.dependencies_of(index) {
+                if let Entry::Vacant(entry) = depths.entry(dependency) {
+                    entry.insert(depth + 1);
+                    queue.push_back(dependency);
+                }
+            }
+        }
+
+        self.direct = depths.values().filter(|depth| **depth == 1).count();
+        self.transitive = depths.values().filter(|depth| **depth > 1).count();
+        self.max_depth = depths.values().copied().max().unwrap_or(0);
+        self.resolved = depths.iter()
+            .filter(|(_, depth)| **depth > 0)
+            .map(|(index, _)| lockfile.packages[*index].clone())
+            .collect();
+
+        let mut versions: BTreeMap<&str, Vec<String>> = BTreeMap::new();
+        for &index in depths.keys() {
+            let package = &lockfile.packages[index];
+            versions.entry(&package.name).or_default().push(package.version.clone());
+        }
+        self.duplicates = versions.into_iter()
+            .filter(|(_, versions)| versions.len() > 1)
+            .map(|(name, versions)| DuplicateCrate { name: name.to_string(), versions })
+            .collect();
+    }
+
+    /// The folders of every path dependency, and of the path dependencies those declare in turn,
+    /// for `SourceLocator::with_path_dirs`. Folders whose manifest cannot be read are kept; their
+    /// own dependencies are just not followed.
+    pub fn path_dependency_dirs(&self) -> Vec<PathBuf> {
+        let mut queue = self.declared.iter()
+            .filter_map(|dependency| match &dependency.source {
+                DependencySource::Path { path } => Some(path.clone()),
+                _ => None,
+            })
+            .collect::<VecDeque<_>>();
+        let mut dirs = BTreeSet::new();
+        while let Some(dir) = queue.pop_front() {
+            if !dirs.insert(dir.clone()) {
+                continue;
+            }
+            let Ok(manifest) = read_raw_manifest(&dir) else { continue };
+            for dependency in manifest.dependencies.values().chain(manifest.build_dependencies.values()) {
+                if let DependencySource::Path { path } = classify_source(dependency, &dir) {
+                    queue.push_back(path);
+                }
+            }
+        }
+        dirs.into_iter().collect()
+    }
+
+    /// Every crate in the build, the project included: the whole resolved graph when there is a
+    /// `Cargo.lock`, the declared dependencies otherwise.
+    pub fn crate_count(&self) -> usize {
+        self.dependency_count() + 1
+    }
+
+    /// Every crate in the build except the project itself.
+    pub fn dependency_count(&self) -> usize {
+        if self.has_lockfile {
+            self.direct + self.transitive
+        } else {
+            self.kinds.total()
+        }
+    }
+}
+
+/// The folder cargo keeps its caches in: `$CARGO_HOME`, or `.cargo` in the home folder.
+pub fn cargo_home() -> Option<PathBuf> {
+    if let Some(cargo_home) = env::var_os("CARGO_HOME") {
+        return Some(PathBuf::from(cargo_home));
+    }
+    env::var_os("HOME")
+        .or_else(|| env::var_os("USERPROFILE"))
+        .map(|home| PathBuf::from(home).join(".cargo"))
+}
+
+/// `SourceLocator` 🗺️ - Finds the sources of locked packages on disk, without any network.
+///
+/// Path dependencies, which `Cargo.lock` lists without a `source`, are looked up among
+/// `path_dirs` by the name and version in their `Cargo.toml`. Then a `cargo vendor` folder is
+/// searched (`vendor/<name>` or `vendor/<name>-<version>`), then the sources cargo already
+/// unpacked in `$CARGO_HOME/registry/src/<index>/<name>-<version>`. Packages that were never
+/// downloaded, such as git dependencies, are not found.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct SourceLocator {
+    pub vendor_dir: Option<PathBuf>,
+    pub registry_src: Option<PathBuf>,
+    pub path_dirs: Vec<PathBuf>,
+}
+
+impl SourceLocator {
+    /// Looks in `vendor_dir`, if given, and in the registry cache of `cargo_home()`.
+    pub fn new(vendor_dir: Option<PathBuf>) -> Self {
+        SourceLocator { vendor_dir, registry_src: cargo_home().map(|home| home.join("registry").join("src")), path_dirs: Vec::new() }
+    }
+
+    /// Looks for path dependencies in these folders too, see `DependencyReport::path_dependency_dirs`.
+    pub fn with_path_dirs(mut self, path_dirs: Vec<PathBuf>) -> Self {
+        self.path_dirs = path_dirs;
+        self
+    }
+
+    pub fn locate(&self, package: &LockedPackage) -> Option<PathBuf> {
+        if package.source.is_none() {
+            let found = self.path_dirs.iter().find(|dir| {
+                manifest_name(dir).as_deref() == Some(package.name.as_str())
+                    && manifest_version(dir).is_none_or(|version| version == package.version)
+            });
+            if found.is_some() {
+                return found.cloned();
+            }
+        }
+        let versioned = format!("{}-{}", package.name, package.version);
+        if let Some(vendor_dir) = &self.vendor_dir {
+            let found = [vendor_dir.join(&versioned), vendor_dir.join(&package.name)]
+                .into_iter()
+                .find(|dir| manifest_version(dir).as_deref() == Some(package.version.as_str()));
+            if found.is_some() {
+                return found;
+            }
+        }
+        let registry_src = self.registry_src.as_ref()?;
+        let mut indices = fs::read_dir(registry_src).ok()?.filter_map(Result::ok).map(|entry| entry.path()).collect::<Vec<_>>();
+        indices.sort();
+        indices.into_iter().map(|index| index.join(&versioned)).find(|dir| dir.join("Cargo.toml").is_file())
+    }
+}
+
+fn manifest_name(dir: &Path) -> Option<String> {
+    Some(Manifest::from_slice(&fs::read(dir.join("Cargo.toml")).ok()?).ok()?.package?.name)
+}
+
+fn manifest_version(dir: &Path) -> Option<String> {
+    let manifest = Manifest::from_slice(&fs::read(dir.join("Cargo.toml")).ok()?).ok()?;
+    manifest.package?.version.get().ok().cloned()
+}
+
+/// The lines of code and the unsafe surface of one dependency, when its sources were found.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CrateLines {
+    pub name: String,
+    pub version: String,
+    pub source_dir: Option<PathBuf>,
+    pub line_counts: LineCounts,
+    pub unsafe_code: UnsafeStats,
+}
+
+/// `DependencyLines` 🏔️ - How much code the project pulls in, crate by crate.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct DependencyLines {
+    pub crates: Vec<CrateLines>,
+}
+
+impl DependencyLines {
+    /// The lines of every dependency whose sources were found.
+    pub fn total(&self) -> LineCounts {
+        self.crates.iter().map(|krate| krate.line_counts).sum()
+    }
+
+    /// The unsafe code of every dependency whose sources were found.
+    pub fn unsafe_code(&self) -> UnsafeStats {
+        self.crates.iter().map(|krate| krate.unsafe_code).sum()
+    }
+
+    /// Dependencies whose sources are not on disk, and are therefore missing from `total`.
+    pub fn missing(&self) -> Vec<&CrateLines> {
+        self.crates.iter().filter(|krate| krate.source_dir.is_none()).collect()
+    }
+}
+
+/// Reads a manifest as written, without cargo's inheritance and target discovery applied, so that
+/// `workspace = true` entries are still recognisable.
+fn read_raw_manifest(dir: &Path) -> Result<Manifest, LocCounterError> {
+    let manifest_path = dir.join("Cargo.toml");
+    fs::read(&manifest_path)
+        .map_err(LocCounterError::IoError)
+        .and_then(|contents| Manifest::from_slice(&contents).map_err(|err| LocCounterError::TomlError(format!("{}: {}", manifest_path.display(), err))))
+}
+
+/// Classifies a dependency entry; relative paths are resolved against `base_dir`, the folder
+/// of the manifest the entry was written in.
+fn classify_source(dependency: &Dependency, base_dir: &Path) -> DependencySource {
+    match dependency {
+        Dependency::Simple(version) => DependencySource::Registry { version: version.clone(), registry: None },
+        Dependency::Inherited(_) => DependencySource::Unresolved,
+        Dependency::Detailed(detail) => {
+            if let Some(url) = &detail.git {
+                let reference = detail.rev.clone().or_else(|| detail.tag.clone()).or_else(|| detail.branch.clone());
+                DependencySource::Git { url: url.clone(), reference }
+            } else if let Some(path) = &detail.path {
+                DependencySource::Path { path: base_dir.join(path) }
+            } else {
+                DependencySource::Registry {
+                    version: detail.version.clone().unwrap_or_else(|| "*".to_string()),
+                    registry: detail.registry.clone().or_else(|| detail.registry_index.clone()),
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_lockfile_graph_is_analyzed() {
+        let dir = std::env::temp_dir().join(format!("countroo-deps-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(dir.join("src")).unwrap();
+        fs::write(dir.join("src/lib.rs"), "").unwrap();
+        fs::write(dir.join("Cargo.toml"), r#"
+[package]
+name = "app"
+version = "0.1.0"
+
+[dependencies]
+a = "1"
+b = "1"
+
+[dev-dependencies]
+c = "1"
+
+[build-dependencies]
+d = "1"
+
+[target.'cfg(windows)'.dependencies]
+winapi = "0.3"
+"#).unwrap();
+        fs::write(dir.join("Cargo.lock"), r#"
+version = 3
+
+[[package]]
+name = "app"
+version = "0.1.0"
+dependencies = ["a", "b", "c"]
+
+[[package]]
+name = "a"
+version = "1.0.0"
+source = "registry+https://github.com/rust-lang/crates.io-index"
+dependencies = ["shared 1.0.0"]
+
+[[package]]
+name = "b"
+version = "1.0.0"
+source = "registry+https://github.com/rust-lang/crates.io-index"
+dependencies = ["shared 2.0.0"]
+
+[[package]]
+name = "c"
+version = "1.0.0"
+source = "registry+https://github.com/rust-lang/crates.io-index"
+
+[[package]]
+name = "shared"
+version = "1.0.0"
+source = "registry+https://github.com/rust-lang/crates.io-index"
+
+[[package]]
+name = "shared"
+version = "2.0.0"
+source = "registry+https://github.com/rust-lang/crates.io-index"
+dependencies = ["leaf"]
+
+[[package]]
+name = "leaf"
+version = "0.1.0"
+source = "registry+https://github.com/rust-lang/crates.io-index"
+"#).unwrap();
+
+        let report = DependencyReport::analyze(std::slice::from_ref(&dir), &dir).unwrap();
+        assert_eq!(report.kinds, DependencyKinds { normal: 2, dev: 1, build: 1, target_specific: 1 });
+        assert_eq!((report.direct, report.transitive, report.max_depth), (3, 3, 3));
+        assert_eq!(report.duplicates, vec![DuplicateCrate { name: "shared".to_string(), versions: vec!["1.0.0".to_string(), "2.0.0".to_string()] }]);
+        assert_eq!(report.crate_count(), 7);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_dependency_sources_are_classified() {
+        let root = std::env::temp_dir().join(format!("countroo-dep-sources-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&root);
+        fs::create_dir_all(root.join("crates/app")).unwrap();
+        fs::write(root.join("Cargo.toml"), r#"
+[workspace]
+members = ["crates/*"]
+
+[workspace.dependencies]
+serde = "1"
+shared = { path = "crates/shared" }
+"#).unwrap();
+        fs::write(root.join("crates/app/Cargo.toml"), r#"
+[package]
+name = "app"
+version = "0.1.0"
+
+[dependencies]
+serde = { workspace = true }
+shared.workspace = true
+forked = { git = "https://example.com/forked.git", tag = "v1" }
+local = { path = "../local", version = "0.1" }
+missing = { workspace = true }
+
+[target.'cfg(unix)'.dependencies]
+libc = { version = "0.2", registry = "mirror" }
+"#).unwrap();
+
+        let report = DependencyReport::analyze(&[root.join("crates/app")], &root).unwrap();
+        assert_eq!(report.sources, SourceCounts { registry: 2, git: 1, path: 2, workspace_inherited: 3 });
+        assert_eq!(report.kinds, DependencyKinds { normal: 5, dev: 0, build: 0, target_specific: 1 });
+        assert!(!report.has_lockfile);
+
+        let non_registry: Vec<(&str, String)> = report.non_registry().iter().map(|d| (d.name.as_str(), d.source.to_string())).collect();
+        assert_eq!(non_registry, vec![
+            ("forked", "git https://example.com/forked.git @ v1".to_string()),
+            ("local", format!("path {}", root.join("crates/app/../local").display())),
+            ("missing", "unresolved workspace dependency".to_string()),
+            ("shared", format!("path {}", root.join("crates/shared").display())),
+        ]);
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn test_dependency_sources_are_found_offline() {
+        let root = std::env::temp_dir().join(format!("countroo-dep-locator-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&root);
+        let crates = [
+            ("vendor/serde", "serde", "1.0.0"),
+            ("registry/src/index.crates.io-1234/serde-1.0.0", "serde", "1.0.0"),
+            ("registry/src/index.crates.io-1234/libc-0.2.1", "libc", "0.2.1"),
+        ];
+        for (dir, name, version) in crates {
+            fs::create_dir_all(root.join(dir)).unwrap();
+            fs::write(root.join(dir).join("Cargo.toml"), format!("[package]\nname = \"{}\"\nversion = \"{}\"\n", name, version)).unwrap();
+        }
+
+        let locator = SourceLocator { vendor_dir: Some(root.join("vendor")), registry_src: Some(root.join("registry/src")), path_dirs: Vec::new() };
+        let package = |name: &str, version: &str| LockedPackage { name: name.to_string(), version: version.to_string(), source: None, dependencies: Vec::new() };
+        assert_eq!(locator.locate(&package("serde", "1.0.0")), Some(root.join("vendor/serde")));
+        assert_eq!(locator.locate(&package("libc", "0.2.1")), Some(root.join("registry/src/index.crates.io-1234/libc-0.2.1")));
+        assert_eq!(locator.locate(&package("libc", "0.3.0")), None);
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn test_path_dependencies_are_found_where_they_are_declared() {
+        let root = std::env::temp_dir().join(format!("countroo-dep-paths-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&root);
+        let manifests = [
+            ("app", "[package]\nname = \"app\"\nversion = \"0.1.0\"\n\n[dependencies]\nhelper = { path = \"../helper\" }\n"),
+            ("helper", "[package]\nname = \"helper\"\nversion = \"0.2.0\"\n\n[dependencies]\ninner = { path = \"../inner\" }\n"),
+            ("inner", "[package]\nname = \"inner\"\nversion = \"0.3.0\"\n"),
+        ];
+        for (dir, manifest) in manifests {
+            fs::create_dir_all(root.join(dir)).unwrap();
+            fs::write(root.join(dir).join("Cargo.toml"), manifest).unwrap();
+        }
+
+        let report = DependencyReport::analyze(&[root.join("app")], &root.join("app")).unwrap();
+        let dirs = report.path_dependency_dirs();
+        assert_eq!(dirs, vec![root.join("app/../helper"), root.join("app/../helper/../inner")]);
+        let locator = SourceLocator { vendor_dir: None, registry_src: None, path_dirs: dirs };
+        let package = |name: &str, version: &str| LockedPackage { name: name.to_string(), version: version.to_string(), source: None, dependencies: Vec::new() };
+        assert_eq!(locator.locate(&package("inner", "0.3.0")), Some(root.join("app/../helper/../inner")));
+        assert_eq!(locator.locate(&package("helper", "0.2.0")), Some(root.join("app/../helper")));
+        assert_eq!(locator.locate(&package("helper", "9.9.9")), None);
+        fs::remove_dir_all(&root).unwrap();
+    }
+}
@@ -0,0 +1,170 @@
+use std::collections::BTreeSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use crate::complexity::{ComplexityReport, FunctionComplexity};
+use crate::module_tree::{ModuleTree, TargetKind};
+
+/// Functions longer than this many lines are called out, after the usual "no function over 80
+/// lines" guidance.
+pub const LONG_FUNCTION_LINES: usize = 80;
+
+/// The upper bounds of the function length histogram; the last bucket holds everything above.
+pub const LENGTH_BUCKETS: [usize; 5] = [10, 20, 40, 80, 160];
+
+/// The upper bounds of the nesting depth histogram; the last bucket holds everything above.
+pub const NESTING_BUCKETS: [usize; 5] = [0, 1, 2, 3, 4];
+
+/// One bar of a histogram: the values from `from` up to and including `to`, or everything from
+/// `from` on when `to` is `None`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "json-output", derive(schemars::JsonSchema))]
+pub struct Bucket {
+    pub from: usize,
+    pub to: Option<usize>,
+    pub count: usize,
+}
+
+impl Bucket {
+    /// `0`, `11-20` or `161+`.
+    pub fn label(&self) -> String {
+        match self.to {
+            Some(to) if to == self.from => to.to_string(),
+            Some(to) => format!("{}-{}", self.from, to),
+            None => format!("{}+", self.from),
+        }
+    }
+}
+
+/// `Distribution` 📊 - How a set of values is spread: percentiles and a histogram.
+///
+/// Percentiles use the nearest-rank method, so they are always one of the values themselves.
+///
+/// ## Example Usage
+/// ```rust
+/// use countroo::prelude::*;
+/// let lengths = Distribution::new(vec![3, 12, 25, 90], &LENGTH_BUCKETS);
+/// assert_eq!(lengths.percentile(50.0), 12);
+/// assert_eq!(lengths.max, 90);
+/// assert_eq!(lengths.histogram[4].label(), "81-160");
+/// ```
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct Distribution {
+    /// The values, sorted.
+    pub values: Vec<usize>,
+    pub max: usize,
+    pub mean: f64,
+    pub histogram: Vec<Bucket>,
+}
+
+impl Distribution {
+    pub fn new(mut values: Vec<usize>, bounds: &[usize]) -> Self {
+        values.sort_unstable();
+        let max = values.last().copied().unwrap_or(0);
+        let mean = if values.is_empty() { 0.0 } else { values.iter().sum::<usize>() as f64 / values.len() as f64 };
+
+        let mut histogram = Vec::new();
+        let mut from = 0;
+        for &to in bounds {
+            histogram.push(Bucket { from, to: Some(to), count: values.iter().filter(|&&value| value >= from && value <= to).count() });
+            from = to + 1;
+        }
+        histogram.push(Bucket { from, to: None, count: values.iter().filter(|&&value| value >= from).count() });
+
+        Distribution { values, max, mean, histogram }
+    }
+
+    pub fn count(&self) -> usize {
+        self.values.len()
+    }
+
+    /// The smallest value that at least `percent`% of the values are less than or equal to.
+    pub fn percentile(&self, percent: f64) -> usize {
+        if self.values.is_empty() {
+            return 0;
+        }
+        let rank = ((percent / 100.0) * self.values.len() as f64).ceil() as usize;
+        self.values[rank.clamp(1, self.values.len()) - 1]
+    }
+}
+
+/// The function lengths and nesting depths of one crate target.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CrateFunctionSizes {
+    pub name: String,
+    pub kind: TargetKind,
+    pub lengths: Distribution,
+    pub nesting: Distribution,
+    /// Functions spanning more than `LONG_FUNCTION_LINES` lines, longest first.
+    pub long_functions: Vec<FunctionComplexity>,
+}
+
+/// `FunctionSizes` 📏 - How long and how deeply nested the Rust functions are, per crate target
+/// and for the project as a whole.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct FunctionSizes {
+    pub lengths: Distribution,
+    pub nesting: Distribution,
+    pub long_functions: Vec<FunctionComplexity>,
+    pub crates: Vec<CrateFunctionSizes>,
+}
+
+impl FunctionSizes {
+    /// Builds the distributions from the measured functions. A function belongs to every target
+    /// whose module tree compiles its file; functions of files no target reaches only count
+    /// towards the project.
+    pub fn new(complexity: &ComplexityReport, module_tree: &ModuleTree) -> Self {
+        let all = complexity.functions().collect::<Vec<_>>();
+        let crates = module_tree.crates.iter()
+            .map(|crate_modules| {
+                let files = crate_modules.files().iter().map(|file| canonical(file)).collect::<BTreeSet<_>>();
+                let functions = all.iter().copied().filter(|function| files.contains(&canonical(&function.file))).collect::<Vec<_>>();
+                let (lengths, nesting, long_functions) = distributions(&functions);
+                CrateFunctionSizes { name: crate_modules.target.name.clone(), kind: crate_modules.target.kind, lengths, nesting, long_functions }
+            })
+            .filter(|sizes| sizes.lengths.count() > 0)
+            .collect();
+        let (lengths, nesting, long_functions) = distributions(&all);
+        FunctionSizes { lengths, nesting, long_functions, crates }
+    }
+}
+
+fn distributions(functions: &[&FunctionComplexity]) -> (Distribution, Distribution, Vec<FunctionComplexity>) {
+    let lengths = Distribution::new(functions.iter().map(|function| function.lines).collect(), &LENGTH_BUCKETS);
+    let nesting = Distribution::new(functions.iter().map(|function| function.max_nesting).collect(), &NESTING_BUCKETS);
+    let mut long_functions = functions.iter()
+        .filter(|function| function.lines > LONG_FUNCTION_LINES)
+        .map(|function| (*function).clone())
+        .collect::<Vec<_>>();
+    long_functions.sort_by_key(|function| std::cmp::Reverse(function.lines));
+    (lengths, nesting, long_functions)
+}
+
+fn canonical(path: &Path) -> PathBuf {
+    fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_distribution_percentiles_and_histogram() {
+        let lengths = Distribution::new((1..=100).rev().collect(), &LENGTH_BUCKETS);
+        assert_eq!(lengths.values[0], 1);
+        assert_eq!((lengths.percentile(50.0), lengths.percentile(90.0), lengths.percentile(100.0)), (50, 90, 100));
+        assert_eq!(lengths.mean, 50.5);
+        let counts: Vec<(String, usize)> = lengths.histogram.iter().map(|bucket| (bucket.label(), bucket.count)).collect();
+        assert_eq!(counts, vec![
+            ("0-10".to_string(), 10), ("11-20".to_string(), 10), ("21-40".to_string(), 20),
+            ("41-80".to_string(), 40), ("81-160".to_string(), 20), ("161+".to_string(), 0),
+        ]);
+
+        let nesting = Distribution::new(vec![0, 0, 1, 7], &NESTING_BUCKETS);
+        assert_eq!(nesting.histogram[0].label(), "0");
+        assert_eq!(nesting.histogram.last().map(|bucket| (bucket.label(), bucket.count)), Some(("5+".to_string(), 1)));
+        assert_eq!(Distribution::new(Vec::new(), &NESTING_BUCKETS).percentile(90.0), 0);
+    }
+}
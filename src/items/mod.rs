@@ -0,0 +1,182 @@
+use std::ops::{Add, AddAssign};
+
+use serde::{Deserialize, Serialize};
+use syn::{ImplItem, Item, TraitItem, Visibility};
+
+/// How many items of one kind there are, split by visibility. Only a plain `pub` counts as
+/// public; `pub(crate)`, `pub(super)` and friends are private to the outside world.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "json-output", derive(schemars::JsonSchema))]
+pub struct ItemCount {
+    pub public: usize,
+    pub private: usize,
+}
+
+impl ItemCount {
+    pub fn total(&self) -> usize {
+        self.public + self.private
+    }
+
+    fn record(&mut self, public: bool) {
+        if public {
+            self.public += 1;
+        } else {
+            self.private += 1;
+        }
+    }
+}
+
+impl Add for ItemCount {
+    type Output = ItemCount;
+
+    fn add(self, other: ItemCount) -> ItemCount {
+        ItemCount { public: self.public + other.public, private: self.private + other.private }
+    }
+}
+
+/// `ItemInventory` 🗃️ - What a module (or a whole crate) is made of.
+///
+/// - **functions**: Free functions.
+/// - **methods**: Functions inside `impl` blocks and trait definitions. Methods declared in a trait
+///   take the visibility of the trait, and methods of trait impls count as public, since they are
+///   as reachable as the trait and type they connect.
+/// - **structs**, **enums**, **traits** and **type_aliases**: As declared.
+/// - **impls**: `impl` blocks; they have no visibility of their own, so they are not split.
+/// - **macros**: `macro_rules!` definitions, public when marked `#[macro_export]`.
+/// - **constants**: `const` and `static` items.
+///
+/// Only the items of the module itself are counted; nested modules keep their own inventory.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "json-output", derive(schemars::JsonSchema))]
+pub struct ItemInventory {
+    pub functions: ItemCount,
+    pub methods: ItemCount,
+    pub structs: ItemCount,
+    pub enums: ItemCount,
+    pub traits: ItemCount,
+    pub impls: usize,
+    pub macros: ItemCount,
+    pub constants: ItemCount,
+    pub type_aliases: ItemCount,
+}
+
+impl ItemInventory {
+    /// Takes stock of a list of items, such as the contents of a file or an inline module.
+    pub fn from_items(items: &[Item]) -> Self {
+        let mut inventory = ItemInventory::default();
+        for item in items {
+            match item {
+                Item::Fn(item) => inventory.functions.record(is_public(&item.vis)),
+                Item::Struct(item) => inventory.structs.record(is_public(&item.vis)),
+                Item::Enum(item) => inventory.enums.record(is_public(&item.vis)),
+                Item::Type(item) => inventory.type_aliases.record(is_public(&item.vis)),
+                Item::Const(item) => inventory.constants.record(is_public(&item.vis)),
+                Item::Static(item) => inventory.constants.record(is_public(&item.vis)),
+                Item::Trait(item) => {
+                    let public = is_public(&item.vis);
+                    inventory.traits.record(public);
+                    for trait_item in &item.items {
+                        if let TraitItem::Fn(_) = trait_item {
+                            inventory.methods.record(public);
+                        }
+                    }
+                }
+                Item::Impl(item) => {
+                    inventory.impls += 1;
+                    for impl_item in &item.items {
+                        if let ImplItem::Fn(method) = impl_item {
+                            inventory.methods.record(item.trait_.is_some() || is_public(&method.vis));
+                        }
+                    }
+                }
+                Item::Macro(item) if item.mac.path.is_ident("macro_rules") => {
+                    inventory.macros.record(item.attrs.iter().any(|attr| attr.path().is_ident("macro_export")));
+                }
+                _ => {}
+            }
+        }
+        inventory
+    }
+
+    /// Rows for a report: the kind of item, its public/private split when it has one, and the total.
+    pub fn rows(&self) -> Vec<(&'static str, Option<ItemCount>, usize)> {
+        let split = |name, count: ItemCount| (name, Some(count), count.total());
+        vec![
+            split("Functions", self.functions),
+            split("Methods", self.methods),
+            split("Structs", self.structs),
+            split("Enums", self.enums),
+            split("Traits", self.traits),
+            ("Impl Blocks", None, self.impls),
+            split("Macros", self.macros),
+            split("Consts & Statics", self.constants),
+            split("Type Aliases", self.type_aliases),
+        ]
+    }
+}
+
+impl Add for ItemInventory {
+    type Output = ItemInventory;
+
+    fn add(self, other: ItemInventory) -> ItemInventory {
+        ItemInventory {
+            functions: self.functions + other.functions,
+            methods: self.methods + other.methods,
+            structs: self.structs + other.structs,
+            enums: self.enums + other.enums,
+            traits: self.traits + other.traits,
+            impls: self.impls + other.impls,
+            macros: self.macros + other.macros,
+            constants: self.constants + other.constants,
+            type_aliases: self.type_aliases + other.type_aliases,
+        }
+    }
+}
+
+impl AddAssign for ItemInventory {
+    fn add_assign(&mut self, other: ItemInventory) {
+        *self = *self + other;
+    }
+}
+
+fn is_public(vis: &Visibility) -> bool {
+    matches!(vis, Visibility::Public(_))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_items_are_counted_by_kind_and_visibility() {
+        let file: syn::File = syn::parse_str(r#"
+            pub fn public() {}
+            pub(crate) fn crate_only() {}
+            fn private() {}
+            pub struct S;
+            struct T;
+            pub enum E { A }
+            pub trait Tr { fn required(&self); fn provided(&self) {} }
+            impl S { pub fn new() -> Self { S } fn helper(&self) {} }
+            impl Tr for S { fn required(&self) {} }
+            #[macro_export]
+            macro_rules! exported { () => {} }
+            macro_rules! local { () => {} }
+            pub const C: u8 = 1;
+            static S2: u8 = 2;
+            type Alias = u8;
+            mod nested { pub fn not_counted_here() {} }
+        "#).unwrap();
+
+        let inventory = ItemInventory::from_items(&file.items);
+        assert_eq!(inventory.functions, ItemCount { public: 1, private: 2 });
+        assert_eq!(inventory.methods, ItemCount { public: 4, private: 1 });
+        assert_eq!(inventory.structs, ItemCount { public: 1, private: 1 });
+        assert_eq!(inventory.enums, ItemCount { public: 1, private: 0 });
+        assert_eq!(inventory.traits, ItemCount { public: 1, private: 0 });
+        assert_eq!(inventory.impls, 2);
+        assert_eq!(inventory.macros, ItemCount { public: 1, private: 1 });
+        assert_eq!(inventory.constants, ItemCount { public: 1, private: 1 });
+        assert_eq!(inventory.type_aliases, ItemCount { public: 0, private: 1 });
+    }
+}
@@ -0,0 +1,568 @@
+use std::fs::File;
+use std::io::{Read, Seek, SeekFrom};
+use std::path::Path;
+
+use serde::Deserialize;
+
+use crate::line_classifier::CommentSyntax;
+
+/// `Language` 🗣️ - Everything `CountRoo` knows about a programming language.
+///
+/// - **name**: The human friendly name shown in reports (`"C++"`, `"Rust"`, `"Makefile"`).
+/// - **extensions**: Lowercase file extensions without the leading dot.
+/// - **filenames**: Exact file names such as `Makefile` or `Cargo.lock`.
+/// - **comments**: The line and block comment markers used by the language.
+/// - **string_delimiters**: Start/end pairs of string literals, so that comment markers inside
+///   strings are not mistaken for comments.
+/// - **interpreters**: Program names found in shebang lines (`#!/usr/bin/env python3`), used to
+///   recognise scripts that have no extension.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Language {
+    pub name: String,
+    pub extensions: Vec<String>,
+    pub filenames: Vec<String>,
+    pub comments: CommentSyntax,
+    pub string_delimiters: Vec<(String, String)>,
+    pub interpreters: Vec<String>,
+}
+
+impl Language {
+    pub fn new(name: &str, extensions: &[&str], filenames: &[&str], comments: CommentSyntax, string_delimiters: &[(&str, &str)]) -> Self {
+        Language {
+            name: name.to_string(),
+            extensions: extensions.iter().map(|e| e.to_lowercase()).collect(),
+            filenames: filenames.iter().map(|f| f.to_string()).collect(),
+            comments,
+            string_delimiters: string_delimiters.iter().map(|(s, e)| (s.to_string(), e.to_string())).collect(),
+            interpreters: Vec::new(),
+        }
+    }
+
+    pub fn with_interpreters(mut self, interpreters: &[&str]) -> Self {
+        self.interpreters = interpreters.iter().map(|i| i.to_string()).collect();
+        self
+    }
+
+    /// Whether `alias` names this language, as used by modelines (`ft=python`, `mode: sh`):
+    /// the language name, one of its extensions or one of its interpreters all qualify.
+    fn is_known_as(&self, alias: &str) -> bool {
+        let alias = alias.to_lowercase();
+        self.name.to_lowercase() == alias
+            || self.extensions.contains(&alias)
+            || self.interpreters.iter().any(|i| i.to_lowercase() == alias)
+    }
+
+    /// A language without any comment or string syntax; every non-blank line is code.
+    pub fn plain(name: &str) -> Self {
+        Language::new(name, &[], &[], CommentSyntax::default(), &[])
+    }
+}
+
+/// `LanguageDefinition` 📝 - A user supplied addition to the built-in language registry.
+///
+/// When `name` matches a built-in language, the extensions and filenames are added to it and any
+/// syntax given here replaces the built-in one. Otherwise a brand new language is registered.
+/// Extensions and filenames claimed here are taken away from every other language.
+///
+/// ## Example (TOML config)
+/// ```toml
+/// [[languages]]
+/// name = "Protobuf"
+/// extensions = ["proto"]
+/// line_comments = ["//"]
+/// block_comments = [["/*", "*/"]]
+/// ```
+///
+/// In a newline separated config the same definition is written on a single line:
+/// `language: Protobuf; extensions = proto; line_comments = //; block_comments = /* */`
+#[derive(Debug, Default, Deserialize, Eq, PartialEq, Clone)]
+pub struct LanguageDefinition {
+    pub name: String,
+    #[serde(default)]
+    pub extensions: Vec<String>,
+    #[serde(default)]
+    pub filenames: Vec<String>,
+    #[serde(default)]
+    pub line_comments: Option<Vec<String>>,
+    #[serde(default)]
+    pub block_comments: Option<Vec<(String, String)>>,
+    #[serde(default)]
+    pub nested_comments: Option<bool>,
+    #[serde(default)]
+    pub string_delimiters: Option<Vec<(String, String)>>,
+    #[serde(default)]
+    pub interpreters: Vec<String>,
+}
+
+impl LanguageDefinition {
+    /// Parses the body of a `language:` line from a newline separated config, i.e. everything
+    /// after the `language:` prefix. Values are whitespace separated; comment and string pairs
+    /// are read two tokens at a time.
+    pub fn from_directive(directive: &str) -> Result<Self, String> {
+        let mut parts = directive.split(';').map(str::trim);
+        let name = parts.next().filter(|n| !n.is_empty())
+            .ok_or_else(|| format!("Language directive without a name: `{}`", directive))?;
+
+        let mut definition = LanguageDefinition { name: name.to_string(), ..Default::default() };
+        for part in parts.filter(|p| !p.is_empty()) {
+            let (key, value) = part.split_once('=')
+                .ok_or_else(|| format!("Expected `key = value` in language directive, found `{}`", part))?;
+            let values: Vec<String> = value.split_whitespace().map(str::to_string).collect();
+            match key.trim() {
+                "extensions" => definition.extensions = values,
+                "filenames" => definition.filenames = values,
+                "interpreters" => definition.interpreters = values,
+                "line_comments" => definition.line_comments = Some(values),
+                "block_comments" => definition.block_comments = Some(Self::pairs(key, values)?),
+                "string_delimiters" => definition.string_delimiters = Some(Self::pairs(key, values)?),
+                "nested_comments" => definition.nested_comments = Some(value.trim().parse()
+                    .map_err(|_| format!("`nested_comments` must be true or false, found `{}`", value.trim()))?),
+                other => return Err(format!("Unknown language directive key `{}`", other)),
+            }
+        }
+        Ok(definition)
+    }
+
+    fn pairs(key: &str, values: Vec<String>) -> Result<Vec<(String, String)>, String> {
+        if !values.len().is_multiple_of(2) {
+            return Err(format!("`{}` expects start/end pairs", key.trim()));
+        }
+        Ok(values.chunks(2).map(|pair| (pair[0].clone(), pair[1].clone())).collect())
+    }
+}
+
+/// `LanguageRegistry` 📚 - Maps file names and extensions to named languages.
+///
+/// Starts out with a built-in list covering the extensions in `config.txt` plus well known
+/// extensionless files (`Makefile`, `Dockerfile`, `Cargo.lock`, ...), and can be extended from
+/// the `languages` section of a `Config`.
+///
+/// ## Example
+/// ```rust
+/// use countroo::prelude::*;
+/// use std::path::Path;
+/// let registry = LanguageRegistry::builtin();
+/// assert_eq!(registry.for_path(Path::new("src/main.cpp")).unwrap().name, "C++");
+/// assert_eq!(registry.for_path(Path::new("Makefile")).unwrap().name, "Makefile");
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LanguageRegistry {
+    languages: Vec<Language>,
+}
+
+impl Default for LanguageRegistry {
+    fn default() -> Self {
+        LanguageRegistry::builtin()
+    }
+}
+
+impl LanguageRegistry {
+    pub fn builtin() -> Self {
+        let c_style = || CommentSyntax::new(&["//"], &[("/*", "*/")], false);
+        let c_nested = || CommentSyntax::new(&["//"], &[("/*", "*/")], true);
+        let hash = || CommentSyntax::new(&["#"], &[], false);
+        let dashes = || CommentSyntax::new(&["--"], &[("/*", "*/")], false);
+        let semicolon = || CommentSyntax::new(&[";"], &[], false);
+        let percent = || CommentSyntax::new(&["%"], &[], false);
+        let bang = || CommentSyntax::new(&["!"], &[], false);
+        let apostrophe = || CommentSyntax::new(&["'"], &[], false);
+        let markup = || CommentSyntax::new(&[], &[("<!--", "-->")], false);
+        let none = CommentSyntax::default;
+
+        let dq: &[(&str, &str)] = &[("\"", "\"")];
+        let dq_sq: &[(&str, &str)] = &[("\"", "\""), ("'", "'")];
+        let js: &[(&str, &str)] = &[("\"", "\""), ("'", "'"), ("`", "`")];
+        // Triple quotes first, so that a `"""` string that is not a docstring reads as one string.
+        let python: &[(&str, &str)] = &[("\"\"\"", "\"\"\""), ("'''", "'''"), ("\"", "\""), ("'", "'")];
+        let rust: &[(&str, &str)] = &[("r###\"", "\"###"), ("r##\"", "\"##"), ("r#\"", "\"#"), ("\"", "\"")];
+
+        let languages = vec![
+            Language::new("1C Enterprise", &["1sc"], &[], CommentSyntax::new(&["//"], &[], false), dq),
+            Language::new("ABAP", &["abap"], &[], CommentSyntax::new(&["\""], &[], false), &[("'", "'")]),
+            Language::new("ABC", &["abc"], &[], percent(), &[]),
+            Language::new("Ada", &["ada", "adb", "ads"], &[], CommentSyntax::new(&["--"], &[], false), dq),
+            Language::new("AutoHotkey", &["ahk"], &[], CommentSyntax::new(&[";"], &[("/*", "*/")], false), dq),
+            Language::new("API Blueprint", &["apib"], &[], markup(), &[]),
+            Language::new("AppleScript", &["applescript", "scpt"], &[], CommentSyntax::new(&["--", "#"], &[("(*", "*)")], true), dq),
+            Language::new("ActionScript", &["as"], &[], c_style(), dq_sq),
+            Language::new("ASP.NET", &["ascx", "aspx"], &[], CommentSyntax::new(&[], &[("<!--", "-->"), ("<%--", "--%>")], false), &[]),
+            Language::new("Assembly", &["asm", "s", "inx"], &[], semicolon(), dq),
+            Language::new("Awk", &["awk"], &[], hash(), dq).with_interpreters(&["awk", "gawk", "mawk"]),
+            Language::new("BASIC", &["bas"], &[], apostrophe(), dq),
+            Language::new("Shell", &["sh", "bash", "zsh", "ksh", "csh", "fish"], &[".bashrc", ".bash_profile", ".zshrc", ".profile"], hash(), dq_sq).with_interpreters(&["sh", "bash", "zsh", "ksh", "csh", "tcsh", "dash", "ash", "fish"]),
+            Language::new("Batch", &["bat", "cmd"], &[], CommentSyntax::new(&["REM", "rem", "::"], &[], false), &[]),
+            Language::new("BitBake", &["bb"], &[], hash(), dq_sq),
+            Language::new("BCL", &["bcl"], &[], c_style(), dq),
+            Language::new("Boo", &["boo"], &[], CommentSyntax::new(&["#", "//"], &[("/*", "*/")], true), dq_sq),
+            Language::new("C", &["c"], &[], c_style(), dq_sq),
+            // `.h` is shared by C and C++; headers count with the C++ family, next to `.hpp`.
+            Language::new("C++", &["cc", "cp", "cpp", "cxx", "c++", "h", "hh", "hpp", "hxx", "inl", "ipp", "inc", "ino"], &[], c_style(), dq_sq),
+            Language::new("C#", &["cs"], &[], c_style(), dq_sq),
+            Language::new("COBOL", &["cbl"], &[], CommentSyntax::new(&["*>"], &[], false), dq_sq),
+            Language::new("ColdFusion", &["cfm", "cfml"], &[], CommentSyntax::new(&[], &[("<!---", "--->")], true), dq_sq),
+            Language::new("CGI", &["cgi"], &[], hash(), dq_sq),
+            Language::new("Lisp", &["cl", "lisp", "lsp", "el"], &[], CommentSyntax::new(&[";"], &[("#|", "|#")], true), dq),
+            Language::new("Clojure", &["clj", "cljs", "cljc"], &[], semicolon(), dq),
+            Language::new("Apex", &["cls"], &[], c_style(), &[("'", "'")]),
+            Language::new("CMake", &["cmake"], &["CMakeLists.txt"], CommentSyntax::new(&["#"], &[("#[[", "]]")], false), dq),
+            Language::new("CoffeeScript", &["coffee"], &[], CommentSyntax::new(&["#"], &[("###", "###")], false), dq_sq),
+            Language::new("Crystal", &["cr"], &[], hash(), dq).with_interpreters(&["crystal"]),
+            Language::new("CSON", &["cson"], &[], hash(), dq_sq),
+            Language::new("CSS", &["css", "wxss"], &[], CommentSyntax::new(&[], &[("/*", "*/")], false), dq_sq),
+            Language::new("CSV", &["csv"], &[], none(), &[]),
+            Language::new("D", &["d"], &[], CommentSyntax::new(&["//"], &[("/*", "*/"), ("/+", "+/")], true), dq),
+            Language::new("Dart", &["dart"], &[], c_nested(), dq_sq).with_interpreters(&["dart"]),
+            Language::new("Stata", &["do"], &[], CommentSyntax::new(&["//"], &[("/*", "*/")], false), dq),
+            Language::new("Diff", &["diff", "patch"], &[], none(), &[]),
+            Language::new("Module Definition", &["def"], &[], semicolon(), dq),
+            Language::new("DML", &["dml"], &[], c_style(), dq),
+            Language::new("Dockerfile", &["dockerfile"], &["Dockerfile", "Containerfile"], hash(), dq),
+            Language::new("DTD", &["dtd"], &[], markup(), dq_sq),
+            Language::new("Eiffel", &["e"], &[], CommentSyntax::new(&["--"], &[], false), dq),
+            Language::new("EBNF", &["ebnf"], &[], CommentSyntax::new(&[], &[("(*", "*)")], false), dq_sq),
+            Language::new("Ruby HTML", &["erb", "rhtml"], &[], CommentSyntax::new(&[], &[("<!--", "-->"), ("<%#", "%>")], false), &[]),
+            Language::new("Erlang", &["erl", "escript"], &[], percent(), dq).with_interpreters(&["escript"]),
+            Language::new("Elixir", &["ex", "exs"], &[], hash(), dq).with_interpreters(&["elixir"]),
+            Language::new("JavaScript", &["js", "es", "mjs", "cjs"], &[], c_style(), js).with_interpreters(&["node", "nodejs", "deno", "bun"]),
+            Language::new("Fortran", &["f", "f03", "f77", "f90", "f95", "for", "fpp"], &[], bang(), dq_sq),
+            Language::new("F#", &["fs", "fsscript", "fsi", "fsx"], &[], CommentSyntax::new(&["//"], &[("(*", "*)")], true), dq),
+            Language::new("ANTLR", &["g4"], &[], c_style(), &[("'", "'")]),
+            Language::new("Go", &["go"], &[], c_style(), &[("\"", "\""), ("`", "`")]),
+            Language::new("Go Template", &["gotmpl"], &[], CommentSyntax::new(&[], &[("{{/*", "*/}}")], false), &[]),
+            Language::new("Groovy", &["groovy", "gradle"], &["Jenkinsfile"], c_style(), dq_sq).with_interpreters(&["groovy"]),
+            Language::new("GameMaker Script", &["gs"], &[], c_style(), dq),
+            Language::new("Handlebars", &["handlebars", "hbs", "mustache"], &[], CommentSyntax::new(&[], &[("{{!--", "--}}"), ("{{!", "}}")], false), &[]),
+            Language::new("HLSL", &["hlsl"], &[], c_style(), dq),
+            Language::new("Haskell", &["hs", "lhs"], &[], CommentSyntax::new(&["--"], &[("{-", "-}")], true), dq),
+            Language::new("Haxe", &["hx"], &[], c_style(), dq_sq),
+            Language::new("HTML", &["html", "htm", "shtml"], &[], markup(), &[]),
+            Language::new("Clean", &["icl"], &[], c_nested(), dq),
+            Language::new("Imba", &["imba"], &[], CommentSyntax::new(&["#"], &[("###", "###")], false), dq_sq),
+            Language::new("INI", &["ini", "cfg"], &[".editorconfig"], CommentSyntax::new(&[";", "#"], &[], false), &[]),
+            Language::new("Jupyter Notebook", &["ipynb"], &[], none(), &[]),
+            Language::new("Intel Hex", &["int"], &[], none(), &[]),
+            Language::new("J", &["j"], &[], CommentSyntax::new(&["NB."], &[], false), &[]),
+            Language::new("Jade", &["jade"], &[], CommentSyntax::new(&["//-", "//"], &[], false), dq_sq),
+            Language::new("Java", &["java"], &[], c_style(), dq_sq),
+            Language::new("Julia", &["jl"], &[], CommentSyntax::new(&["#"], &[("#=", "=#")], true), dq).with_interpreters(&["julia"]),
+            Language::new("JSON", &["json", "jsonld"], &[".prettierrc", ".babelrc"], none(), dq),
+            Language::new("JSP", &["jsp"], &[], CommentSyntax::new(&[], &[("<%--", "--%>"), ("<!--", "-->")], false), dq),
+            Language::new("JSX", &["jsx"], &[], c_style(), js),
+            Language::new("Kotlin", &["kt", "kts"], &[], c_nested(), dq),
+            Language::new("XML", &["xml", "xsd", "xsl", "xslt", "xaml", "launch", "tmlanguage", "webapp", "wxml"], &[], markup(), &[]),
+            Language::new("Less", &["less"], &[], c_style(), dq_sq),
+            Language::new("Log", &["log"], &[], none(), &[]),
+            Language::new("LiveScript", &["ls"], &[], CommentSyntax::new(&["#"], &[("/*", "*/")], false), dq_sq),
+            Language::new("Lua", &["lua"], &[], CommentSyntax::new(&["--"], &[("--[[", "]]")], false), dq_sq).with_interpreters(&["lua", "luajit"]),
+            Language::new("Objective-C", &["m"], &[], c_style(), dq_sq),
+            Language::new("Objective-C++", &["mm"], &[], c_style(), dq_sq),
+            Language::new("M4", &["m4"], &[], CommentSyntax::new(&["#", "dnl"], &[], false), &[]),
+            Language::new("Makefile", &["mak", "mk"], &["Makefile", "makefile", "GNUmakefile"], hash(), &[]).with_interpreters(&["make", "gmake"]),
+            Language::new("Markdown", &["md", "markdown"], &["README", "CHANGELOG"], markup(), &[]),
+            Language::new("OCaml", &["ml", "mli"], &[], CommentSyntax::new(&[], &[("(*", "*)")], true), dq),
+            Language::new("MLIR", &["mlir"], &[], CommentSyntax::new(&["//"], &[], false), dq),
+            Language::new("Modelica", &["mo"], &[], c_style(), dq),
+            Language::new("Go Module", &["mod"], &["go.mod"], CommentSyntax::new(&["//"], &[], false), dq),
+            Language::new("Troff", &["ms", "t"], &[], CommentSyntax::new(&[".\\\""], &[], false), &[]),
+            Language::new("Movable Type", &["mtml"], &[], markup(), &[]),
+            Language::new("Nunjucks", &["njk"], &[], CommentSyntax::new(&[], &[("{#", "#}")], false), dq_sq),
+            Language::new("Nyquist", &["ny"], &[], semicolon(), dq),
+            Language::new("Oberon", &["oc", "odc"], &[], CommentSyntax::new(&[], &[("(*", "*)")], true), dq),
+            Language::new("Pascal", &["pas", "pp"], &[], CommentSyntax::new(&["//"], &[("{", "}"), ("(*", "*)")], false), &[("'", "'")]),
+            Language::new("PHP", &["php", "php3", "php4", "php5", "phtml"], &[], CommentSyntax::new(&["//", "#"], &[("/*", "*/")], false), dq_sq).with_interpreters(&["php"]),
+            Language::new("Perl", &["pl", "pm", "plx"], &[], CommentSyntax::new(&["#"], &[("=pod", "=cut")], false), dq_sq).with_interpreters(&["perl"]),
+            Language::new("Raku", &["pl6"], &[], hash(), dq_sq).with_interpreters(&["raku", "perl6"]),
+            Language::new("Gettext", &["po", "pot"], &[], hash(), dq),
+            Language::new("POV-Ray", &["pov"], &[], c_style(), dq),
+            Language::new("Clipper", &["prg"], &[], CommentSyntax::new(&["//", "*", "&&"], &[("/*", "*/")], false), dq_sq),
+            Language::new("PostScript", &["ps"], &[], percent(), &[]),
+            Language::new("PowerShell", &["ps1", "psd1", "psm1"], &[], CommentSyntax::new(&["#"], &[("<#", "#>")], false), dq_sq).with_interpreters(&["pwsh", "powershell"]),
+            Language::new("Photoshop", &["psd"], &[], none(), &[]),
+            Language::new("Python", &["py", "pyw", "pyi", "pyx"], &["SConstruct", "SConscript"], CommentSyntax::new(&["#"], &[("\"\"\"", "\"\"\""), ("'''", "'''")], false).docstrings(), python).with_interpreters(&["python", "pypy", "pypy3"]),
+            Language::new("Python Bytecode", &["pyc", "pyd", "pyo"], &[], none(), &[]),
+            Language::new("QML", &["qml"], &[], c_style(), dq_sq),
+            Language::new("R", &["r"], &[], hash(), dq_sq).with_interpreters(&["Rscript"]),
+            Language::new("Ruby", &["rb", "rake", "ru", "thor"], &["Gemfile", "Rakefile", "Vagrantfile", "Podfile"], CommentSyntax::new(&["#"], &[("=begin", "=end")], false), dq_sq).with_interpreters(&["ruby", "jruby"]),
+            Language::new("RJS", &["rjs"], &[], hash(), dq_sq),
+            Language::new("Rust", &["rs"], &[], c_nested(), rust),
+            Language::new("reStructuredText", &["rst"], &[], none(), &[]),
+            Language::new("Rich Text", &["rt"], &[], none(), &[]),
+            Language::new("Sass", &["sass", "scss"], &[], c_style(), dq_sq),
+            Language::new("Scala", &["scala", "sbt"], &[], c_nested(), dq).with_interpreters(&["scala"]),
+            Language::new("Scheme", &["scm"], &[], CommentSyntax::new(&[";"], &[("#|", "|#")], true), dq),
+            Language::new("SaltStack", &["sls"], &[], hash(), dq_sq),
+            Language::new("Smarty", &["smarty"], &[], CommentSyntax::new(&[], &[("{*", "*}")], false), dq_sq),
+            Language::new("Solidity", &["sol"], &[], c_style(), dq_sq),
+            Language::new("SQL", &["sql"], &[], dashes(), &[("'", "'")]),
+            Language::new("Smalltalk", &["st"], &[], CommentSyntax::new(&[], &[("\"", "\"")], false), &[("'", "'")]),
+            Language::new("Stylus", &["styl"], &[], c_style(), dq_sq),
+            Language::new("SystemVerilog", &["sv", "svh"], &[], c_style(), dq),
+            Language::new("Swift", &["swift"], &[], c_nested(), dq).with_interpreters(&["swift"]),
+            Language::new("Tcl", &["tcl"], &[], hash(), dq).with_interpreters(&["tclsh", "wish"]),
+            Language::new("TeX", &["tex"], &[], percent(), &[]),
+            Language::new("Terraform", &["tf", "tfvars"], &[], CommentSyntax::new(&["#", "//"], &[("/*", "*/")], false), dq),
+            Language::new("TOML", &["toml"], &["Cargo.lock", "Pipfile", "poetry.lock"], hash(), &[("\"\"\"", "\"\"\""), ("'''", "'''"), ("\"", "\""), ("'", "'")]),
+            Language::new("TypeScript", &["ts", "mts", "cts"], &[], c_style(), js).with_interpreters(&["ts-node", "tsx"]),
+            Language::new("TSX", &["tsx"], &[], c_style(), js),
+            Language::new("Twig", &["twig"], &[], CommentSyntax::new(&[], &[("{#", "#}")], false), dq_sq),
+            Language::new("Visual Basic", &["vb", "vba", "vbs"], &[], apostrophe(), dq),
+            Language::new("VHDL", &["vhdl"], &[], CommentSyntax::new(&["--"], &[], false), dq),
+            Language::new("Velocity", &["vtl"], &[], CommentSyntax::new(&["##"], &[("#*", "*#")], false), dq_sq),
+            Language::new("Vue", &["vue"], &[], CommentSyntax::new(&["//"], &[("<!--", "-->"), ("/*", "*/")], false), js),
+            Language::new("YAML", &["yaml", "yml"], &[".clang-format"], hash(), dq_sq),
+        ];
+
+        LanguageRegistry { languages }
+    }
+
+    /// Builds the built-in registry and applies a list of user definitions on top of it.
+    pub fn with_definitions(definitions: &[LanguageDefinition]) -> Self {
+        let mut registry = LanguageRegistry::builtin();
+        registry.extend(definitions);
+        registry
+    }
+
+    pub fn languages(&self) -> &[Language] {
+        &self.languages
+    }
+
+    /// Finds a language by name, ignoring case.
+    pub fn get(&self, name: &str) -> Option<&Language> {
+        self.languages.iter().find(|l| l.name.eq_ignore_ascii_case(name))
+    }
+
+    /// Resolves the language of a path, matching exact file names before extensions.
+    pub fn for_path(&self, path: &Path) -> Option<&Language> {
+        self.for_filename(path).or_else(|| self.for_extension(path))
+    }
+
+    pub fn for_filename(&self, path: &Path) -> Option<&Language> {
+        let file_name = path.file_name().and_then(|n| n.to_str())?;
+        self.languages.iter().find(|l| l.filenames.iter().any(|f| f == file_name))
+    }
+
+    pub fn for_extension(&self, path: &Path) -> Option<&Language> {
+        let extension = path.extension().and_then(|e| e.to_str())?.to_lowercase();
+        self.languages.iter().find(|l| l.extensions.contains(&extension))
+    }
+
+    /// Like `for_path`, but extensionless files nobody claims by name are opened and recognised
+    /// by their shebang line or a Vim/Emacs modeline. 🔎
+    pub fn detect(&self, path: &Path) -> Option<&Language> {
+        self.for_path(path).or_else(|| self.for_extensionless(path))
+    }
+
+    /// Opens an extensionless file and looks for a shebang or modeline. Files with an extension
+    /// are never sniffed; their extension is the source of truth.
+    pub fn for_extensionless(&self, path: &Path) -> Option<&Language> {
+        if path.extension().is_some() {
+            return None;
+        }
+        let (head, tail) = read_head_and_tail(path).ok()?;
+        self.for_contents(&head, &tail)
+    }
+
+    /// Recognises a language from the first and last lines of a file: the shebang wins,
+    /// then modelines in the first or last five lines, as Vim itself looks for them.
+    pub fn for_contents(&self, head: &str, tail: &str) -> Option<&Language> {
+        let first_line = head.lines().next().unwrap_or_default();
+        if let Some(interpreter) = shebang_interpreter(first_line) {
+            return self.for_interpreter(&interpreter);
+        }
+        let head_lines = head.lines().take(MODELINE_LINES);
+        let tail_lines = tail.lines().rev().take(MODELINE_LINES);
+        head_lines.chain(tail_lines)
+            .find_map(modeline_language)
+            .and_then(|alias| self.languages.iter().find(|l| l.is_known_as(&alias)))
+    }
+
+    /// Finds the language run by an interpreter, tolerating version suffixes (`python3.11`).
+    pub fn for_interpreter(&self, interpreter: &str) -> Option<&Language> {
+        let unversioned = interpreter.trim_end_matches(|c: char| c.is_ascii_digit() || c == '.' || c == '-');
+        [interpreter, unversioned].into_iter()
+            .find_map(|name| self.languages.iter().find(|l| l.interpreters.iter().any(|i| i == name)))
+    }
+
+    /// Applies user definitions to the registry, see `LanguageDefinition` for the merge rules.
+    pub fn extend(&mut self, definitions: &[LanguageDefinition]) {
+        for definition in definitions {
+            let extensions: Vec<String> = definition.extensions.iter().map(|e| e.trim_start_matches('.').to_lowercase()).collect();
+            for language in self.languages.iter_mut().filter(|l| !l.name.eq_ignore_ascii_case(&definition.name)) {
+                language.extensions.retain(|e| !extensions.contains(e));
+                language.filenames.retain(|f| !definition.filenames.contains(f));
+            }
+
+            let language = match self.languages.iter().position(|l| l.name.eq_ignore_ascii_case(&definition.name)) {
+                Some(index) => &mut self.languages[index],
+                None => {
+                    self.languages.push(Language::plain(&definition.name));
+                    self.languages.last_mut().unwrap()
+                }
+            };
+
+            for extension in extensions {
+                if !language.extensions.contains(&extension) {
+                    language.extensions.push(extension);
+                }
+            }
+            for filename in &definition.filenames {
+                if !language.filenames.contains(filename) {
+                    language.filenames.push(filename.clone());
+                }
+            }
+            if let Some(line_comments) = &definition.line_comments {
+                language.comments.line_comments = line_comments.clone();
+            }
+            if let Some(block_comments) = &definition.block_comments {
+                language.comments.block_comments = block_comments.clone();
+            }
+            if let Some(nested) = definition.nested_comments {
+                language.comments.nested_blocks = nested;
+            }
+            if let Some(string_delimiters) = &definition.string_delimiters {
+                language.string_delimiters = string_delimiters.clone();
+            }
+            for interpreter in &definition.interpreters {
+                if !language.interpreters.contains(interpreter) {
+                    language.interpreters.push(interpreter.clone());
+                }
+            }
+        }
+    }
+}
+
+/// How many lines at the start and end of a file may hold a modeline.
+const MODELINE_LINES: usize = 5;
+/// How many bytes at either end of an extensionless file are read when sniffing its language.
+const SNIFF_BYTES: u64 = 4096;
+
+fn read_head_and_tail(path: &Path) -> std::io::Result<(String, String)> {
+    let mut file = File::open(path)?;
+    let len = file.metadata()?.len();
+
+    let mut head = Vec::new();
+    file.by_ref().take(SNIFF_BYTES).read_to_end(&mut head)?;
+    let mut tail = Vec::new();
+    if len > SNIFF_BYTES {
+        file.seek(SeekFrom::Start(len.saturating_sub(SNIFF_BYTES).max(SNIFF_BYTES)))?;
+        file.read_to_end(&mut tail)?;
+    }
+    Ok((String::from_utf8_lossy(&head).into_owned(), String::from_utf8_lossy(&tail).into_owned()))
+}
+
+/// Extracts the interpreter name from a shebang line, looking through `env` and its flags:
+/// `#!/usr/bin/env -S python3 -u` gives `python3`, `#!/bin/bash -e` gives `bash`.
+fn shebang_interpreter(line: &str) -> Option<String> {
+    let command = line.strip_prefix("#!")?;
+    let mut words = command.split_whitespace();
+    let mut program = words.next()?.rsplit('/').next()?;
+    if program == "env" {
+        program = words.find(|w| !w.starts_with('-') && !w.contains('='))?;
+    }
+    Some(program.to_string())
+}
+
+/// Extracts the file type from a Vim (`vim: set ft=python:`) or Emacs
+/// (`-*- mode: ruby -*-`, `-*- ruby -*-`) modeline.
+fn modeline_language(line: &str) -> Option<String> {
+    if let Some(start) = line.find("-*-") {
+        let rest = &line[start + 3..];
+        let body = &rest[..rest.find("-*-")?];
+        let mode = match body.split(';').find_map(|part| part.trim().strip_prefix("mode:")) {
+            Some(mode) => mode,
+            None if !body.contains(':') => body,
+            None => return None,
+        };
+        return Some(mode.trim().trim_end_matches("-mode").to_string()).filter(|m| !m.is_empty());
+    }
+
+    let marker = ["vim:", "vi:", "ex:"].iter().find_map(|marker| {
+        line.match_indices(marker)
+            .find(|(index, _)| *index == 0 || line[..*index].ends_with(char::is_whitespace))
+            .map(|(index, _)| index + marker.len())
+    })?;
+    line[marker..].split(|c: char| c.is_whitespace() || c == ':')
+        .find_map(|option| ["ft=", "filetype=", "syntax=", "syn="].iter().find_map(|key| option.strip_prefix(key)))
+        .map(str::to_string)
+        .filter(|ft| !ft.is_empty())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_c_family_extensions_group_together() {
+        let registry = LanguageRegistry::builtin();
+        for file in ["a.h", "a.cc", "a.cpp", "a.cxx", "a.hpp", "A.CPP"] {
+            assert_eq!(registry.for_path(Path::new(file)).unwrap().name, "C++", "{}", file);
+        }
+        assert_eq!(registry.for_path(Path::new("a.c")).unwrap().name, "C");
+    }
+
+    #[test]
+    fn test_exact_filenames_are_recognised() {
+        let registry = LanguageRegistry::builtin();
+        assert_eq!(registry.for_path(Path::new("project/Makefile")).unwrap().name, "Makefile");
+        assert_eq!(registry.for_path(Path::new("Dockerfile")).unwrap().name, "Dockerfile");
+        assert_eq!(registry.for_path(Path::new("Cargo.lock")).unwrap().name, "TOML");
+        assert!(registry.for_path(Path::new("LICENSE")).is_none());
+    }
+
+    #[test]
+    fn test_definitions_extend_and_claim_extensions() {
+        let proto = LanguageDefinition::from_directive("Protobuf; extensions = proto inc; line_comments = //").unwrap();
+        let registry = LanguageRegistry::with_definitions(&[proto]);
+        let language = registry.for_path(Path::new("api.proto")).unwrap();
+        assert_eq!(language.name, "Protobuf");
+        assert_eq!(language.comments.line_comments, vec!["//".to_string()]);
+        assert_eq!(registry.for_path(Path::new("header.inc")).unwrap().name, "Protobuf");
+        assert!(!registry.get("C++").unwrap().extensions.contains(&"inc".to_string()));
+    }
+
+    #[test]
+    fn test_shebangs_select_the_interpreter_language() {
+        let registry = LanguageRegistry::builtin();
+        let detect = |head: &str| registry.for_contents(head, "").map(|l| l.name.clone());
+        assert_eq!(detect("#!/usr/bin/env bash\necho hi"), Some("Shell".to_string()));
+        assert_eq!(detect("#!/usr/bin/env python3\nprint()"), Some("Python".to_string()));
+        assert_eq!(detect("#!/usr/bin/env -S python3.11 -u\n"), Some("Python".to_string()));
+        assert_eq!(detect("#!/bin/sh -e\n"), Some("Shell".to_string()));
+        assert_eq!(detect("#!/usr/bin/make -f\n"), Some("Makefile".to_string()));
+        assert_eq!(detect("#!/usr/bin/unknown-tool\n"), None);
+    }
+
+    #[test]
+    fn test_modelines_select_a_language() {
+        let registry = LanguageRegistry::builtin();
+        let detect = |head: &str, tail: &str| registry.for_contents(head, tail).map(|l| l.name.clone());
+        assert_eq!(detect("# vim: set ft=python:\nx = 1", ""), Some("Python".to_string()));
+        assert_eq!(detect("x\n", "echo\n# vim: ts=4 filetype=sh\n"), Some("Shell".to_string()));
+        assert_eq!(detect("; -*- mode: lisp; tab-width: 2 -*-\n", ""), Some("Lisp".to_string()));
+        assert_eq!(detect("# -*- ruby -*-\n", ""), Some("Ruby".to_string()));
+        assert_eq!(detect("# -*- coding: utf-8 -*-\n", ""), None);
+        assert_eq!(detect("this is evim: not a modeline ft=python\n", ""), None);
+    }
+
+    #[test]
+    fn test_extensionless_scripts_are_detected_from_disk() {
+        let dir = std::env::temp_dir().join(format!("countroo-shebang-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let script = dir.join("deploy");
+        std::fs::write(&script, "#!/usr/bin/env bash\nset -e\n").unwrap();
+        let with_extension = dir.join("notes.unknown");
+        std::fs::write(&with_extension, "#!/usr/bin/env bash\n").unwrap();
+
+        let registry = LanguageRegistry::builtin();
+        assert_eq!(registry.detect(&script).unwrap().name, "Shell");
+        assert!(registry.detect(&with_extension).is_none());
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_invalid_directive_is_rejected() {
+        assert!(LanguageDefinition::from_directive("Broken; block_comments = /*").is_err());
+        assert!(LanguageDefinition::from_directive("; extensions = x").is_err());
+        assert!(LanguageDefinition::from_directive("Broken; colour = blue").is_err());
+    }
+}
@@ -1,4 +1,18 @@
 pub mod output_adapters;
+pub mod line_classifier;
+pub mod languages;
+pub mod walker;
+pub mod module_tree;
+pub mod items;
+pub mod test_code;
+pub mod workspace;
+pub mod dependencies;
+pub mod unsafe_code;
+pub mod complexity;
+pub mod function_size;
+pub mod budgets;
+pub mod baseline;
+pub mod report;
 #[cfg(feature = "tabular-output")]
 #[allow(unused_imports)]
 use prettytable::{table, Table, Row, Cell, row};
@@ -6,20 +20,39 @@ use prettytable::{table, Table, Row, Cell, row};
 ///
 /// Imports Section!
 ///
-use std::collections::HashMap;
-use num_format::{Locale, ToFormattedString};
+use std::collections::{BTreeSet, HashMap};
 use std::fs::File;
-use std::io::{self, BufRead, BufReader, Read};
-use walkdir::WalkDir;
+use std::io::{self, Read};
 use std::error::Error;
 use std::{env, fmt};
 use std::fmt::Display;
 use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
 use cargo_toml_workspace::cargo_toml;
 
 use rayon::prelude::*;
 use serde::Deserialize;
 
+use line_classifier::LineCounts;
+use languages::{Language, LanguageDefinition, LanguageRegistry};
+use walker::FileWalker;
+use module_tree::{ModuleStats, ModuleTree};
+use items::ItemInventory;
+use test_code::TestSplit;
+use workspace::{MemberReport, Workspace, WorkspaceReport};
+use dependencies::{CrateLines, DependencyLines, DependencyReport, DependencySource, SourceLocator};
+use unsafe_code::UnsafeStats;
+use complexity::{ComplexityReport, FileComplexity, FunctionComplexity};
+use function_size::{Distribution, FunctionSizes};
+use budgets::{BudgetViolation, Budgets, Measurements};
+use baseline::{Baseline, FunctionBaseline, Regression};
+use report::{
+    ComplexitySummary, CrateFunctionSizesReport, CrateReport, CrateUnsafeReport, DeclaredDependencyReport, DependencyLinesReport, DependencySummary,
+    DistributionReport, FileComplexityReport, FileReport, FunctionComplexityReport, FunctionSizesReport, LanguageReport, LockedPackageReport,
+    ModuleUnsafeReport, OrphanedFilesReport, ProjectReport, Report, SourceKind, Timings, ToolchainReport, UnsafeReport, WorkspaceMemberReport,
+    WorkspaceSummary, REPORT_SCHEMA_VERSION,
+};
+
 #[cfg(feature = "default")]
 use output_adapters::*;
 
@@ -39,7 +72,6 @@ pub const CONFIG_TXT_CONTENTS: &str = include_str!("config.txt");
 /// Custom Traits & Types Section
 ///
 #[cfg(feature = "default")]
-
 pub trait CertainTypesCounter {
     fn count_lines_of_code_for_certain_types(&mut self) -> Result<usize, LocCounterError>;
 }
@@ -70,6 +102,8 @@ pub enum LocCounterError {
     JsonError(String),
     YamlError(String),
     XmlError(String),
+    BudgetError(String),
+    BaselineError(String),
 }
 
 #[cfg(feature = "default")]
@@ -82,6 +116,8 @@ impl fmt::Display for LocCounterError {
             LocCounterError::YamlError(err) => write!(f, "YAML Error: {}", err),
             LocCounterError::XmlError(err) => write!(f, "XML Error: {}", err),
             LocCounterError::TomlError(msg) => write!(f, "TOML Error: {}", msg),
+            LocCounterError::BudgetError(msg) => write!(f, "Budget Error: {}", msg),
+            LocCounterError::BaselineError(msg) => write!(f, "Baseline Error: {}", msg),
         }
     }
 }
@@ -115,6 +151,25 @@ impl From<toml::de::Error> for LocCounterError{
 ///   ensuring nothing important gets overlooked. 📚
 /// - **Count Empty Lines**: A boolean toggle that dictates whether `CountRoo` counts those
 ///   silent, empty lines as part of the total. 📉🚫
+/// - **Languages**: Extra `LanguageDefinition`s layered on top of the built-in
+///   `LanguageRegistry`, teaching `CountRoo` new extensions, file names and comment syntax. 🗣️
+/// - **Respect Ignore Files**: Whether `.gitignore`, `.ignore` and `.countrooignore` files are
+///   honoured while walking the project (on by default), keeping `target/` and friends out. 🙈
+/// - **Include / Exclude**: Glob patterns that narrow the walk down, e.g. only
+///   `crates/*/src/**` but never `**/generated/**` or `*.pb.rs`. They are relative to the project
+///   base, whichever count walks the project. See `FileWalker` for the matching rules. 🎯
+/// - **Exclude Orphaned Files**: Whether `.rs` files that no crate root reaches through its
+///   `mod` declarations are left out of the counts. They are always listed in the report. 🏚️
+/// - **Count Dependency Lines**: Whether the sources of every locked dependency are counted too,
+///   from a `cargo vendor` folder (`vendor_dir`, or `vendor/` next to `Cargo.lock`) or from the
+///   sources cargo already unpacked in `$CARGO_HOME/registry/src`, and path dependencies from
+///   their own folders. Nothing is downloaded. 🏔️
+/// - **Budgets**: Limits on total lines, lines per file and per function, unsafe blocks, the
+///   comment ratio and the dependency count, see `Budgets`. 💰
+/// - **Baseline**: A baseline file to ratchet against, see `Baseline`. Needs the `ratchet`
+///   feature, which is on by default. 📌
+/// - **Most Complex Functions**: How many of the most complex functions, and of the files
+///   holding them, the report lists (`DEFAULT_MOST_COMPLEX_FUNCTIONS` unless set). 🌀
 ///
 /// ## Configuration Flexibility
 /// Enabled with various feature flags, `Config` can morph to understand different configuration
@@ -139,6 +194,38 @@ pub struct Config {
     pub config_path: Option<String>,
     pub extensions: Vec<String>,
     pub count_empty_lines: bool,
+    #[serde(default)]
+    pub languages: Vec<LanguageDefinition>,
+    #[serde(default = "default_respect_ignore_files")]
+    pub respect_ignore_files: bool,
+    #[serde(default)]
+    pub include: Vec<String>,
+    #[serde(default)]
+    pub exclude: Vec<String>,
+    #[serde(default)]
+    pub exclude_orphaned_files: bool,
+    #[serde(default)]
+    pub count_dependency_lines: bool,
+    #[serde(default)]
+    pub vendor_dir: Option<String>,
+    #[serde(default)]
+    pub budgets: Budgets,
+    #[serde(default)]
+    pub baseline: Option<String>,
+    #[serde(default = "default_most_complex_functions")]
+    pub most_complex_functions: usize,
+}
+
+fn default_respect_ignore_files() -> bool {
+    true
+}
+
+/// How many of the most complex functions, and of the files holding them, the report lists
+/// unless `Config::most_complex_functions` says otherwise. 🌀
+pub const DEFAULT_MOST_COMPLEX_FUNCTIONS: usize = 10;
+
+fn default_most_complex_functions() -> usize {
+    DEFAULT_MOST_COMPLEX_FUNCTIONS
 }
 
 
@@ -166,12 +253,32 @@ impl PathHelpers for Config{
                 config_path: Some(p.join(rel_path).to_string_lossy().to_string()),
                 extensions: vec!["rs".to_string()],
                 count_empty_lines: false,
+                languages: Vec::new(),
+                respect_ignore_files: true,
+                include: Vec::new(),
+                exclude: Vec::new(),
+                exclude_orphaned_files: false,
+                count_dependency_lines: false,
+                vendor_dir: None,
+                budgets: Budgets::default(),
+                baseline: None,
+                most_complex_functions: DEFAULT_MOST_COMPLEX_FUNCTIONS,
             })
         } else {
             Err(LocCounterError::ConfigError("Unable to find src folder".to_string()))
         }
     }
      fn from_abs_file_path(config_path: &str, project_path: String) -> Result<Config, LocCounterError> {
+        let config = Self::parse_config_file(config_path, project_path)?;
+        // Catch malformed include/exclude globs here rather than halfway through a count.
+        FileWalker::new(&config)?;
+        Ok(config)
+    }
+}
+
+#[cfg(any(feature = "toml-config", feature = "json-config", feature = "yaml-config", feature = "xml-config", feature = "newline-config"))]
+impl Config {
+    fn parse_config_file(config_path: &str, project_path: String) -> Result<Config, LocCounterError> {
         let extension = Path::new(config_path)
             .extension()
             .and_then(|ext| ext.to_str())
@@ -214,8 +321,7 @@ impl PathHelpers for Config{
 
             #[cfg(feature = "default")]
             "txt" => {
-                let txt: Vec<String> = contents.lines().map(|l| l.to_owned()).collect();
-                Self::from_str_vec(txt, false, project_path)
+                Self::from_line_separated_string(&contents, false, project_path)
             }
             _ => Err(LocCounterError::ConfigError("Unsupported config file type".to_string()))
         }
@@ -234,7 +340,16 @@ impl Config {
     /// ## Parameters
     /// - `config`: A string with each line specifying a file extension to include in the analysis.
     ///   This method treats each line as a separate extension, transforming your string into a
-    ///   ticket for `CountRoo` to explore files of those types.
+    ///   ticket for `CountRoo` to explore files of those types. Lines starting with `language:`
+    ///   define or extend a language instead, see `LanguageDefinition::from_directive`, and a
+    ///   `respect_ignore_files: false` line turns off `.gitignore` handling. `include: <glob>` and
+    ///   `exclude: <glob>` lines add include and exclude patterns. `exclude_orphaned_files: true`
+    ///   leaves `.rs` files that are not part of the module tree out of the counts.
+    ///   `count_dependency_lines: true` counts the lines of the dependencies as well, and
+    ///   `vendor_dir: <path>` points at a `cargo vendor` folder to find them in.
+    ///   Budget lines such as `max_file_lines: 500` or `strict_budgets: true` set `Budgets`, and
+    ///   `baseline: countroo-baseline.json` turns on ratchet mode against that file.
+    ///   `most_complex_functions: 20` lists that many of the most complex functions.
     /// - `count_empty_lines`: A boolean indicating whether to count empty lines within files.
     ///   This toggles whether `CountRoo` counts all lines or just those with content.
     /// - `project_path`: The starting point for `CountRoo`'s adventure, specified as a path
@@ -260,13 +375,64 @@ impl Config {
     /// Utilize `from_line_separated_string` to easily convert a simple string into a complex
     /// `Config` for `CountRoo`, ensuring your analysis is as detailed or as broad as you wish. 🌟🔍
     pub fn from_line_separated_string(config: &str, count_empty_lines: bool, project_path: String) -> Result<Config, LocCounterError> {
-        let extensions: Vec<String> = config.lines().map(|line| line.to_string()).collect();
-        Ok(Config {
+        let mut extensions = Vec::new();
+        let mut languages = Vec::new();
+        let mut respect_ignore_files = true;
+        let mut include = Vec::new();
+        let mut exclude = Vec::new();
+        let mut exclude_orphaned_files = false;
+        let mut count_dependency_lines = false;
+        let mut vendor_dir = None;
+        let mut budgets = Budgets::default();
+        let mut baseline = None;
+        let mut most_complex_functions = DEFAULT_MOST_COMPLEX_FUNCTIONS;
+        for line in config.lines().map(str::trim).filter(|line| !line.is_empty()) {
+            if let Some(pattern) = line.strip_prefix("include:") {
+                include.push(pattern.trim().to_string());
+            } else if let Some(pattern) = line.strip_prefix("exclude:") {
+                exclude.push(pattern.trim().to_string());
+            } else if let Some(directive) = line.strip_prefix("language:") {
+                languages.push(LanguageDefinition::from_directive(directive).map_err(LocCounterError::ConfigError)?);
+            } else if let Some(value) = line.strip_prefix("respect_ignore_files:") {
+                respect_ignore_files = value.trim().parse()
+                    .map_err(|_| LocCounterError::ConfigError(format!("`respect_ignore_files` must be true or false, found `{}`", value.trim())))?;
+            } else if let Some(value) = line.strip_prefix("exclude_orphaned_files:") {
+                exclude_orphaned_files = value.trim().parse()
+                    .map_err(|_| LocCounterError::ConfigError(format!("`exclude_orphaned_files` must be true or false, found `{}`", value.trim())))?;
+            } else if let Some(value) = line.strip_prefix("count_dependency_lines:") {
+                count_dependency_lines = value.trim().parse()
+                    .map_err(|_| LocCounterError::ConfigError(format!("`count_dependency_lines` must be true or false, found `{}`", value.trim())))?;
+            } else if let Some(path) = line.strip_prefix("vendor_dir:") {
+                vendor_dir = Some(path.trim().to_string());
+            } else if let Some(path) = line.strip_prefix("baseline:") {
+                baseline = Some(path.trim().to_string());
+            } else if let Some(value) = line.strip_prefix("most_complex_functions:") {
+                most_complex_functions = value.trim().parse()
+                    .map_err(|_| LocCounterError::ConfigError(format!("`most_complex_functions` must be a whole number, found `{}`", value.trim())))?;
+            } else if budgets.parse_directive(line).map_err(LocCounterError::ConfigError)? {
+                continue;
+            } else {
+                extensions.push(line.to_string());
+            }
+        }
+        let config = Config {
             project_src_path: project_path,
             config_path: None,
             extensions,
             count_empty_lines,
-        })
+            languages,
+            respect_ignore_files,
+            include,
+            exclude,
+            exclude_orphaned_files,
+            count_dependency_lines,
+            vendor_dir,
+            budgets,
+            baseline,
+            most_complex_functions,
+        };
+        FileWalker::new(&config)?;
+        Ok(config)
     }
 
     /// Constructs a `Config` from a vector of file extensions, a flag for counting empty lines,
@@ -312,9 +478,24 @@ impl Config {
             config_path: None,
             extensions,
             count_empty_lines,
+            languages: Vec::new(),
+            respect_ignore_files: true,
+            include: Vec::new(),
+            exclude: Vec::new(),
+            exclude_orphaned_files: false,
+            count_dependency_lines: false,
+            vendor_dir: None,
+            budgets: Budgets::default(),
+            baseline: None,
+            most_complex_functions: DEFAULT_MOST_COMPLEX_FUNCTIONS,
         })
     }
 
+    /// The built-in `LanguageRegistry` extended with the `languages` of this config. 📚
+    pub fn language_registry(&self) -> LanguageRegistry {
+        LanguageRegistry::with_definitions(&self.languages)
+    }
+
     /// Constructs a new `ConfigBuilder` to kickstart your `CountRoo` configuration journey. 🚀🛠️
     ///
     /// This function is your gateway to creating a custom `Config` for `CountRoo`, allowing
@@ -390,6 +571,16 @@ pub struct ConfigBuilder {
     project_path: Option<String>,
     extensions: Vec<String>,
     count_empty_lines: bool,
+    languages: Vec<LanguageDefinition>,
+    respect_ignore_files: Option<bool>,
+    include: Vec<String>,
+    exclude: Vec<String>,
+    exclude_orphaned_files: bool,
+    count_dependency_lines: bool,
+    vendor_dir: Option<String>,
+    budgets: Budgets,
+    baseline: Option<String>,
+    most_complex_functions: Option<usize>,
 }
 
 #[cfg(feature = "default")]
@@ -544,6 +735,191 @@ impl ConfigBuilder {
         self
     }
 
+    /// Teaches `CountRoo` a new language, or new extensions for a known one. 🗣️
+    ///
+    /// ## Example Usage
+    /// ```rust
+    /// use countroo::prelude::*;
+    /// let config = ConfigBuilder::default()
+    ///     .project_path("src")
+    ///     .extension("proto")
+    ///     .language(LanguageDefinition {
+    ///         name: "Protobuf".to_string(),
+    ///         extensions: vec!["proto".to_string()],
+    ///         line_comments: Some(vec!["//".to_string()]),
+    ///         ..Default::default()
+    ///     })
+    ///     .build()
+    ///     .expect("Config build failed");
+    /// ```
+    pub fn language(mut self, definition: LanguageDefinition) -> Self {
+        self.languages.push(definition);
+        self
+    }
+
+    /// Chooses whether `.gitignore`, `.ignore` and `.countrooignore` files are honoured while
+    /// walking the project. They are, unless you say otherwise. 🙈
+    ///
+    /// ## Example Usage
+    /// ```rust
+    /// use countroo::prelude::*;
+    /// let config = ConfigBuilder::default()
+    ///     .project_path("src")
+    ///     .extension("rs")
+    ///     .respect_ignore_files(false) // Count generated and ignored files too
+    ///     .build()
+    ///     .expect("Config build failed");
+    /// ```
+    pub fn respect_ignore_files(mut self, respect: bool) -> Self {
+        self.respect_ignore_files = Some(respect);
+        self
+    }
+
+    /// Only counts files matching this glob; call it repeatedly to include several patterns. 🎯
+    ///
+    /// Patterns are relative to the project path, see `FileWalker` for the matching rules.
+    ///
+    /// ## Example Usage
+    /// ```rust
+    /// use countroo::prelude::*;
+    /// let config = ConfigBuilder::default()
+    ///     .project_path(".")
+    ///     .extension("rs")
+    ///     .include("crates/*/src/**")
+    ///     .build()
+    ///     .expect("Config build failed");
+    /// ```
+    pub fn include(mut self, pattern: &str) -> Self {
+        self.include.push(pattern.to_owned());
+        self
+    }
+
+    /// Leaves out files matching this glob, even when they are included. 🚫
+    ///
+    /// ## Example Usage
+    /// ```rust
+    /// use countroo::prelude::*;
+    /// let config = ConfigBuilder::default()
+    ///     .project_path(".")
+    ///     .extension("rs")
+    ///     .exclude("**/generated/**")
+    ///     .exclude("*.pb.rs")
+    ///     .build()
+    ///     .expect("Config build failed");
+    /// ```
+    pub fn exclude(mut self, pattern: &str) -> Self {
+        self.exclude.push(pattern.to_owned());
+        self
+    }
+
+    /// Leaves `.rs` files that no crate root includes through a `mod` declaration out of the
+    /// counts. They are reported as orphaned either way. 🏚️
+    ///
+    /// ## Example Usage
+    /// ```rust
+    /// use countroo::prelude::*;
+    /// let config = ConfigBuilder::default()
+    ///     .project_path("src")
+    ///     .extension("rs")
+    ///     .exclude_orphaned_files(true)
+    ///     .build()
+    ///     .expect("Config build failed");
+    /// ```
+    pub fn exclude_orphaned_files(mut self, exclude: bool) -> Self {
+        self.exclude_orphaned_files = exclude;
+        self
+    }
+
+    /// Counts the lines of every locked dependency as well, from a vendor folder or the local
+    /// registry cache. Nothing is downloaded; crates that are not on disk are reported as missing. 🏔️
+    ///
+    /// ## Example Usage
+    /// ```rust
+    /// use countroo::prelude::*;
+    /// let config = ConfigBuilder::default()
+    ///     .project_path("src")
+    ///     .extension("rs")
+    ///     .count_dependency_lines(true)
+    ///     .build()
+    ///     .expect("Config build failed");
+    /// ```
+    pub fn count_dependency_lines(mut self, count: bool) -> Self {
+        self.count_dependency_lines = count;
+        self
+    }
+
+    /// Looks for dependency sources in this `cargo vendor` folder before the registry cache. 📦
+    ///
+    /// ## Example Usage
+    /// ```rust
+    /// use countroo::prelude::*;
+    /// let config = ConfigBuilder::default()
+    ///     .project_path("src")
+    ///     .extension("rs")
+    ///     .count_dependency_lines(true)
+    ///     .vendor_dir("vendor")
+    ///     .build()
+    ///     .expect("Config build failed");
+    /// ```
+    pub fn vendor_dir(mut self, path: &str) -> Self {
+        self.vendor_dir = Some(path.to_owned());
+        self
+    }
+
+    /// Sets the budgets the project is checked against after every analysis. 💰
+    ///
+    /// ## Example Usage
+    /// ```rust
+    /// use countroo::prelude::*;
+    /// let config = ConfigBuilder::default()
+    ///     .project_path("src")
+    ///     .extension("rs")
+    ///     .budgets(Budgets { max_function_lines: Some(80), strict: true, ..Budgets::default() })
+    ///     .build()
+    ///     .expect("Config build failed");
+    /// ```
+    pub fn budgets(mut self, budgets: Budgets) -> Self {
+        self.budgets = budgets;
+        self
+    }
+
+    /// Ratchets the project against a baseline file, relative to the project's `Cargo.toml`. The
+    /// file is written on the first run; after that, only regressions are reported. 📌
+    ///
+    /// ## Example Usage
+    /// ```rust
+    /// use countroo::prelude::*;
+    /// let config = ConfigBuilder::default()
+    ///     .project_path("src")
+    ///     .extension("rs")
+    ///     .baseline("countroo-baseline.json")
+    ///     .build()
+    ///     .expect("Config build failed");
+    /// ```
+    pub fn baseline(mut self, path: &str) -> Self {
+        self.baseline = Some(path.to_owned());
+        self
+    }
+
+    /// How many of the most complex functions, and of the files holding them, the report lists;
+    /// `DEFAULT_MOST_COMPLEX_FUNCTIONS` unless set. 🌀
+    ///
+    /// ## Example Usage
+    /// ```rust
+    /// use countroo::prelude::*;
+    /// let config = ConfigBuilder::default()
+    ///     .project_path("src")
+    ///     .extension("rs")
+    ///     .most_complex_functions(25)
+    ///     .build()
+    ///     .expect("Config build failed");
+    /// assert_eq!(config.most_complex_functions, 25);
+    /// ```
+    pub fn most_complex_functions(mut self, count: usize) -> Self {
+        self.most_complex_functions = Some(count);
+        self
+    }
+
     /// Builds the `Config` object from the builder pattern setup. 🏗️🛠️
     ///
     /// Consumes the builder to produce a finalized `Config` instance, encapsulating all
@@ -553,7 +929,8 @@ impl ConfigBuilder {
     ///
     /// ## Returns
     /// - `Ok(Config)`: A shiny new `Config` object ready to guide `CountRoo` on its journey 🧭.
-    /// - `Err(&'static str)`: A not-so-shiny error message indicating what went wrong during the build process. 😓
+    /// - `Err(&'static str)`: A not-so-shiny error message indicating what went wrong during the build process,
+    ///   such as a missing project path, a malformed include/exclude glob or a `NaN` comment ratio. 😓
     ///
     /// ## Example Usage
     /// ```rust
@@ -569,12 +946,24 @@ impl ConfigBuilder {
     /// Ensure your project path is set; otherwise, this build will politely refuse to proceed,
     /// citing a lack of directions. 🚫🗺️
     pub fn build(self) -> Result<Config, &'static str> {
-        Ok(Config {
+        let config = Config {
             project_src_path: self.project_path.ok_or("Project or workspace path is required")?,
             config_path: None,
             extensions: self.extensions,
             count_empty_lines: self.count_empty_lines,
-        })
+            languages: self.languages,
+            respect_ignore_files: self.respect_ignore_files.unwrap_or(true),
+            include: self.include,
+            exclude: self.exclude,
+            exclude_orphaned_files: self.exclude_orphaned_files,
+            count_dependency_lines: self.count_dependency_lines,
+            vendor_dir: self.vendor_dir,
+            budgets: self.budgets,
+            baseline: self.baseline,
+            most_complex_functions: self.most_complex_functions.unwrap_or(DEFAULT_MOST_COMPLEX_FUNCTIONS),
+        };
+        FileWalker::new(&config).map_err(|_| "Invalid include or exclude glob pattern")?;
+        Ok(config)
     }
 }
 
@@ -587,7 +976,7 @@ impl ConfigBuilder {
 ///
 /// ## Functionality
 /// - **export**: Takes your data on a journey from the internal state to an external representation,
-/// allowing it to be written, viewed, and shared far and wide. 🌍
+///   allowing it to be written, viewed, and shared far and wide. 🌍
 ///
 /// ## Requirements
 /// Implementors must define how their data is exported, providing a writer that adheres to the
@@ -711,12 +1100,37 @@ pub trait Analyzable{
 ///
 /// ## Features
 /// - **Total Lines of Code** 📊: Keeps track of the total lines across your project.
+/// - **Line Breakdown** 🧮: Splits those lines into code, comment and blank lines.
 /// - **Number of Files** 🗂️: Counts the files explored in the code safari.
-/// - **Crate Count** 📦: Tallies up the crates your project depends on.
+/// - **Crate Count** 📦: Tallies up the crates your project depends on, transitive ones included.
+/// - **Dependencies** 🔗: Direct and transitive dependencies from `Cargo.lock`, declared ones per
+///   kind (normal, dev, build and target specific) and per source (registry, git, path and
+///   workspace-inherited), duplicated crates and the depth of the tree.
 /// - **Project Name** 🏷️: Captures the name of your project, if available.
 /// - **Rust Edition** 🦀: Identifies the Rust edition your project is using.
 /// - **Rust Compiler Version** 🔧: Determines the version of the Rust compiler (`rustc`) in use.
 /// - **Module Count** 📚: Counts the number of modules within your Rust project.
+/// - **Module Tree** 🌳: The module tree of every crate root, with inline/file/test module counts.
+/// - **Item Inventory** 🗃️: Functions, methods, structs, enums, traits, impls, macros, consts and
+///   type aliases across every crate root, split into public and private. Per crate and per module
+///   numbers live in `module_tree`.
+/// - **Test Code** 🧪: Rust lines split into production and test code (`#[cfg(test)]` modules,
+///   `#[test]` functions, `tests/`, `benches/` and doc-test examples), with a test-to-code ratio.
+/// - **Orphaned Files** 🏚️: `.rs` files on disk that no crate root reaches through `mod` declarations.
+/// - **Workspace** 🏘️: For a Cargo workspace, the numbers of every member plus a workspace rollup.
+/// - **Dependency Lines** 🏔️: When `count_dependency_lines` is on, the lines and unsafe code of
+///   every dependency whose sources are on disk.
+/// - **Unsafe Code** ☢️: `unsafe` blocks, functions, impls and traits, `extern` blocks and
+///   `#![forbid(unsafe_code)]` declarations across every crate root. Per crate and per module
+///   numbers live in `module_tree`.
+/// - **Complexity** 🌀: Cyclomatic and cognitive complexity of every Rust function, with per-file
+///   maxima and averages and the most complex functions of the project.
+/// - **Function Sizes** 📏: The length and nesting depth of every Rust function, as percentiles
+///   and histograms per crate target, with the functions over `LONG_FUNCTION_LINES` lines.
+/// - **Regressions** 📌: What got worse since the configured baseline, see `enforce_baseline`.
+/// - **Budget Violations** 💰: Every configured budget the project goes over, see `enforce_budgets`.
+/// - **Languages** 🗣️: The `LanguageRegistry` used to group files into named languages.
+/// - **Analysis Time** ⏱️: How long `analyze_code_base` took.
 ///
 /// ## Example
 /// ```rust
@@ -736,83 +1150,129 @@ pub trait Analyzable{
 pub struct CountRoo {
     pub config: Config,
     pub total_lines: usize,
+    pub line_counts: LineCounts,
     pub num_files: usize,
     pub num_crates: usize,
     pub project_name: Option<String>,
     pub rust_edition: Option<String>,
     pub rustc_version: Option<String>,
     pub num_modules: usize,
+    pub module_tree: ModuleTree,
+    pub module_stats: ModuleStats,
+    pub orphaned_files: Vec<PathBuf>,
+    pub item_inventory: ItemInventory,
+    pub test_split: TestSplit,
+    pub workspace: Option<WorkspaceReport>,
+    pub dependencies: DependencyReport,
+    pub dependency_lines: Option<DependencyLines>,
+    pub unsafe_code: UnsafeStats,
+    pub complexity: ComplexityReport,
+    pub function_sizes: FunctionSizes,
+    pub budget_violations: Vec<BudgetViolation>,
+    pub regressions: Vec<Regression>,
+    pub languages: LanguageRegistry,
+    pub analysis_time: Duration,
+    file_walker: FileWalker,
+    counted_files: Vec<CountedFile>,
+    language_rows: Vec<LanguageReport>,
 }
 
 impl Exportable for CountRoo {
     fn export(&self, writer: Box<dyn OutputWriter>) -> Result<(), io::Error> {
-        writer.write(self.to_string().as_str())
+        writer.write(&self.report())
     }
 }
 
 #[cfg(feature = "default")]
 impl CountRoo {
-    fn new(config: Config) -> Self {
-        let mut countroo = CountRoo { config, total_lines: 0, num_files: 0, num_crates: 0, project_name: None, rust_edition: None, rustc_version: None, num_modules: 0 };
+    /// Analyzes the project `config` points at.
+    ///
+    /// Panics when the `include`/`exclude` globs are malformed, which `ConfigBuilder::build` and
+    /// the config file readers already reject; use `try_new` for a `Config` put together by hand.
+    pub fn new(config: Config) -> Self {
+        Self::try_new(config).expect("Invalid include or exclude glob pattern")
+    }
+
+    /// Like `new`, but hands malformed `include`/`exclude` globs back as an error. 🛡️
+    pub fn try_new(config: Config) -> Result<Self, LocCounterError> {
+        let languages = config.language_registry();
+        let mut countroo = CountRoo { config, total_lines: 0, line_counts: LineCounts::default(), num_files: 0, num_crates: 0, project_name: None, rust_edition: None, rustc_version: None, num_modules: 0, module_tree: ModuleTree::default(), module_stats: ModuleStats::default(), orphaned_files: Vec::new(), item_inventory: ItemInventory::default(), test_split: TestSplit::default(), workspace: None, dependencies: DependencyReport::default(), dependency_lines: None, unsafe_code: UnsafeStats::default(), complexity: ComplexityReport::default(), function_sizes: FunctionSizes::default(), budget_violations: Vec::new(), regressions: Vec::new(), languages, analysis_time: Duration::default(), file_walker: FileWalker::default(), counted_files: Vec::new(), language_rows: Vec::new() };
+        // Built once, so that every count after this one shares the same validated globs.
+        countroo.file_walker = FileWalker::new(&countroo.config)?.anchored_at(countroo.get_project_base());
         countroo.analyze_code_base();
-        countroo
+        Ok(countroo)
     }
 
     pub fn count_lines_of_code(&mut self) -> Result<usize, LocCounterError> {
-        let extensions =  self.config.extensions.clone();
-        
-        let config = &self.config.clone();
-        let project_path =  PathBuf::from(&self.config.project_src_path);
-
-        let line_counts = WalkDir::new(project_path)
-            .into_iter()
-            .filter_map(Result::ok)
-            .filter({
-                let extensions = extensions.iter()
-                    .par_bridge()
-                    .map(|e| e.to_lowercase())
-                    .collect::<Vec<_>>();
-                move |e| {
-                     e.file_type().is_file() &&
-                        e.path().extension()
-                            .and_then(|os_str| os_str.to_str())
-                            .map(|ext| extensions.contains(&ext.to_lowercase()))
-                            .unwrap_or(false)
-                }
-            })
-            .par_bridge()
-            .map(move |entry| {
-                    Self::count_lines_for_file(config.count_empty_lines, &entry.into_path().to_string_lossy().to_string()).unwrap_or(0)
-            }).collect::<Vec<usize>>();
-
-
-        for result in line_counts {
-            self.total_lines += result;
+        // The extensions may have changed since the analysis, e.g. `count_lines_of_code_for_certain_types`.
+        self.recount_files();
+        for (_, file) in &self.counted_files {
+            let result = LineCounts { code: file.code, comment: file.comment, blank: file.blank };
+            self.line_counts += result;
+            self.total_lines += result.counted(self.config.count_empty_lines);
         }
 
         Ok(self.total_lines)
     }
 
     pub fn count_lines_for_file(count_empty_lines: bool, entry: &String) -> Result<usize, LocCounterError> {
+        Ok(Self::count_line_kinds_for_file(&LanguageRegistry::builtin(), entry)?.counted(count_empty_lines))
+    }
+
+    /// Splits a single file into code, comment and blank lines, using the comment syntax
+    /// of the language `languages` resolves for it. 🧮
+    pub fn count_line_kinds_for_file(languages: &LanguageRegistry, entry: &String) -> Result<LineCounts, LocCounterError> {
+        let path = Path::new(entry);
         // Return early if the path is not a file, just in case ( Fallback Guard ).
-        if !Path::new(entry).is_file() {
-            return Ok(0);
+        if !path.is_file() {
+            return Ok(LineCounts::default());
         }
-        let file = File::open(entry).map_err(LocCounterError::IoError)?;
-        let reader = BufReader::new(file);
+        line_classifier::classify_file(path, languages.detect(path))
+    }
 
-        let mut count = 0;
-        for line in reader.lines() {
-            let line = line.map_err(LocCounterError::IoError)?;
-            if count_empty_lines || !line.trim().is_empty() {
-                count += 1;
-            }
+    /// Like `count_line_kinds_for_file`, for a file whose language is already known, so that
+    /// extensionless files are not opened a second time to read their shebang. 🧮
+    fn classify_counted_file(path: &Path, language: Option<&Language>) -> LineCounts {
+        if !path.is_file() {
+            return LineCounts::default();
         }
-        Ok(count)
+        line_classifier::classify_file(path, language).unwrap_or_default()
+    }
+
+    /// The counted files among `files`, each with the language it was recognised as. Every file
+    /// is recognised once, here, and the language travels along with it. 🔎
+    fn counted_files<'a>(extensions: &[String], languages: &'a LanguageRegistry, files: Vec<PathBuf>) -> Vec<(PathBuf, Option<&'a Language>)> {
+        files.into_par_iter()
+            .filter_map(|path| {
+                let language = languages.detect(&path);
+                Self::is_counted_path(extensions, &path, language).then_some((path, language))
+            })
+            .collect()
+    }
+
+    /// A file is counted when its extension is one of the configured `extensions`, or when it
+    /// is recognised by its exact file name, shebang or modeline as a language with one of
+    /// those extensions (a `Makefile` is counted as soon as `mk` is configured, a
+    /// `#!/usr/bin/env bash` script as soon as `sh` is). `language` is what
+    /// `LanguageRegistry::detect` made of the file.
+    fn is_counted_path(extensions: &[String], path: &Path, language: Option<&Language>) -> bool {
+        let by_extension = path.extension()
+            .and_then(|os_str| os_str.to_str())
+            .map(|ext| extensions.contains(&ext.to_lowercase()))
+            .unwrap_or(false);
+        // Only a match on the file name or the contents counts; a language found through an
+        // extension that is not configured does not.
+        let by_name_or_contents = |language: &Language| path.extension().is_none()
+            || path.file_name().and_then(|name| name.to_str()).is_some_and(|name| language.filenames.iter().any(|f| f == name));
+
+        by_extension || language
+            .filter(|language| by_name_or_contents(language))
+            .map(|language| language.extensions.iter().any(|ext| extensions.contains(ext)))
+            .unwrap_or(false)
     }
 
 
-    /// Calculates and aggregates the lines of code per occurrences of each file extension within the project's source directory.
+    /// Calculates and aggregates the lines of code per language within the project's source directory.
     ///
     /// # Arguments
     ///
@@ -821,27 +1281,668 @@ impl CountRoo {
     /// # Returns
     ///
     /// A HashMap where:
-    /// * Keys are strings representing unique file extensions found.
-    /// * Values are the corresponding counts of files with that extension.
+    /// * Keys are language names from the `LanguageRegistry` (`"Rust"`, `"C++"`, `"Makefile"`);
+    ///   files no language claims are grouped under their raw extension.
+    /// * Values are the code, comment and blank line counts of files in that language.
     ///
     /// # Assumptions
     ///
     /// * The `config` member of the struct holds relevant configuration data.
-    /// * `config.count_empty_lines` influences the counting behavior (Toggling it to true marks whitespace new lines as code lines).
     /// * `config.project_src_path` defines the base directory for searching files (Usually src folder).
-    pub fn calculate_extension_counts(&self) -> HashMap<String, usize> {
-       Self::calculate_extension_counts_for(self.config.count_empty_lines, &self.config.project_src_path)
+    /// * Use `LineCounts::counted` with `config.count_empty_lines` to get a single total per extension.
+    pub fn calculate_extension_counts(&self) -> HashMap<String, LineCounts> {
+       Self::calculate_extension_counts_for(&self.config.project_src_path, &self.languages, &self.walker())
     }
 
-    pub fn calculate_extension_counts_for(count_empty_lines: bool, path: &String) -> HashMap<String, usize> {
-        let mut extension_counts = HashMap::new();
-        for entry in WalkDir::new(path) {
-            let entry = entry.unwrap();
-            if entry.file_type().is_file() {
-                if let Some(ext) = entry.path().extension().and_then(|ext| ext.to_str()) {
-                    let count = extension_counts.entry(ext.to_string()).or_insert(0);
-                    *count += Self::count_lines_for_file(count_empty_lines, &entry.path().to_string_lossy().to_string()).unwrap_or(0);
+    /// The `FileWalker` for this config, with its globs anchored at the project base, which also
+    /// skips the orphaned files when the config asks to leave them out. 🚶
+    fn walker(&self) -> FileWalker {
+        if self.config.exclude_orphaned_files {
+            self.file_walker.clone().skipping(self.orphaned_files.iter().cloned())
+        } else {
+            self.file_walker.clone()
+        }
+    }
+
+    /// Splits the Rust files below `project_base` into production and test lines. Files under
+    /// `tests/` and `benches/`, and files only compiled for tests, are test code through and
+    /// through; the others are split item by item. 🧪
+    fn split_test_code(&self, project_base: &Path, files: Vec<PathBuf>) -> TestSplit {
+        let test_files = self.module_tree.test_files().into_iter()
+            .map(|file| std::fs::canonicalize(&file).unwrap_or(file))
+            .collect::<BTreeSet<_>>();
+        let languages = &self.languages;
+        let splits = files
+            .into_par_iter()
+            .filter(|path| path.extension().is_some_and(|ext| ext == "rs"))
+            .map(|path| {
+                let relative = path.strip_prefix(project_base).unwrap_or(&path);
+                let whole_file_is_test = relative.starts_with("tests") || relative.starts_with("benches")
+                    || test_files.contains(&std::fs::canonicalize(&path).unwrap_or_else(|_| path.clone()));
+                test_code::split_file(&path, languages.detect(&path), whole_file_is_test).unwrap_or_default()
+            })
+            .collect::<Vec<TestSplit>>();
+        splits.into_iter().fold(TestSplit::default(), |acc, split| acc + split)
+    }
+
+    /// Measures the cyclomatic and cognitive complexity of every function in the Rust files
+    /// among `files`. 🌀
+    fn measure_complexity(files: &[PathBuf]) -> ComplexityReport {
+        let mut files = files.par_iter()
+            .filter(|path| path.extension().is_some_and(|ext| ext == "rs"))
+            .map(|path| FileComplexity::from_file(path).unwrap_or_default())
+            .filter(|file| !file.functions.is_empty())
+            .collect::<Vec<_>>();
+        files.sort_by(|a, b| a.file.cmp(&b.file));
+        ComplexityReport { files }
+    }
+
+    /// Counts every counted file below `project_src_path`, sorted by its path relative to the
+    /// project base. The analysis keeps the result, so that the report, the budgets, the baseline
+    /// and the rustc environment all add up to `total_lines` without walking the disk again.
+    fn count_files(&self) -> Vec<CountedFile> {
+        let project_base = self.get_project_base();
+        let extensions = self.config.extensions.iter().map(|e| e.to_lowercase()).collect::<Vec<_>>();
+        let mut files = Self::counted_files(&extensions, &self.languages, self.walker().files(&self.config.project_src_path))
+            .into_par_iter()
+            .map(|(path, language)| {
+                let counts = Self::classify_counted_file(&path, language);
+                let relative = path.strip_prefix(&project_base).unwrap_or(&path);
+                let file = FileReport {
+                    path: relative.to_string_lossy().replace('\\', "/"),
+                    language: match language {
+                        Some(language) => Some(language.name.clone()),
+                        None => path.extension().map(|ext| ext.to_string_lossy().to_string()),
+                    },
+                    code: counts.code,
+                    comment: counts.comment,
+                    blank: counts.blank,
+                    bytes: std::fs::metadata(&path).map(|metadata| metadata.len()).unwrap_or(0),
+                };
+                (std::fs::canonicalize(&path).unwrap_or(path), file)
+            })
+            .collect::<Vec<_>>();
+        files.sort_by(|(_, a), (_, b)| a.path.cmp(&b.path));
+        files
+    }
+
+    /// The counted files added up per language, most code first; files no language claims go
+    /// under their extension, or "Other" without one.
+    fn language_rows(files: &[CountedFile]) -> Vec<LanguageReport> {
+        let mut languages: Vec<LanguageReport> = Vec::new();
+        for (_, file) in files {
+            let name = file.language.clone().unwrap_or_else(|| "Other".to_string());
+            let index = match languages.iter().position(|language| language.name == name) {
+                Some(index) => index,
+                None => {
+                    languages.push(LanguageReport { name, ..Default::default() });
+                    languages.len() - 1
                 }
+            };
+            let language = &mut languages[index];
+            language.files += 1;
+            language.code += file.code;
+            language.comment += file.comment;
+            language.blank += file.blank;
+        }
+        languages.sort_by(|a, b| b.code.cmp(&a.code).then_with(|| a.name.cmp(&b.name)));
+        languages
+    }
+
+    /// Counts the files afresh, for when the extensions changed after the analysis. 🔄
+    fn recount_files(&mut self) {
+        self.counted_files = self.count_files();
+        self.language_rows = Self::language_rows(&self.counted_files);
+    }
+
+    /// Checks the analysed project against the configured `Budgets`. 💰
+    pub fn check_budgets(&self) -> Result<Vec<BudgetViolation>, LocCounterError> {
+        let budgets = &self.config.budgets;
+        if budgets.is_empty() {
+            return Ok(Vec::new());
+        }
+        let files = &self.counted_files;
+        let measurements = Measurements {
+            file_lines: files.iter().map(|(_, file)| (PathBuf::from(&file.path), file.counted(self.config.count_empty_lines))).collect(),
+            functions: self.complexity.functions().collect(),
+            unsafe_blocks: self.unsafe_code.unsafe_blocks,
+            code_lines: files.iter().map(|(_, file)| file.code).sum(),
+            comment_lines: files.iter().map(|(_, file)| file.comment).sum(),
+            dependencies: self.dependencies.dependency_count(),
+        };
+        Ok(budgets.check(&measurements))
+    }
+
+    /// Reports budget violations to cargo from a build script: each one becomes a
+    /// `cargo:warning=` line, and in strict mode the violations are returned as an error, so the
+    /// build can be failed with the list of offenders. 🚨
+    pub fn enforce_budgets(&self) -> Result<(), LocCounterError> {
+        for violation in &self.budget_violations {
+            println!("cargo:warning=countroo budget exceeded: {}", violation);
+        }
+        if self.config.budgets.strict && !self.budget_violations.is_empty() {
+            let offenders = self.budget_violations.iter().map(|violation| format!("  - {}", violation)).collect::<Vec<_>>();
+            return Err(LocCounterError::BudgetError(format!("{} budget(s) exceeded:\n{}", offenders.len(), offenders.join("\n"))));
+        }
+        Ok(())
+    }
+
+    /// Takes a snapshot of the project to ratchet against: lines per file, unsafe blocks per file,
+    /// the size and complexity of every function and the names of every dependency. 📌
+    pub fn baseline(&self) -> Result<Baseline, LocCounterError> {
+        let project_base = self.get_project_base();
+        let key = |path: &Path| path.strip_prefix(&project_base).unwrap_or(path).to_string_lossy().replace('\\', "/");
+
+        let files = self.counted_files.iter()
+            .map(|(_, file)| (file.path.clone(), file.counted(self.config.count_empty_lines)))
+            .collect();
+        let mut unsafe_blocks = std::collections::BTreeMap::new();
+        for node in self.module_tree.crates.iter().flat_map(|crate_modules| crate_modules.root.iter()) {
+            if node.unsafe_code.unsafe_blocks > 0 {
+                *unsafe_blocks.entry(key(&node.file)).or_insert(0) += node.unsafe_code.unsafe_blocks;
+            }
+        }
+        let mut functions = std::collections::BTreeMap::new();
+        for function in self.complexity.functions() {
+            let entry = functions.entry(format!("{}::{}", key(&function.file), function.name)).or_insert_with(FunctionBaseline::default);
+            // Functions sharing a name (one per `cfg`, say) are held to the largest of them.
+            entry.lines = entry.lines.max(function.lines);
+            entry.cognitive = entry.cognitive.max(function.cognitive);
+        }
+        let dependencies = if self.dependencies.has_lockfile {
+            self.dependencies.resolved.iter().map(|package| package.name.clone()).collect()
+        } else {
+            self.dependencies.declared.iter().map(|dependency| dependency.name.clone()).collect()
+        };
+        Ok(Baseline { files, unsafe_blocks, functions, dependencies })
+    }
+
+    /// Where the configured baseline file lives, relative to the project base.
+    fn baseline_path(&self) -> Option<PathBuf> {
+        self.config.baseline.as_ref().map(|path| Path::new(&self.get_project_base()).join(path))
+    }
+
+    /// The regressions since the configured baseline; none while there is no baseline file yet.
+    fn compare_with_baseline(&self) -> Result<Vec<Regression>, LocCounterError> {
+        #[cfg(feature = "ratchet")]
+        if let Some(path) = self.baseline_path() {
+            if let Some(baseline) = Baseline::load(path)? {
+                return Ok(baseline.compare(&self.baseline()?));
+            }
+        }
+        Ok(Vec::new())
+    }
+
+    /// Writes the configured baseline file, lowered to wherever the project improved on it, or
+    /// as the project is now when there is no baseline yet. 📌
+    pub fn tighten_baseline(&self) -> Result<(), LocCounterError> {
+        let Some(path) = self.baseline_path() else {
+            return Err(LocCounterError::ConfigError("No `baseline` file is configured".to_string()));
+        };
+        #[cfg(feature = "ratchet")]
+        {
+            let current = self.baseline()?;
+            let tightened = match Baseline::load(&path)? {
+                Some(baseline) => baseline.tighten(&current),
+                None => current,
+            };
+            tightened.save(&path)
+        }
+        #[cfg(not(feature = "ratchet"))]
+        Err(LocCounterError::ConfigError(format!("Baseline {} needs the `ratchet` feature", path.display())))
+    }
+
+    /// Ratchets the project from a build script. The first run writes the baseline file, and so
+    /// does a run with `COUNTROO_TIGHTEN_BASELINE` set, after tightening it. Any other run turns
+    /// every regression into a `cargo:warning=` line and returns them as an error, so the build
+    /// fails on regressions only.
+    ///
+    /// Cargo is told to rerun the build script when `COUNTROO_TIGHTEN_BASELINE` changes. As that
+    /// switches off its rerun on any change in the package, every walked file and the baseline
+    /// itself are listed with `cargo:rerun-if-changed` too. 📌
+    pub fn enforce_baseline(&self) -> Result<(), LocCounterError> {
+        let Some(path) = self.baseline_path() else { return Ok(()) };
+        println!("cargo:rerun-if-env-changed={}", TIGHTEN_BASELINE_ENV);
+        println!("cargo:rerun-if-changed={}", path.display());
+        for file in self.walker().files(self.get_project_base()) {
+            println!("cargo:rerun-if-changed={}", file.display());
+        }
+        if !path.is_file() || env::var_os(TIGHTEN_BASELINE_ENV).is_some() {
+            return self.tighten_baseline();
+        }
+        for regression in &self.regressions {
+            println!("cargo:warning=countroo regression: {}", regression);
+        }
+        if !self.regressions.is_empty() {
+            let regressions = self.regressions.iter().map(|regression| format!("  - {}", regression)).collect::<Vec<_>>();
+            return Err(LocCounterError::BaselineError(format!(
+                "{} regression(s) since {}; set {}=1 to accept improvements into the baseline:\n{}",
+                regressions.len(), path.display(), TIGHTEN_BASELINE_ENV, regressions.join("\n")
+            )));
+        }
+        Ok(())
+    }
+
+    /// The statistics of the project as `COUNTROO_*` environment variables, for a build script to
+    /// hand to rustc so the crate can `env!()` them. Text values are reduced to a single line, and
+    /// missing ones are left empty. 🏷️
+    ///
+    /// ## Example Usage
+    /// ```rust
+    /// // Inside build.rs
+    /// use countroo::prelude::*;
+    /// let counter = CountRoo::new(Config::builder().project_path("src").extension("rs").build().unwrap());
+    /// let env = counter.rustc_env().expect("Failed to collect the statistics");
+    /// assert!(env.iter().any(|(name, _)| name == "COUNTROO_TOTAL_LINES"));
+    /// // And in the crate itself:
+    /// // println!("built from {} lines across {} crates", env!("COUNTROO_TOTAL_LINES"), env!("COUNTROO_CRATES"));
+    /// ```
+    pub fn rustc_env(&self) -> Result<Vec<(String, String)>, LocCounterError> {
+        let files = &self.counted_files;
+        let lines = files.iter().map(|(_, file)| LineCounts { code: file.code, comment: file.comment, blank: file.blank }).sum::<LineCounts>();
+        let text = |value: &Option<String>| value.as_deref().unwrap_or("").lines().next().unwrap_or("").trim().to_string();
+        let vars: Vec<(&str, String)> = vec![
+            ("PROJECT_NAME", text(&self.project_name)),
+            ("RUST_EDITION", text(&self.rust_edition)),
+            ("RUSTC_VERSION", text(&self.rustc_version)),
+            ("TOTAL_LINES", lines.counted(self.config.count_empty_lines).to_string()),
+            ("CODE_LINES", lines.code.to_string()),
+            ("COMMENT_LINES", lines.comment.to_string()),
+            ("BLANK_LINES", lines.blank.to_string()),
+            ("FILES", files.len().to_string()),
+            ("CRATES", self.package_count().to_string()),
+            ("DEPENDENCIES", self.dependencies.dependency_count().to_string()),
+            ("DIRECT_DEPENDENCIES", self.dependencies.direct.to_string()),
+            ("MODULES", self.num_modules.to_string()),
+            ("FUNCTIONS", self.item_inventory.functions.total().to_string()),
+            ("METHODS", self.item_inventory.methods.total().to_string()),
+            ("STRUCTS", self.item_inventory.structs.total().to_string()),
+            ("ENUMS", self.item_inventory.enums.total().to_string()),
+            ("TRAITS", self.item_inventory.traits.total().to_string()),
+            ("PRODUCTION_LINES", self.test_split.production.code.to_string()),
+            ("TEST_LINES", self.test_split.test.code.to_string()),
+            ("UNSAFE_BLOCKS", self.unsafe_code.unsafe_blocks.to_string()),
+            ("UNSAFE_FNS", self.unsafe_code.unsafe_fns.to_string()),
+            ("MAX_CYCLOMATIC", self.complexity.max_cyclomatic().to_string()),
+            ("MAX_COGNITIVE", self.complexity.max_cognitive().to_string()),
+            ("MAX_FUNCTION_LINES", self.function_sizes.lengths.max.to_string()),
+            ("VERSION", env!("CARGO_PKG_VERSION").to_string()),
+        ];
+        Ok(vars.into_iter().map(|(name, value)| (format!("COUNTROO_{}", name), value)).collect())
+    }
+
+    /// Prints a `cargo:rustc-env=COUNTROO_...` line for every statistic of `rustc_env`. Meant to be
+    /// called from `build.rs`. 🏷️
+    pub fn emit_rustc_env(&self) -> Result<(), LocCounterError> {
+        for (name, value) in self.rustc_env()? {
+            println!("cargo:rustc-env={}={}", name, value);
+        }
+        Ok(())
+    }
+
+    /// The analysis as a `Report`, the data model every `OutputWriter` works from. Built from the
+    /// files and languages counted once by the analysis, so the table, the writers, the budgets and
+    /// `rustc_env` all agree on them. 🧾
+    pub fn report(&self) -> Report {
+        let started = Instant::now();
+        let project_base = self.get_project_base();
+        let count_empty_lines = self.config.count_empty_lines;
+        let files = &self.counted_files;
+
+        let crates = self.module_tree.crates.iter()
+            .map(|crate_modules| {
+                let crate_files = crate_modules.files().iter()
+                    .map(|file| std::fs::canonicalize(file).unwrap_or_else(|_| file.clone()))
+                    .collect::<BTreeSet<_>>();
+                let counted = files.iter().filter(|(path, _)| crate_files.contains(path)).map(|(_, file)| file).collect::<Vec<_>>();
+                CrateReport {
+                    name: crate_modules.target.name.clone(),
+                    kind: crate_modules.target.kind,
+                    root_file: crate_modules.target.root_file.strip_prefix(&project_base)
+                        .unwrap_or(&crate_modules.target.root_file).to_string_lossy().replace('\\', "/"),
+                    files: crate_files.len(),
+                    modules: crate_modules.stats().total(),
+                    code: counted.iter().map(|file| file.code).sum(),
+                    comment: counted.iter().map(|file| file.comment).sum(),
+                    blank: counted.iter().map(|file| file.blank).sum(),
+                    functions: self.complexity.functions()
+                        .filter(|function| crate_files.contains(&std::fs::canonicalize(&function.file).unwrap_or_else(|_| function.file.clone())))
+                        .count(),
+                    unsafe_blocks: crate_modules.unsafe_code().unsafe_blocks,
+                }
+            })
+            .collect();
+
+        let relative = |path: &Path| path.strip_prefix(&project_base).unwrap_or(path).to_string_lossy().replace('\\', "/");
+        let unsafe_code = UnsafeReport {
+            project: self.unsafe_code,
+            crates: self.module_tree.crates.iter()
+                .map(|crate_modules| CrateUnsafeReport {
+                    name: crate_modules.target.name.clone(),
+                    kind: crate_modules.target.kind,
+                    forbids_unsafe_code: crate_modules.forbids_unsafe_code(),
+                    unsafe_code: crate_modules.unsafe_code(),
+                })
+                .collect(),
+            modules: self.module_tree.crates.iter()
+                .flat_map(|crate_modules| crate_modules.root.iter())
+                .filter(|node| node.unsafe_code != UnsafeStats::default())
+                .map(|node| ModuleUnsafeReport { path: node.path.clone(), file: relative(&node.file), unsafe_code: node.unsafe_code })
+                .collect(),
+            dependencies: self.dependency_lines.as_ref().map(DependencyLines::unsafe_code),
+        };
+        let dependency_lines = self.dependency_lines.as_ref().map(|dependency_lines| {
+            dependency_lines.crates.iter()
+                .map(|krate| DependencyLinesReport {
+                    name: krate.name.clone(),
+                    version: krate.version.clone(),
+                    source_dir: krate.source_dir.as_ref().map(|dir| dir.to_string_lossy().replace('\\', "/")),
+                    code: krate.line_counts.code,
+                    comment: krate.line_counts.comment,
+                    blank: krate.line_counts.blank,
+                    unsafe_code: krate.unsafe_code,
+                })
+                .collect()
+        });
+        let function_report = |function: &FunctionComplexity| FunctionComplexityReport {
+            name: function.name.clone(),
+            file: relative(&function.file),
+            line: function.line,
+            lines: function.lines,
+            max_nesting: function.max_nesting,
+            cyclomatic: function.cyclomatic,
+            cognitive: function.cognitive,
+        };
+        let shown = self.config.most_complex_functions;
+        let mut complex_files = self.complexity.files.iter().collect::<Vec<_>>();
+        complex_files.sort_by_key(|file| std::cmp::Reverse((file.max_cognitive(), file.max_cyclomatic())));
+        let complexity = ComplexitySummary {
+            functions: self.complexity.functions().count(),
+            average_cyclomatic: self.complexity.average_cyclomatic(),
+            average_cognitive: self.complexity.average_cognitive(),
+            max_cyclomatic: self.complexity.max_cyclomatic(),
+            max_cognitive: self.complexity.max_cognitive(),
+            files: complex_files.into_iter().take(shown)
+                .map(|file| FileComplexityReport {
+                    path: relative(&file.file),
+                    functions: file.functions.len(),
+                    max_cyclomatic: file.max_cyclomatic(),
+                    max_cognitive: file.max_cognitive(),
+                    average_cyclomatic: file.average_cyclomatic(),
+                    average_cognitive: file.average_cognitive(),
+                })
+                .collect(),
+            most_complex: self.complexity.top(shown).into_iter().map(function_report).collect(),
+        };
+        let distribution = |distribution: &Distribution| DistributionReport {
+            count: distribution.count(),
+            max: distribution.max,
+            mean: distribution.mean,
+            p50: distribution.percentile(50.0),
+            p90: distribution.percentile(90.0),
+            histogram: distribution.histogram.clone(),
+        };
+        let function_sizes = FunctionSizesReport {
+            lengths: distribution(&self.function_sizes.lengths),
+            nesting: distribution(&self.function_sizes.nesting),
+            long_functions: self.function_sizes.long_functions.iter().map(function_report).collect(),
+            crates: self.function_sizes.crates.iter()
+                .map(|crate_sizes| CrateFunctionSizesReport {
+                    name: crate_sizes.name.clone(),
+                    kind: crate_sizes.kind,
+                    lengths: distribution(&crate_sizes.lengths),
+                    nesting: distribution(&crate_sizes.nesting),
+                    long_functions: crate_sizes.long_functions.len(),
+                })
+                .collect(),
+        };
+        let workspace = self.workspace.as_ref().map(|workspace| WorkspaceSummary {
+            root: workspace.root.to_string_lossy().replace('\\', "/"),
+            is_virtual: workspace.is_virtual,
+            members: workspace.members.iter()
+                .map(|member| {
+                    let manifest_dir = member.manifest_dir.strip_prefix(&workspace.root).unwrap_or(&member.manifest_dir);
+                    WorkspaceMemberReport {
+                        name: member.name.clone(),
+                        manifest_dir: match manifest_dir.as_os_str().is_empty() {
+                            true => ".".to_string(),
+                            false => manifest_dir.to_string_lossy().replace('\\', "/"),
+                        },
+                        files: member.num_files,
+                        code: member.line_counts.code,
+                        comment: member.line_counts.comment,
+                        blank: member.line_counts.blank,
+                        modules: member.modules,
+                        items: member.items,
+                        test_split: member.test_split,
+                    }
+                })
+                .collect(),
+        });
+        let dependencies = DependencySummary {
+            has_lockfile: self.dependencies.has_lockfile,
+            direct: self.dependencies.direct,
+            transitive: self.dependencies.transitive,
+            max_depth: self.dependencies.max_depth,
+            duplicates: self.dependencies.duplicates.clone(),
+            kinds: self.dependencies.kinds,
+            sources: self.dependencies.sources,
+            declared: self.dependencies.declared.iter()
+                .map(|declared| DeclaredDependencyReport {
+                    name: declared.name.clone(),
+                    member: declared.member.clone(),
+                    kind: declared.kind,
+                    target: declared.target.clone(),
+                    source_kind: match declared.source {
+                        DependencySource::Registry { .. } => SourceKind::Registry,
+                        DependencySource::Git { .. } => SourceKind::Git,
+                        DependencySource::Path { .. } => SourceKind::Path,
+                        DependencySource::Unresolved => SourceKind::Unresolved,
+                    },
+                    source: declared.source.to_string(),
+                    workspace_inherited: declared.workspace_inherited,
+                })
+                .collect(),
+            resolved: self.dependencies.resolved.iter()
+                .map(|package| LockedPackageReport { name: package.name.clone(), version: package.version.clone(), source: package.source.clone() })
+                .collect(),
+        };
+        let orphaned_files = OrphanedFilesReport {
+            excluded: self.config.exclude_orphaned_files,
+            files: self.orphaned_files.iter().map(|path| relative(path)).collect(),
+        };
+
+        let files = files.iter().map(|(_, file)| file.clone()).collect::<Vec<_>>();
+        let languages = self.language_rows.clone();
+        let lines = files.iter().map(|file| LineCounts { code: file.code, comment: file.comment, blank: file.blank }).sum::<LineCounts>();
+        let project = ProjectReport {
+            name: self.project_name.clone(),
+            path: project_base.clone(),
+            total_lines: lines.counted(count_empty_lines),
+            count_empty_lines,
+            code_lines: lines.code,
+            comment_lines: lines.comment,
+            blank_lines: lines.blank,
+            files: files.len(),
+            modules: self.num_modules,
+            dependencies: self.dependencies.dependency_count(),
+            functions: self.complexity.functions().count(),
+            unsafe_blocks: self.unsafe_code.unsafe_blocks,
+        };
+        let toolchain = ToolchainReport {
+            edition: self.rust_edition.clone(),
+            rustc_version: self.rustc_version.clone(),
+            countroo_version: env!("CARGO_PKG_VERSION").to_string(),
+        };
+        let timings = Timings {
+            analysis_ms: self.analysis_time.as_millis() as u64,
+            report_ms: started.elapsed().as_millis() as u64,
+        };
+        Report {
+            schema_version: REPORT_SCHEMA_VERSION,
+            project,
+            languages,
+            files,
+            crates,
+            modules: self.module_stats,
+            items: self.item_inventory,
+            test_split: self.test_split,
+            workspace,
+            dependencies,
+            dependency_lines,
+            unsafe_code,
+            complexity,
+            function_sizes,
+            orphaned_files,
+            budget_violations: self.budget_violations.clone(),
+            regressions: self.regressions.clone(),
+            toolchain,
+            timings,
+        }
+    }
+
+    /// The source of a Rust module holding the statistics of the project as a typed constant,
+    /// `pub const STATS: ProjectStats`. The module defines `ProjectStats` and `LanguageStats`
+    /// itself, so the crate including it only needs `countroo` as a build dependency. 🧾
+    pub fn stats_module(&self) -> Result<String, LocCounterError> {
+        let report = self.report();
+        let project = &report.project;
+        let optional = |value: &Option<String>| match value {
+            Some(value) => format!("Some({:?})", value),
+            None => "None".to_string(),
+        };
+
+        let mut module = String::from(STATS_MODULE_PRELUDE);
+        module.push_str("pub const STATS: ProjectStats = ProjectStats {\n");
+        module.push_str(&format!("    crate_name: {:?},\n", project.name.clone().unwrap_or_default()));
+        module.push_str(&format!("    edition: {},\n", optional(&report.toolchain.edition)));
+        module.push_str(&format!("    rustc_version: {},\n", optional(&report.toolchain.rustc_version)));
+        module.push_str(&format!("    countroo_version: {:?},\n", report.toolchain.countroo_version));
+        module.push_str(&format!("    total_lines: {},\n", project.total_lines));
+        module.push_str(&format!("    code_lines: {},\n    comment_lines: {},\n    blank_lines: {},\n", project.code_lines, project.comment_lines, project.blank_lines));
+        module.push_str(&format!("    files: {},\n    crates: {},\n    modules: {},\n", project.files, self.package_count(), project.modules));
+        module.push_str("    languages: &[\n");
+        for language in &report.languages {
+            module.push_str(&format!(
+                "        LanguageStats {{ name: {:?}, files: {}, code: {}, comment: {}, blank: {} }},\n",
+                language.name, language.files, language.code, language.comment, language.blank
+            ));
+        }
+        module.push_str("    ],\n};\n");
+        Ok(module)
+    }
+
+    /// Writes `stats_module` to `$OUT_DIR/countroo_stats.rs`, for the crate to pick up with
+    /// `include!(concat!(env!("OUT_DIR"), "/countroo_stats.rs"));`. Meant to be called from
+    /// `build.rs`, the only place `OUT_DIR` is set. 🧾
+    pub fn write_stats_module(&self) -> Result<PathBuf, LocCounterError> {
+        let out_dir = env::var_os("OUT_DIR")
+            .ok_or_else(|| LocCounterError::ConfigError("`OUT_DIR` is not set; call this from build.rs".to_string()))?;
+        let path = Path::new(&out_dir).join("countroo_stats.rs");
+        std::fs::write(&path, self.stats_module()?).map_err(LocCounterError::IoError)?;
+        Ok(path)
+    }
+
+    /// The packages of the project itself: every member of its workspace, or just the one package.
+    /// A package may build several crates (a library, binaries, tests...); those are the targets of
+    /// `module_tree`. 📦
+    pub fn package_count(&self) -> usize {
+        self.workspace.as_ref().map_or(1, |workspace| workspace.members.len())
+    }
+
+    /// Breaks a workspace down member by member. Members nested in the folder of another member
+    /// (such as `crates/*` below a root package) are left out of that member's numbers. 🏘️
+    fn workspace_report(&self, workspace: &Workspace) -> Result<WorkspaceReport, LocCounterError> {
+        let walker = self.walker();
+        let extensions = self.config.extensions.iter().map(|e| e.to_lowercase()).collect::<Vec<_>>();
+        let languages = &self.languages;
+        let mut members = Vec::new();
+        for member in &workspace.members {
+            let nested = workspace.nested_member_dirs(member);
+            let files = walker.files(&member.manifest_dir).into_iter()
+                .filter(|path| !nested.iter().any(|dir| path.starts_with(dir)))
+                .collect::<Vec<_>>();
+            let code_files = Self::counted_files(&extensions, languages, files.clone());
+            let line_counts = code_files.par_iter()
+                .map(|(path, language)| Self::classify_counted_file(path, *language))
+                .sum();
+            let tree = ModuleTree::from_manifest_dir(&member.manifest_dir)?;
+            members.push(MemberReport {
+                name: member.name.clone(),
+                manifest_dir: member.manifest_dir.clone(),
+                num_files: code_files.len(),
+                line_counts,
+                modules: tree.stats(),
+                items: tree.items(),
+                test_split: self.split_test_code(&member.manifest_dir, files),
+            });
+        }
+        Ok(WorkspaceReport { root: workspace.root.clone(), is_virtual: workspace.is_virtual, members })
+    }
+
+    /// Analyses the dependencies of every package in the project against the `Cargo.lock` and
+    /// `[workspace.dependencies]` of the workspace it belongs to, or of the package itself. 🔗
+    fn analyze_dependencies(&self) -> Result<DependencyReport, LocCounterError> {
+        let project_base = PathBuf::from(self.get_project_base());
+        let manifest_dirs = match &self.workspace {
+            Some(workspace) => workspace.members.iter().map(|member| member.manifest_dir.clone()).collect(),
+            None => vec![project_base.clone()],
+        };
+        let workspace_root = Workspace::find(&project_base)?.map(|workspace| workspace.root).unwrap_or(project_base);
+        DependencyReport::analyze(&manifest_dirs, &workspace_root)
+    }
+
+    /// Counts the lines and the unsafe code of every locked dependency that can be found on disk, in the configured
+    /// vendor folder, in `vendor/` next to the workspace root, or in the local registry cache. 🏔️
+    fn count_dependency_lines(&self) -> Result<DependencyLines, LocCounterError> {
+        let project_base = PathBuf::from(self.get_project_base());
+        let workspace_root = Workspace::find(&project_base)?.map(|workspace| workspace.root).unwrap_or(project_base);
+        let vendor_dir = match &self.config.vendor_dir {
+            Some(dir) => Some(workspace_root.join(dir)),
+            None => Some(workspace_root.join("vendor")).filter(|dir| dir.is_dir()),
+        };
+        let locator = SourceLocator::new(vendor_dir).with_path_dirs(self.dependencies.path_dependency_dirs());
+        let walker = FileWalker::default();
+        let extensions = self.config.extensions.iter().map(|e| e.to_lowercase()).collect::<Vec<_>>();
+        let languages = &self.languages;
+        let crates = self.dependencies.resolved.par_iter()
+            .map(|package| {
+                let source_dir = locator.locate(package);
+                let files = source_dir.as_ref().map(|dir| walker.files(dir)).unwrap_or_default();
+                let line_counts = Self::counted_files(&extensions, languages, files.clone()).into_iter()
+                    .map(|(path, language)| Self::classify_counted_file(&path, language))
+                    .sum();
+                let unsafe_code = files.iter()
+                    .filter(|path| path.extension().is_some_and(|ext| ext == "rs"))
+                    .map(|path| UnsafeStats::from_file(path).unwrap_or_default())
+                    .sum();
+                CrateLines { name: package.name.clone(), version: package.version.clone(), source_dir, line_counts, unsafe_code }
+            })
+            .collect();
+        Ok(DependencyLines { crates })
+    }
+
+    /// Lists the `.rs` files below `project_base` that are not part of the module tree of any
+    /// target. Without a resolved tree, nothing is considered orphaned. 🏚️
+    fn find_orphaned_files(&self, project_base: &str) -> Result<Vec<PathBuf>, LocCounterError> {
+        if self.module_tree.crates.is_empty() {
+            return Ok(Vec::new());
+        }
+        let candidates = self.file_walker.files(project_base);
+        Ok(self.module_tree.orphans(&candidates))
+    }
+
+    pub fn calculate_extension_counts_for(path: &String, languages: &LanguageRegistry, walker: &FileWalker) -> HashMap<String, LineCounts> {
+        let mut extension_counts = HashMap::new();
+        for path in walker.files(path) {
+            let language = languages.detect(&path);
+            let group = match language {
+                Some(language) => Some(language.name.clone()),
+                None => path.extension().and_then(|ext| ext.to_str()).map(|ext| ext.to_string()),
+            };
+            if let Some(group) = group {
+                let counts = extension_counts.entry(group).or_insert_with(LineCounts::default);
+                *counts += Self::classify_counted_file(&path, language);
             }
         }
 
@@ -858,11 +1959,22 @@ impl CountRoo {
 impl Analyzable for CountRoo{
 
     fn get_project_base(&self) -> String {
-        PathBuf::from(&self.config.project_src_path).parent().unwrap().to_string_lossy().to_string()
+        let src = PathBuf::from(&self.config.project_src_path);
+        // A path that points at a package or workspace root, as `count_it_all!(workspace: true)`
+        // does, is the base itself; otherwise it is the `src` folder inside of it.
+        if src.join("Cargo.toml").is_file() {
+            return src.to_string_lossy().to_string();
+        }
+        match src.parent() {
+            Some(parent) if !parent.as_os_str().is_empty() => parent.to_string_lossy().to_string(),
+            // A bare relative `src` has the current folder as its base.
+            _ => ".".to_string(),
+        }
     }
     fn is_code_file(&self, path: &str) -> bool {
-        Path::new(path).extension().map(|ext| self.config.extensions.contains(&ext.to_string_lossy().to_string()))
-            .unwrap_or(false)
+        let extensions = self.config.extensions.iter().map(|e| e.to_lowercase()).collect::<Vec<_>>();
+        let path = Path::new(path);
+        Self::is_counted_path(&extensions, path, self.languages.detect(path))
     }
 
     fn get_manifest(&self) -> Result<cargo_toml::Manifest, LocCounterError> {
@@ -871,15 +1983,15 @@ impl Analyzable for CountRoo{
             return Err(LocCounterError::TomlError("Cargo.toml not found".to_string()));
         }
 
-        let contents = std::fs::read_to_string(cargo_toml_path).unwrap();
+        let contents = std::fs::read_to_string(cargo_toml_path).map_err(LocCounterError::IoError)?;
         let manifest: cargo_toml::Manifest = cargo_toml::Manifest::from_str(&contents).map_err(|err| LocCounterError::TomlError(err.to_string()))?;
         Ok(manifest)
     }
 
     fn count_code_files(&self, project_path: &str) -> Result<usize, LocCounterError> {
         let mut count = 0;
-        for entry in WalkDir::new(project_path).into_iter().flatten() {
-            if entry.file_type().is_file() && self.is_code_file(entry.path().to_string_lossy().as_ref()) {
+        for path in self.walker().files(project_path) {
+            if self.is_code_file(path.to_string_lossy().as_ref()) {
                 count += 1;
             }
         }
@@ -887,11 +1999,17 @@ impl Analyzable for CountRoo{
     }
 
     fn count_crates(&self) -> Result<usize, LocCounterError> {
-        Ok(self.get_manifest()?.dependencies.len() + 1)
+        Ok(self.analyze_dependencies()?.crate_count())
     }
 
     fn get_project_name(&self) -> Result<Option<String>, LocCounterError> {
-        Ok(Some(self.get_manifest()?.package.unwrap().name.to_string()))
+        match self.get_manifest()?.package {
+            Some(package) => Ok(Some(package.name.to_string())),
+            // A virtual workspace manifest has no package, so the workspace is named after its folder.
+            None => Ok(std::fs::canonicalize(self.get_project_base())
+                .ok()
+                .and_then(|base| base.file_name().map(|name| name.to_string_lossy().to_string()))),
+        }
     }
 
     fn get_rust_version() -> Option<String> {
@@ -906,8 +2024,11 @@ impl Analyzable for CountRoo{
     }
 
     fn get_rust_edition(&self) -> Option<String> {
-        let manifest = self.get_manifest().map_err(|err| LocCounterError::TomlError(err.to_string())).unwrap();
-        match manifest.package.unwrap().edition.unwrap(){
+        let manifest = self.get_manifest().ok()?;
+        // Virtual manifests and `edition.workspace = true` take the edition of `[workspace.package]`.
+        let workspace_edition = manifest.workspace.as_ref().and_then(|workspace| workspace.package.as_ref()).and_then(|package| package.edition);
+        let edition = manifest.package.as_ref().and_then(|package| package.edition.get().ok().copied()).or(workspace_edition)?;
+        match edition {
             cargo_toml::Edition::E2015 => Some("2015".to_string()),
             cargo_toml::Edition::E2018 => Some("2018".to_string()),
             cargo_toml::Edition::E2021 => Some("2021".to_string()),
@@ -916,30 +2037,59 @@ impl Analyzable for CountRoo{
         }
     }
 
+    /// Counts the modules declared from every crate root of the package in `project_path`,
+    /// resolved through the real module tree rather than by searching the sources for `mod `.
     fn count_rust_modules(project_path: &str) -> Result<usize, LocCounterError> {
-        let mut count = 0;
-        for entry in WalkDir::new(project_path)
-            .into_iter()
-            .filter_map(Result::ok)
-            .filter(|e| e.file_type().is_file() && e.path().extension().map(|ext| ext == "rs").unwrap_or(false))
-        {
-            let contents = std::fs::read_to_string(entry.path()).expect("Failed to analyze whether a file is a rust module or not.");
-            count += contents.matches("mod ").count();
-        }
-        Ok(count)
+        Ok(ModuleTree::from_manifest_dir(project_path)?.stats().total())
     }
 
+    /// Runs every analysis. One that fails (a broken manifest, an unreadable lockfile...) leaves its
+    /// section empty and says why in a `cargo:warning=` line, rather than taking the whole count
+    /// down with it. 🩹
     fn analyze_code_base(&mut self){
+        let started = Instant::now();
         let project_base = self.get_project_base();
-        self.num_files = self.count_code_files(project_base.as_str()).expect("Failed to count code files");
-        self.num_crates = self.count_crates().expect("Failed to count crates");
-        self.project_name = self.get_project_name().expect("Failed to get project name");
+        let workspace = or_warn("read the workspace", Workspace::from_root(project_base.as_str()));
+        self.module_tree = or_warn("resolve the module tree", match &workspace {
+            Some(workspace) => ModuleTree::from_workspace(workspace),
+            None => ModuleTree::from_manifest_dir(project_base.as_str()),
+        });
+        self.module_stats = self.module_tree.stats();
+        self.num_modules = self.module_stats.total();
+        self.item_inventory = self.module_tree.items();
+        self.unsafe_code = self.module_tree.unsafe_code();
+        self.orphaned_files = or_warn("look for orphaned files", self.find_orphaned_files(project_base.as_str()));
+        self.recount_files();
+        let files = self.walker().files(project_base.as_str());
+        self.complexity = Self::measure_complexity(&files);
+        self.function_sizes = FunctionSizes::new(&self.complexity, &self.module_tree);
+        self.test_split = self.split_test_code(Path::new(&project_base), files);
+        self.workspace = workspace.and_then(|workspace| or_warn("break the workspace down per member", self.workspace_report(&workspace).map(Some)));
+        self.num_files = or_warn("count code files", self.count_code_files(project_base.as_str()));
+        self.dependencies = or_warn("analyze dependencies", self.analyze_dependencies());
+        self.num_crates = self.dependencies.crate_count();
+        if self.config.count_dependency_lines {
+            self.dependency_lines = or_warn("count dependency lines", self.count_dependency_lines().map(Some));
+        }
+        self.project_name = or_warn("get the project name", self.get_project_name());
         self.rustc_version = Self::get_rust_version();
-        self.num_modules = Self::count_rust_modules(project_base.as_str()).expect("Failed to count modules");
         self.rust_edition = self.get_rust_edition();
+        self.budget_violations = or_warn("check the budgets", self.check_budgets());
+        self.regressions = or_warn("compare with the baseline", self.compare_with_baseline());
+        self.analysis_time = started.elapsed();
     }
 }
 
+/// The result of one analysis, or its empty section with a `cargo:warning=` line saying what went
+/// wrong.
+#[cfg(feature = "default")]
+fn or_warn<T: Default>(analysis: &str, result: Result<T, LocCounterError>) -> T {
+    result.unwrap_or_else(|err| {
+        println!("cargo:warning=countroo could not {}, leaving it out: {}", analysis, err);
+        T::default()
+    })
+}
+
 #[cfg(feature = "default")]
 impl Default for CountRoo {
     fn default() -> Self {
@@ -948,7 +2098,6 @@ impl Default for CountRoo {
 }
 
 #[cfg(feature = "default")]
-
 impl CertainTypesCounter for CountRoo {
     fn count_lines_of_code_for_certain_types(&mut self) -> Result<usize, LocCounterError> {
         self.config.extensions = vec![
@@ -981,42 +2130,58 @@ impl CertainTypesCounter for CountRoo {
     }
 }
 #[cfg(feature = "default")]
-
 impl AllTypesCounter for CountRoo {
     fn count_lines_of_code_for_all_types(&mut self) -> Result<usize, LocCounterError> {
         self.count_lines_of_code()
     }
 }
 
+/// The type definitions at the top of every generated stats module. 🧾
+const STATS_MODULE_PRELUDE: &str = r#"// Generated by countroo from build.rs; do not edit.
+
+/// The lines and files of one language.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LanguageStats {
+    pub name: &'static str,
+    pub files: usize,
+    pub code: usize,
+    pub comment: usize,
+    pub blank: usize,
+}
+
+/// The statistics of the crate, as counted when it was built.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ProjectStats {
+    pub crate_name: &'static str,
+    pub edition: Option<&'static str>,
+    pub rustc_version: Option<&'static str>,
+    pub countroo_version: &'static str,
+    pub total_lines: usize,
+    pub code_lines: usize,
+    pub comment_lines: usize,
+    pub blank_lines: usize,
+    pub files: usize,
+    /// The packages of the project itself (every workspace member), not the ones it depends on.
+    pub crates: usize,
+    pub modules: usize,
+    /// Sorted by lines of code, most first.
+    pub languages: &'static [LanguageStats],
+}
+
+"#;
+
+/// A counted file: its canonical path, to tell which crate target it belongs to, and its row of
+/// the report.
+type CountedFile = (PathBuf, FileReport);
+
+/// Set this environment variable to tighten the baseline file instead of checking against it. 📌
+pub const TIGHTEN_BASELINE_ENV: &str = "COUNTROO_TIGHTEN_BASELINE";
+
+
 impl Display for CountRoo{
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        let extension_counts = self.calculate_extension_counts();
-        let total_count = extension_counts.values().sum::<usize>();
-
-        let s1_header = row!["Project ", "Module #", "Dependency #"];
-        let s1_data = row![self.project_name.as_ref().unwrap_or(&self.get_project_name().unwrap().unwrap()), self.num_modules, self.num_crates];
-        let s2_footer = row!["Rust Edition", "Rustc Version", "Countroo Version"];
-        let s2_data = row![self.rust_edition.as_ref().unwrap_or(&"None".to_string()), self.rustc_version.as_ref().unwrap_or(&"None".to_string()), "0.1.0"];
-        let c_header = row!["Language", "Count", "Percentage %"];
-
-        let mut tbl = Table::new();
-        tbl.add_row(s1_header);
-        tbl.add_row(s1_data);
-        tbl.add_row(c_header);
-
-        for (extension, count) in extension_counts {
-            let formatted_extension = format!("> {}", extension);
-            let formatted_count = count.to_formatted_string(&Locale::en);
-            let percentage = ((count as f32/total_count as f32) * 100.0).round();
-            let formatted_percentage = format!("{}%", percentage);
-
-            tbl.add_row(row![formatted_extension, formatted_count, formatted_percentage]);
-        }
-        tbl.add_row(s2_footer);
-        tbl.add_row(s2_data);
-        write!(f, "{}", tbl)
+        write!(f, "{}", self.report())
     }
-
 }
 
 #[cfg(feature = "default")]
@@ -1024,11 +2189,15 @@ impl Display for CountRoo{
 #[macro_export]
 macro_rules! count_some_generic {
     ($folder:ident) => {
-        let mut counter = $crate::CountRoo::new(Config::default());
-        counter.config.project_src_path = $folder.to_string_lossy().to_string();
+        let mut config = $crate::Config::default();
+        config.project_src_path = $folder.to_string_lossy().to_string();
+        let mut counter = $crate::CountRoo::new(config);
         counter.count_lines_of_code_for_certain_types().expect("Failed to count lines of code");
         let writer_boxed = Box::<$crate::output_adapters::StdoutWriter>::default();
-        counter.export(writer_boxed).expect("Failed to export to Stdout");
+        $crate::Exportable::export(&counter, writer_boxed).expect("Failed to export to Stdout");
+        if let Err(err) = counter.enforce_budgets().and_then(|_| counter.enforce_baseline()) {
+            panic!("{}", err);
+        }
     }
 }
 
@@ -1041,13 +2210,12 @@ macro_rules! count_some_generic {
 /// most to you.
 ///
 /// Under the hood, it creates a new `CountRoo` instance with default settings, initiates the
-/// counting process for specified file types, and then attempts to export the results to stdout.
+/// counting process for specified file types, and then prints the results to stdout.
 /// It's a quick and easy way to get insights without getting bogged down in configuration details.
 ///
 /// ## Requirements
 /// - The `default` feature must be enabled for this macro to be available.
-/// - `CountRoo`, `Config`, and a suitable `OutputWriter` (like `StdoutWriter`) must be defined
-///   and available in your project.
+/// - `CountRoo` and `Config` must be defined and available in your project.
 ///
 /// ## Example Usage
 /// Simply invoke the macro in your code where you need to perform the count:
@@ -1073,9 +2241,12 @@ macro_rules! count_some {
        count_some_generic!(pp);
     };
     (workspace: true) => {
-        // Logic when workspace toggle is true
-        let pp = Config::find_src_folder().unwrap();
-        let pp = pp.parent().unwrap();
+        // Count the whole workspace the crate belongs to, or the crate itself outside of one
+        let pp = $crate::Config::find_src_folder().unwrap();
+        let pp = $crate::workspace::Workspace::find(pp.parent().unwrap())
+            .expect("Failed to read the workspace")
+            .map(|workspace| workspace.root)
+            .unwrap_or_else(|| pp.parent().unwrap().to_path_buf());
         count_some_generic!(pp);
     };
     (workspace: false) => {
@@ -1097,11 +2268,26 @@ macro_rules! count_some {
 #[macro_export]
 macro_rules! count_folder {
      ($folder:ident) => {
-        let mut counter = $crate::CountRoo::default();
-        counter.config.project_src_path = $folder.to_string_lossy().to_string();
+        count_folder!($folder, print: true, rustc_env: false, stats_module: false);
+     };
+     ($folder:ident, print: $print:expr, rustc_env: $rustc_env:expr, stats_module: $stats_module:expr) => {
+        let mut config = $crate::Config::from_rel_file_path("config.txt").expect("Unable to find src folder");
+        config.project_src_path = $folder.to_string_lossy().to_string();
+        let mut counter = $crate::CountRoo::new(config);
         counter.count_lines_of_code_for_all_types().expect("Failed to count lines of code");
-        let writer_boxed = Box::<$crate::output_adapters::StdoutWriter>::default();
-        counter.export(writer_boxed).expect("Failed to export to Stdout");
+        if $print {
+            let writer_boxed = Box::<$crate::output_adapters::StdoutWriter>::default();
+            $crate::Exportable::export(&counter, writer_boxed).expect("Failed to export to Stdout");
+        }
+        if $rustc_env {
+            counter.emit_rustc_env().expect("Failed to emit the statistics as rustc environment variables");
+        }
+        if $stats_module {
+            counter.write_stats_module().expect("Failed to write countroo_stats.rs");
+        }
+        if let Err(err) = counter.enforce_budgets().and_then(|_| counter.enforce_baseline()) {
+            panic!("{}", err);
+        }
     }
 }
 
@@ -1114,13 +2300,12 @@ macro_rules! count_folder {
 ///
 /// This macro simplifies the process by automatically setting up `CountRoo` with a configuration
 /// based on a relative file path to "config.txt", initiating a thorough line count, and then
-/// exporting the tally to stdout. Ideal for when you need a bird's-eye view of your project's
+/// printing the tally to stdout. Ideal for when you need a bird's-eye view of your project's
 /// size and scope.
 ///
 /// ## Requirements
 /// - The `default` feature flag must be active for this macro to come into play.
-/// - Ensure that `CountRoo`, `Config`, and an appropriate `OutputWriter` (like `StdoutWriter`)
-///   are in scope and properly defined.
+/// - Ensure that `CountRoo` and `Config` are in scope and properly defined.
 ///
 /// ## Example Invocation
 /// To get a comprehensive line count of your project, simply call:
@@ -1131,9 +2316,18 @@ macro_rules! count_folder {
 ///     count_it_all!();
 ///     // If you're using it for a workspace, you can just use it like this inside any project's build.rs
 ///     countroo::count_it_all!(workspace: true);
+///     // Hand the statistics to rustc as `COUNTROO_*` variables as well, or instead of the table
+///     countroo::count_it_all!(rustc_env: true);
+///     countroo::count_it_all!(rustc_env: only);
 /// }
 /// ```
 ///
+/// With `rustc_env`, the crate being built can embed its own statistics, for instance in its
+/// `--version` output: `env!("COUNTROO_TOTAL_LINES")`, `env!("COUNTROO_CRATES")` and the rest of
+/// `CountRoo::rustc_env`. For typed access, `count_it_all!(stats_module: true)` writes
+/// `$OUT_DIR/countroo_stats.rs` with a `pub const STATS: ProjectStats`, to be pulled in with
+/// `include!(concat!(env!("OUT_DIR"), "/countroo_stats.rs"));`.
+///
 /// Whether you're evaluating the scale of a new project, or you need detailed metrics for
 /// documentation or analysis, `count_it_all!` offers a hassle-free solution to gather those
 /// insights with minimal setup. 🚀📊
@@ -1147,9 +2341,12 @@ macro_rules! count_it_all {
         count_folder!(pp);
     };
     (workspace: true) => {
-        // Logic when workspace toggle is true
+        // Count the whole workspace the crate belongs to, or the crate itself outside of one
         let pp = $crate::Config::find_src_folder().unwrap();
-        let pp = pp.parent().unwrap();
+        let pp = $crate::workspace::Workspace::find(pp.parent().unwrap())
+            .expect("Failed to read the workspace")
+            .map(|workspace| workspace.root)
+            .unwrap_or_else(|| pp.parent().unwrap().to_path_buf());
         count_folder!(pp);
     };
     (workspace: false) => {
@@ -1157,6 +2354,21 @@ macro_rules! count_it_all {
         let pp = $crate::Config::find_src_folder().unwrap();
         count_folder!(pp);
     };
+    (rustc_env: true) => {
+        // Print the table and emit `cargo:rustc-env=COUNTROO_*` lines
+        let pp = $crate::Config::find_src_folder().unwrap();
+        count_folder!(pp, print: true, rustc_env: true, stats_module: false);
+    };
+    (rustc_env: only) => {
+        // Emit `cargo:rustc-env=COUNTROO_*` lines without printing the table
+        let pp = $crate::Config::find_src_folder().unwrap();
+        count_folder!(pp, print: false, rustc_env: true, stats_module: false);
+    };
+    (stats_module: true) => {
+        // Print the table and write `$OUT_DIR/countroo_stats.rs`
+        let pp = $crate::Config::find_src_folder().unwrap();
+        count_folder!(pp, print: true, rustc_env: false, stats_module: true);
+    };
     ($other:tt) => { // Catch any other inputs that don't match the patterns above
         compile_error!(concat!("Invalid input to count_it_all! macro: ", stringify!($other)));
    }
@@ -1188,6 +2400,108 @@ mod tests {
     }
     
 
+    #[test]
+    fn test_count_it_all_with_rustc_env(){
+        count_it_all!(rustc_env: only);
+
+        let counter = CountRoo::new(Config::builder().project_path("src").extension("rs").build().unwrap());
+        let env = counter.rustc_env().unwrap().into_iter().collect::<HashMap<_, _>>();
+        assert_eq!(env["COUNTROO_PROJECT_NAME"], "countroo");
+        assert!(env["COUNTROO_TOTAL_LINES"].parse::<usize>().unwrap() > 1000);
+        assert_eq!(env["COUNTROO_CRATES"], "1");
+        assert_eq!(counter.package_count(), 1);
+        assert_eq!(env["COUNTROO_DEPENDENCIES"], counter.dependencies.dependency_count().to_string());
+        assert!(env.keys().all(|name| name.starts_with("COUNTROO_")));
+        assert!(env.values().all(|value| !value.contains('\n')));
+    }
+
+    #[test]
+    fn test_globs_are_anchored_at_the_project_base(){
+        let config = Config::builder().project_path("src").extension("rs").include("src/report/**").build().unwrap();
+        let mut counter = CountRoo::new(config);
+        let total_lines = counter.count_lines_of_code().unwrap();
+        let report = counter.report();
+        assert!(total_lines > 0);
+        assert_eq!(total_lines, report.project.total_lines);
+        assert_eq!(report.files.iter().map(|file| file.path.as_str()).collect::<Vec<_>>(), vec!["src/report/mod.rs"]);
+        assert_eq!(counter.calculate_extension_counts()["Rust"], counter.line_counts);
+    }
+
+    #[test]
+    fn test_malformed_globs_are_an_error_rather_than_a_panic(){
+        let mut config = Config::from_str_vec(vec!["rs".to_string()], false, "src".to_string()).unwrap();
+        config.exclude.push("src/[oops".to_string());
+        assert!(matches!(CountRoo::try_new(config), Err(LocCounterError::ConfigError(_))));
+    }
+
+    #[test]
+    fn test_report_adds_up(){
+        let counter = CountRoo::new(Config::builder().project_path("src").extension("rs").build().unwrap());
+        let report = counter.report();
+        assert_eq!(report.project.name.as_deref(), Some("countroo"));
+        assert_eq!(report.project.files, report.files.len());
+        assert_eq!(report.project.code_lines, report.files.iter().map(|file| file.code).sum::<usize>());
+        assert_eq!(report.languages[0].name, "Rust");
+        assert_eq!(report.languages.iter().map(|language| language.files).sum::<usize>(), report.files.len());
+        assert!(report.files.iter().any(|file| file.path == "src/report/mod.rs" && file.bytes > 0));
+        let library = report.crates.iter().find(|krate| krate.kind == module_tree::TargetKind::Lib).unwrap();
+        assert_eq!(library.root_file, "src/lib.rs");
+        assert!(library.code > 0 && library.functions > 0);
+        assert_eq!(report.toolchain.countroo_version, env!("CARGO_PKG_VERSION"));
+        assert!(report.to_string().contains("> Rust"));
+        assert!(!report.project.count_empty_lines);
+        assert_eq!(report.unsafe_code.project, counter.unsafe_code);
+        assert_eq!(report.unsafe_code.crates.len(), report.crates.len());
+        assert_eq!(report.unsafe_code.dependencies, None);
+        assert_eq!(report.modules.total(), counter.num_modules);
+        assert_eq!((report.items, report.test_split), (counter.item_inventory, counter.test_split));
+        assert_eq!(report.workspace.is_some(), counter.workspace.is_some());
+        assert_eq!(report.dependencies.declared.len(), counter.dependencies.declared.len());
+        assert_eq!(report.dependencies.resolved.len(), counter.dependencies.resolved.len());
+        assert_eq!(report.function_sizes.lengths.count, report.project.functions);
+        assert_eq!(report.function_sizes.crates.len(), report.crates.len());
+        assert_eq!(report.orphaned_files.files.len(), counter.orphaned_files.len());
+        assert_eq!((report.budget_violations.len(), report.regressions.len()), (counter.budget_violations.len(), counter.regressions.len()));
+        // `Display` shows the same languages, with the same percentages, as the report.
+        let rust = format!("{}%", report.project.percentage(&report.languages[0]));
+        assert!(counter.to_string().lines().any(|line| line.contains("> Rust") && line.contains(&rust)));
+        // ...and every other section, since it prints the report itself.
+        assert_eq!(counter.to_string(), report.to_string());
+        assert!(report.to_string().contains("Function Sizes") && report.to_string().contains("Dependencies"));
+    }
+
+    #[test]
+    fn test_report_lists_as_many_complex_functions_as_configured(){
+        let config = Config::from_line_separated_string("rs\nmost_complex_functions: 3\n", false, "src".to_string()).unwrap();
+        assert_eq!(config.most_complex_functions, 3);
+        assert!(Config::from_line_separated_string("rs\nmost_complex_functions: many\n", false, "src".to_string()).is_err());
+        assert_eq!(Config::builder().project_path("src").extension("rs").build().unwrap().most_complex_functions, DEFAULT_MOST_COMPLEX_FUNCTIONS);
+
+        let counter = CountRoo::new(Config::builder().project_path("src").extension("rs").most_complex_functions(3).build().unwrap());
+        let complexity = counter.report().complexity;
+        assert_eq!(complexity.most_complex.len(), 3);
+        assert_eq!(complexity.files.len(), 3);
+        assert_eq!(complexity.functions, counter.complexity.functions().count());
+        let top = &complexity.most_complex[0];
+        assert_eq!((top.cognitive, top.cyclomatic), (complexity.max_cognitive, counter.complexity.top(1)[0].cyclomatic));
+        assert!(top.file.starts_with("src/") && top.line > 0);
+    }
+
+    #[test]
+    fn test_stats_module_is_valid_rust(){
+        // The package root rather than `src`, so that `Cargo.toml` is counted too.
+        let config = Config::from_str_vec(vec!["rs".to_string(), "toml".to_string()], false, env!("CARGO_MANIFEST_DIR").to_string()).unwrap();
+        let counter = CountRoo::new(config);
+        let module = counter.stats_module().unwrap();
+        let file = syn::parse_file(&module).unwrap();
+        assert_eq!(file.items.len(), 3);
+        assert!(module.contains("pub const STATS: ProjectStats = ProjectStats {"));
+        assert!(module.contains("crate_name: \"countroo\","));
+        assert!(module.contains("LanguageStats { name: \"Rust\", files: "));
+        assert!(module.contains("LanguageStats { name: \"TOML\", files: "));
+        assert!(counter.write_stats_module().is_err() || env::var_os("OUT_DIR").is_some());
+    }
+
     #[test]
     fn test_count_it_all_for_non_workspace(){
         count_it_all!(workspace: false);
@@ -1215,6 +2529,16 @@ mod tests {
             config_path: None,
             extensions: vec!["rs".to_string()],
             count_empty_lines: false,
+            languages: Vec::new(),
+            respect_ignore_files: true,
+            include: Vec::new(),
+            exclude: Vec::new(),
+            exclude_orphaned_files: false,
+            count_dependency_lines: false,
+            vendor_dir: None,
+            budgets: Budgets::default(),
+            baseline: None,
+            most_complex_functions: DEFAULT_MOST_COMPLEX_FUNCTIONS,
         };
 
         let mut counter = CountRoo::new(config);
@@ -1336,6 +2660,181 @@ mod tests {
         assert_eq!(result1, result3);
     }
 
+    #[test]
+    fn test_line_separated_config_reads_include_and_exclude_globs(){
+        let config = Config::from_line_separated_string("rs\ninclude: crates/*/src/**\nexclude: **/generated/**\nexclude: *.pb.rs\n", false, "src".to_string()).unwrap();
+        assert_eq!(config.extensions, vec!["rs".to_string()]);
+        assert_eq!(config.include, vec!["crates/*/src/**".to_string()]);
+        assert_eq!(config.exclude, vec!["**/generated/**".to_string(), "*.pb.rs".to_string()]);
+
+        assert!(Config::from_line_separated_string("rs\nexclude: src/[oops\n", false, "src".to_string()).is_err());
+        assert!(Config::builder().project_path("src").exclude("src/[oops").build().is_err());
+    }
+
+    #[test]
+    fn test_orphaned_files_can_be_excluded(){
+        let config = Config::from_line_separated_string("rs\nexclude_orphaned_files: true\n", false, "src".to_string()).unwrap();
+        assert!(config.exclude_orphaned_files);
+        assert!(Config::from_line_separated_string("rs\nexclude_orphaned_files: maybe\n", false, "src".to_string()).is_err());
+
+        let counter = CountRoo::new(Config::builder().project_path("src").extension("rs").exclude_orphaned_files(true).build().unwrap());
+        assert!(counter.orphaned_files.is_empty());
+        assert!(counter.module_tree.files().iter().any(|file| file.ends_with("module_tree/mod.rs")));
+    }
+
+    #[test]
+    fn test_counted_files_keep_the_language_they_were_recognised_as(){
+        let dir = std::env::temp_dir().join(format!("countroo-counted-files-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        for (name, contents) in [("deploy", "#!/usr/bin/env bash\necho hi\n"), ("Makefile", "all:\n"), ("a.hpp", "int x;\n"), ("b.cpp", "int y;\n")] {
+            std::fs::write(dir.join(name), contents).unwrap();
+        }
+        let languages = LanguageRegistry::builtin();
+        let extensions = ["sh", "cpp"].map(String::from).to_vec();
+        let mut files = CountRoo::counted_files(&extensions, &languages, FileWalker::default().files(&dir)).into_iter()
+            .map(|(path, language)| (path.file_name().unwrap().to_string_lossy().to_string(), language.map(|language| language.name.clone())))
+            .collect::<Vec<_>>();
+        files.sort();
+        // `a.hpp` is C++, but only `.cpp` is configured; `Makefile` needs `mk`.
+        assert_eq!(files, vec![("b.cpp".to_string(), Some("C++".to_string())), ("deploy".to_string(), Some("Shell".to_string()))]);
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_path_dependencies_are_counted_crate_by_crate(){
+        let root = std::env::temp_dir().join(format!("countroo-path-dependency-lines-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&root);
+        for (name, manifest, source) in [
+            ("app", "[package]\nname = \"app\"\nversion = \"0.1.0\"\nedition = \"2021\"\n\n[dependencies]\nhelper = { path = \"../helper\" }\n", "pub fn app() {}\n"),
+            ("helper", "[package]\nname = \"helper\"\nversion = \"0.2.0\"\nedition = \"2021\"\n", "pub fn helper() {\n    unsafe {}\n}\n"),
+        ] {
+            std::fs::create_dir_all(root.join(name).join("src")).unwrap();
+            std::fs::write(root.join(name).join("Cargo.toml"), manifest).unwrap();
+            std::fs::write(root.join(name).join("src/lib.rs"), source).unwrap();
+        }
+        std::fs::write(root.join("app/Cargo.lock"), "version = 3\n\n[[package]]\nname = \"app\"\nversion = \"0.1.0\"\ndependencies = [\"helper\"]\n\n[[package]]\nname = \"helper\"\nversion = \"0.2.0\"\n").unwrap();
+
+        let config = Config::from_line_separated_string("rs\ncount_dependency_lines: true\n", false, root.join("app/src").to_string_lossy().to_string()).unwrap();
+        let counter = CountRoo::new(config);
+        let crates = counter.report().dependency_lines.unwrap();
+        assert_eq!(crates.len(), 1);
+        assert_eq!((crates[0].name.as_str(), crates[0].code, crates[0].unsafe_code.unsafe_blocks), ("helper", 3, 1));
+        assert!(crates[0].source_dir.is_some());
+        assert!(counter.to_string().lines().any(|line| line.contains("> helper 0.2.0") && !line.contains("not on disk")));
+        std::fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn test_a_failing_analysis_leaves_its_section_empty(){
+        let root = std::env::temp_dir().join(format!("countroo-broken-lockfile-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&root);
+        std::fs::create_dir_all(root.join("src")).unwrap();
+        std::fs::write(root.join("Cargo.toml"), "[package]\nname = \"broken\"\nversion = \"0.1.0\"\nedition = \"2021\"\n").unwrap();
+        std::fs::write(root.join("Cargo.lock"), "this is not a lockfile [[[\n").unwrap();
+        std::fs::write(root.join("src/lib.rs"), "pub fn one() {}\n").unwrap();
+
+        let config = Config::from_str_vec(vec!["rs".to_string()], false, root.join("src").to_string_lossy().to_string()).unwrap();
+        let counter = CountRoo::new(config);
+        assert_eq!(counter.dependencies, DependencyReport::default());
+        assert_eq!(counter.project_name, Some("broken".to_string()));
+        assert_eq!(counter.report().project.code_lines, 1);
+        std::fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn test_strict_budgets_fail_with_the_offenders(){
+        let root = std::env::temp_dir().join(format!("countroo-budgets-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&root);
+        std::fs::create_dir_all(root.join("src")).unwrap();
+        std::fs::write(root.join("Cargo.toml"), "[package]\nname = \"budgets\"\nversion = \"0.1.0\"\nedition = \"2021\"\n").unwrap();
+        std::fs::write(root.join("src/lib.rs"), "pub fn short() {}\n\npub fn long() {\n    let a = 1;\n    let b = 2;\n    let _ = a + b;\n}\n").unwrap();
+        let src = root.join("src").to_string_lossy().to_string();
+
+        let config = Config::from_line_separated_string("rs\nmax_function_lines: 3\nmax_unsafe_blocks: 0\nstrict_budgets: true\n", false, src.clone()).unwrap();
+        assert_eq!(config.budgets.max_function_lines, Some(3));
+        let counter = CountRoo::new(config);
+        let violations = counter.budget_violations.iter().map(ToString::to_string).collect::<Vec<_>>();
+        assert_eq!(violations.len(), 1, "{:?}", violations);
+        assert!(violations[0].starts_with("max_function_lines: long ("), "{}", violations[0]);
+        assert!(violations[0].ends_with("is at 5, over the budget of 3"), "{}", violations[0]);
+        let err = counter.enforce_budgets().unwrap_err().to_string();
+        assert!(err.contains("max_function_lines: long"));
+
+        let mut lenient = Config::from_str_vec(vec!["rs".to_string()], false, src).unwrap();
+        lenient.budgets = Budgets { max_function_lines: Some(3), ..Budgets::default() };
+        let lenient = CountRoo::new(lenient);
+        assert_eq!(lenient.budget_violations.len(), 1);
+        assert!(lenient.enforce_budgets().is_ok());
+        std::fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn test_baseline_ratchets_on_regressions_only(){
+        let root = std::env::temp_dir().join(format!("countroo-ratchet-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&root);
+        std::fs::create_dir_all(root.join("src")).unwrap();
+        std::fs::write(root.join("Cargo.toml"), "[package]\nname = \"ratchet\"\nversion = \"0.1.0\"\nedition = \"2021\"\n").unwrap();
+        std::fs::write(root.join("src/lib.rs"), "pub fn a() {\n    let _ = 1;\n}\n").unwrap();
+        let analyze = || {
+            let mut config = Config::from_str_vec(vec!["rs".to_string()], false, root.join("src").to_string_lossy().to_string()).unwrap();
+            config.baseline = Some("countroo-baseline.json".to_string());
+            CountRoo::new(config)
+        };
+
+        analyze().enforce_baseline().unwrap();
+        assert!(root.join("countroo-baseline.json").is_file());
+
+        std::fs::write(root.join("src/lib.rs"), "pub fn a() {\n    unsafe {}\n    let _ = 1;\n}\n").unwrap();
+        let counter = analyze();
+        let regressions = counter.regressions.iter().map(ToString::to_string).collect::<Vec<_>>();
+        assert_eq!(regressions, vec![
+            "lines in src/lib.rs went from 3 to 4",
+            "unsafe blocks in src/lib.rs went from 0 to 1",
+            "lines in function src/lib.rs::a went from 3 to 4",
+        ]);
+        assert!(counter.enforce_baseline().unwrap_err().to_string().contains("3 regression(s)"));
+
+        std::fs::write(root.join("src/lib.rs"), "pub fn a() {}\n").unwrap();
+        let counter = analyze();
+        assert!(counter.regressions.is_empty());
+        counter.tighten_baseline().unwrap();
+        let baseline = Baseline::load(root.join("countroo-baseline.json")).unwrap().unwrap();
+        assert_eq!(baseline.files["src/lib.rs"], 1);
+        std::fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn test_virtual_workspace_is_broken_down_per_member(){
+        let root = std::env::temp_dir().join(format!("countroo-virtual-workspace-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&root);
+        std::fs::create_dir_all(&root).unwrap();
+        std::fs::write(root.join("Cargo.toml"), "[workspace]\nmembers = [\"crates/*\"]\n\n[workspace.package]\nedition = \"2021\"\n").unwrap();
+        for (name, source) in [("alpha", "pub fn a() {}\n"), ("beta", "mod b;\n")] {
+            let dir = root.join("crates").join(name);
+            std::fs::create_dir_all(dir.join("src")).unwrap();
+            std::fs::write(dir.join("Cargo.toml"), format!("[package]\nname = \"{}\"\nversion = \"0.1.0\"\nedition.workspace = true\n", name)).unwrap();
+            std::fs::write(dir.join("src/lib.rs"), source).unwrap();
+        }
+        std::fs::write(root.join("crates/beta/src/b.rs"), "fn b() {}\n\nfn c() {}\n").unwrap();
+
+        let config = Config::from_str_vec(vec!["rs".to_string()], false, root.to_string_lossy().to_string()).unwrap();
+        let counter = CountRoo::new(config);
+        assert_eq!(counter.project_name, root.file_name().map(|name| name.to_string_lossy().to_string()));
+        assert_eq!(counter.rust_edition, Some("2021".to_string()));
+
+        let workspace = counter.workspace.as_ref().unwrap();
+        assert!(workspace.is_virtual);
+        let members: Vec<(&str, usize)> = workspace.members.iter().map(|m| (m.name.as_str(), m.line_counts.code)).collect();
+        assert_eq!(members, vec![("alpha", 1), ("beta", 3)]);
+        assert_eq!(workspace.rollup().line_counts, LineCounts { code: 4, comment: 0, blank: 1 });
+        assert_eq!(workspace.rollup().modules.file, 1);
+        assert_eq!(counter.num_modules, 1);
+        assert_eq!(counter.package_count(), 2);
+        assert_eq!(counter.module_tree.crates.len(), 2);
+        std::fs::remove_dir_all(&root).unwrap();
+    }
+
     #[test]
     fn test_builder_pattern_produces_same_object_with_extension(){
         let src_path = Config::find_src_folder().unwrap();
@@ -1352,6 +2851,16 @@ mod tests {
             config_path: None,
             extensions: vec!["rs".to_string(),"py".to_string()],
             count_empty_lines: false,
+            languages: Vec::new(),
+            respect_ignore_files: true,
+            include: Vec::new(),
+            exclude: Vec::new(),
+            exclude_orphaned_files: false,
+            count_dependency_lines: false,
+            vendor_dir: None,
+            budgets: Budgets::default(),
+            baseline: None,
+            most_complex_functions: DEFAULT_MOST_COMPLEX_FUNCTIONS,
         };
 
         assert_eq!(config.project_src_path, config2.project_src_path);
@@ -1373,6 +2882,16 @@ mod tests {
             config_path: None,
             extensions: vec!["rs".to_string(),"py".to_string()],
             count_empty_lines: false,
+            languages: Vec::new(),
+            respect_ignore_files: true,
+            include: Vec::new(),
+            exclude: Vec::new(),
+            exclude_orphaned_files: false,
+            count_dependency_lines: false,
+            vendor_dir: None,
+            budgets: Budgets::default(),
+            baseline: None,
+            most_complex_functions: DEFAULT_MOST_COMPLEX_FUNCTIONS,
         };
 
         assert_eq!(config.project_src_path, config2.project_src_path);
@@ -1387,6 +2906,20 @@ pub mod prelude{
     pub use crate::Config;
     pub use crate::ConfigBuilder;
     pub use crate::CountRoo;
+    pub use crate::languages::{Language, LanguageDefinition, LanguageRegistry};
+    pub use crate::line_classifier::{CommentSyntax, LineCounts};
+    pub use crate::walker::FileWalker;
+    pub use crate::items::{ItemCount, ItemInventory};
+    pub use crate::test_code::TestSplit;
+    pub use crate::report::*;
+    pub use crate::baseline::{Baseline, FunctionBaseline, Metric, Regression};
+    pub use crate::budgets::{BudgetViolation, Budgets, CommentRatio, Measurements};
+    pub use crate::function_size::{Bucket, CrateFunctionSizes, Distribution, FunctionSizes, LENGTH_BUCKETS, LONG_FUNCTION_LINES, NESTING_BUCKETS};
+    pub use crate::complexity::{ComplexityReport, FileComplexity, FunctionComplexity};
+    pub use crate::unsafe_code::{forbids_unsafe_code, UnsafeStats};
+    pub use crate::dependencies::{cargo_home, CrateLines, DeclaredDependency, DependencyKind, DependencyKinds, DependencyLines, DependencyReport, DependencySource, DuplicateCrate, LockedPackage, Lockfile, SourceCounts, SourceLocator};
+    pub use crate::workspace::{MemberReport, Workspace, WorkspaceMember, WorkspaceReport};
+    pub use crate::module_tree::{CrateModules, CrateTarget, ModuleKind, ModuleNode, ModuleStats, ModuleTree, TargetKind};
     pub use crate::Analyzable;
     pub use crate::Exportable;
     pub use crate::LocCounterError;
@@ -0,0 +1,310 @@
+use std::fs;
+use std::iter::Sum;
+use std::ops::{Add, AddAssign};
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+use crate::languages::Language;
+use crate::LocCounterError;
+
+/// `LineCounts` 🧮 - How a file (or a whole project) splits into code, comments and air.
+///
+/// Every non-empty line is either **code** (it holds at least one token outside of a comment)
+/// or a **comment** (it holds nothing but comment text, doc comments included). Lines made of
+/// whitespace only are **blank**. A line such as `let x = 1; // one` counts as code.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "json-output", derive(schemars::JsonSchema))]
+pub struct LineCounts {
+    pub code: usize,
+    pub comment: usize,
+    pub blank: usize,
+}
+
+/// What a single line turned out to be.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LineKind {
+    Code,
+    Comment,
+    Blank,
+}
+
+impl LineCounts {
+    /// Counts one more line of the given kind.
+    pub fn record(&mut self, kind: LineKind) {
+        match kind {
+            LineKind::Code => self.code += 1,
+            LineKind::Comment => self.comment += 1,
+            LineKind::Blank => self.blank += 1,
+        }
+    }
+
+    /// Every physical line, blank or not.
+    pub fn total(&self) -> usize {
+        self.code + self.comment + self.blank
+    }
+
+    /// The lines that count towards a total, honouring `Config::count_empty_lines`.
+    pub fn counted(&self, count_empty_lines: bool) -> usize {
+        if count_empty_lines {
+            self.total()
+        } else {
+            self.code + self.comment
+        }
+    }
+}
+
+impl Add for LineCounts {
+    type Output = LineCounts;
+
+    fn add(self, other: LineCounts) -> LineCounts {
+        LineCounts {
+            code: self.code + other.code,
+            comment: self.comment + other.comment,
+            blank: self.blank + other.blank,
+        }
+    }
+}
+
+impl AddAssign for LineCounts {
+    fn add_assign(&mut self, other: LineCounts) {
+        *self = *self + other;
+    }
+}
+
+impl FromIterator<LineKind> for LineCounts {
+    fn from_iter<I: IntoIterator<Item = LineKind>>(iter: I) -> Self {
+        let mut counts = LineCounts::default();
+        iter.into_iter().for_each(|kind| counts.record(kind));
+        counts
+    }
+}
+
+impl Sum for LineCounts {
+    fn sum<I: Iterator<Item = LineCounts>>(iter: I) -> Self {
+        iter.fold(LineCounts::default(), Add::add)
+    }
+}
+
+/// `CommentSyntax` 💬 - The comment markers of a single language.
+///
+/// - **line_comments**: Markers that comment out the rest of the line (`//`, `#`, `--`).
+/// - **block_comments**: Start/end pairs such as `/*` and `*/`.
+/// - **nested_blocks**: Whether block comments nest, as they do in Rust, Swift or Haskell.
+/// - **docstrings**: Whether block comments are really docstrings, such as Python's `"""`: they
+///   only count as comments when they stand alone as a statement, with nothing before them on
+///   the line. Anywhere else (`x = """...`) they are string literals, and so code.
+///
+/// Doc comments (`///`, `//!`, `/** */`) are picked up by their plain comment prefixes.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct CommentSyntax {
+    pub line_comments: Vec<String>,
+    pub block_comments: Vec<(String, String)>,
+    pub nested_blocks: bool,
+    pub docstrings: bool,
+}
+
+impl CommentSyntax {
+    pub fn new(line_comments: &[&str], block_comments: &[(&str, &str)], nested_blocks: bool) -> Self {
+        CommentSyntax {
+            line_comments: line_comments.iter().map(|c| c.to_string()).collect(),
+            block_comments: block_comments.iter().map(|(s, e)| (s.to_string(), e.to_string())).collect(),
+            nested_blocks,
+            docstrings: false,
+        }
+    }
+
+    /// Marks the block comments as docstrings, see `docstrings`.
+    pub fn docstrings(mut self) -> Self {
+        self.docstrings = true;
+        self
+    }
+
+    fn line_comment_at(&self, rest: &str) -> bool {
+        self.line_comments.iter().any(|marker| rest.starts_with(marker.as_str()))
+    }
+
+    fn block_comment_at(&self, rest: &str) -> Option<usize> {
+        self.block_comments.iter().position(|(start, _)| rest.starts_with(start.as_str()))
+    }
+}
+
+/// Splits `contents` into code, comment and blank lines according to the syntax of `language`.
+///
+/// Block comments are tracked across lines; when the language nests block comments, every nested
+/// opener must be closed before code is recognised again. String literals count as code and are
+/// skipped as a whole, so a `//` inside `"http://..."` does not start a comment.
+pub fn classify(contents: &str, language: &Language) -> LineCounts {
+    classify_lines(contents, language).into_iter().collect()
+}
+
+/// Like `classify`, but keeps the kind of every line, in order, for callers that need to know
+/// which lines are which (the first entry is line 1).
+pub fn classify_lines(contents: &str, language: &Language) -> Vec<LineKind> {
+    let syntax = &language.comments;
+    let strings = &language.string_delimiters;
+    // Languages that do not use `'` for strings may still use it for char literals (`'"'`).
+    let char_literals = !strings.iter().any(|(start, _)| start == "'");
+
+    let mut kinds = Vec::new();
+    // The block comment we are currently inside of, and how deeply it is nested.
+    let mut open_block: Option<usize> = None;
+    let mut depth = 0usize;
+    // The string literal we are currently inside of; strings may span several lines.
+    let mut open_string: Option<usize> = None;
+
+    for line in contents.lines() {
+        let trimmed = line.trim();
+        if trimmed.is_empty() {
+            kinds.push(LineKind::Blank);
+            continue;
+        }
+
+        let mut has_code = open_string.is_some();
+        let mut rest = trimmed;
+        while let Some(c) = rest.chars().next() {
+            if let Some(string) = open_string {
+                let end = strings[string].1.as_str();
+                if c == '\\' {
+                    rest = &rest[c.len_utf8()..];
+                    rest = rest.chars().next().map_or(rest, |escaped| &rest[escaped.len_utf8()..]);
+                } else if rest.starts_with(end) {
+                    rest = &rest[end.len()..];
+                    open_string = None;
+                } else {
+                    rest = &rest[c.len_utf8()..];
+                }
+                continue;
+            }
+
+            if let Some(block) = open_block {
+                let (start, end) = &syntax.block_comments[block];
+                if rest.starts_with(end.as_str()) {
+                    rest = &rest[end.len()..];
+                    depth -= 1;
+                    if depth == 0 {
+                        open_block = None;
+                    }
+                } else if syntax.nested_blocks && rest.starts_with(start.as_str()) {
+                    rest = &rest[start.len()..];
+                    depth += 1;
+                } else {
+                    rest = &rest[c.len_utf8()..];
+                }
+                continue;
+            }
+
+            if c.is_whitespace() {
+                rest = &rest[c.len_utf8()..];
+            } else if syntax.line_comment_at(rest) {
+                break;
+            } else if let Some(block) = syntax.block_comment_at(rest).filter(|_| !syntax.docstrings || !has_code) {
+                rest = &rest[syntax.block_comments[block].0.len()..];
+                open_block = Some(block);
+                depth = 1;
+            } else if let Some(string) = strings.iter().position(|(start, _)| rest.starts_with(start.as_str())) {
+                rest = &rest[strings[string].0.len()..];
+                open_string = Some(string);
+                has_code = true;
+            } else {
+                has_code = true;
+                rest = &rest[char_literal_len(rest, char_literals).unwrap_or(c.len_utf8())..];
+            }
+        }
+
+        kinds.push(if has_code { LineKind::Code } else { LineKind::Comment });
+    }
+
+    kinds
+}
+
+/// Length of a char literal such as `'a'` or `'\''` at the start of `rest`, if there is one.
+/// Lifetimes like `'a` are not followed by a closing quote and are left alone.
+fn char_literal_len(rest: &str, enabled: bool) -> Option<usize> {
+    if !enabled || !rest.starts_with('\'') {
+        return None;
+    }
+    let mut chars = rest.char_indices().skip(1);
+    let (_, first) = chars.next()?;
+    if first != '\\' {
+        return chars.next().filter(|(_, c)| *c == '\'').map(|(index, _)| index + 1);
+    }
+    // Escapes such as `'\n'`, `'\''` or `'\u{1F980}'`.
+    chars.next()?;
+    chars.take(8)
+        .take_while(|(_, c)| !c.is_whitespace())
+        .find(|(_, c)| *c == '\'')
+        .map(|(index, _)| index + 1)
+}
+
+/// Reads a file from disk and classifies its lines. Files without a known `language` have
+/// every non-blank line counted as code.
+pub fn classify_file(path: &Path, language: Option<&Language>) -> Result<LineCounts, LocCounterError> {
+    let contents = fs::read_to_string(path).map_err(LocCounterError::IoError)?;
+    Ok(match language {
+        Some(language) => classify(&contents, language),
+        None => classify(&contents, &Language::plain("")),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::languages::LanguageRegistry;
+
+    fn language(name: &str) -> Language {
+        LanguageRegistry::builtin().get(name).unwrap().clone()
+    }
+
+    #[test]
+    fn test_rust_lines_are_split_into_code_comment_and_blank() {
+        let source = "//! Crate docs\n\n/// Adds one.\nfn add_one(x: i32) -> i32 {\n    x + 1 // trailing\n}\n";
+        let counts = classify(source, &language("Rust"));
+        assert_eq!(counts, LineCounts { code: 3, comment: 2, blank: 1 });
+    }
+
+    #[test]
+    fn test_nested_block_comments_in_rust() {
+        let source = "/* outer\n/* inner */\nstill comment */\nlet x = 1;\n/* a */ let y = 2;";
+        let counts = classify(source, &language("Rust"));
+        assert_eq!(counts, LineCounts { code: 2, comment: 3, blank: 0 });
+    }
+
+    #[test]
+    fn test_block_comments_do_not_nest_in_c() {
+        let source = "/* outer /* inner */\nint x = 1;\n";
+        let counts = classify(source, &language("C"));
+        assert_eq!(counts, LineCounts { code: 1, comment: 1, blank: 0 });
+    }
+
+    #[test]
+    fn test_comment_markers_inside_strings_are_code() {
+        let source = "let url = \"http://example.com /* not a comment\";\nlet quote = '\"';\nlet multi = \"first\n// still the string\n\";\n";
+        let counts = classify(source, &language("Rust"));
+        assert_eq!(counts, LineCounts { code: 5, comment: 0, blank: 0 });
+    }
+
+    #[test]
+    fn test_lifetimes_are_not_char_literals() {
+        let source = "fn f<'a>(x: &'a str) -> &'a str { x }\n// comment\n";
+        let counts = classify(source, &language("Rust"));
+        assert_eq!(counts, LineCounts { code: 1, comment: 1, blank: 0 });
+    }
+
+    #[test]
+    fn test_python_docstrings_are_comments_but_assigned_strings_are_code() {
+        let source = "def f():\n    \"\"\"Docs\n    more docs.\n    \"\"\"\n    x = \"\"\"data\n# not a comment\nstill data\"\"\"\n    return x\n";
+        let counts = classify(source, &language("Python"));
+        assert_eq!(counts, LineCounts { code: 5, comment: 3, blank: 0 });
+        let single_line = "y = '''one line'''  # trailing\n'''standalone'''\n";
+        assert_eq!(classify(single_line, &language("Python")), LineCounts { code: 1, comment: 1, blank: 0 });
+    }
+
+    #[test]
+    fn test_unknown_language_treats_everything_as_code() {
+        let counts = classify("# not a comment here\n\nvalue\n", &Language::plain("Text"));
+        assert_eq!(counts, LineCounts { code: 2, comment: 0, blank: 1 });
+        assert_eq!(counts.counted(false), 2);
+        assert_eq!(counts.counted(true), 3);
+    }
+}
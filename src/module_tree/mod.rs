@@ -0,0 +1,503 @@
+use std::collections::BTreeSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use cargo_toml_workspace::cargo_toml::{self, Product};
+use serde::{Deserialize, Serialize};
+use syn::ext::IdentExt;
+use syn::punctuated::Punctuated;
+use syn::{Attribute, Expr, Item, ItemMod, Lit, Meta, Token};
+
+use crate::items::ItemInventory;
+use crate::unsafe_code::UnsafeStats;
+use crate::workspace::Workspace;
+use crate::LocCounterError;
+
+/// The kind of compilation target a crate root belongs to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[cfg_attr(feature = "json-output", derive(schemars::JsonSchema))]
+#[serde(rename_all = "snake_case")]
+pub enum TargetKind {
+    Lib,
+    Bin,
+    Test,
+    Example,
+    Bench,
+    BuildScript,
+}
+
+/// A single compilation target of a package, e.g. `src/lib.rs` or `tests/smoke.rs`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CrateTarget {
+    pub name: String,
+    pub kind: TargetKind,
+    pub root_file: PathBuf,
+}
+
+/// How a module came to be: the crate root itself, a `mod foo { ... }` block, or a
+/// `mod foo;` declaration that pulls in another file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ModuleKind {
+    Root,
+    Inline,
+    File,
+}
+
+/// `ModuleNode` 🧩 - One module in a crate's module tree.
+///
+/// - **path**: The module path, starting with the crate name (`countroo::output_adapters`).
+/// - **file**: The file holding the module's items; for inline modules, the file they sit in.
+/// - **is_test**: Set for `#[cfg(test)]` modules and everything nested inside them.
+/// - **items**: The items declared directly in this module, see `ItemInventory`.
+/// - **unsafe_code**: The unsafe code written directly in this module, see `UnsafeStats`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ModuleNode {
+    pub name: String,
+    pub path: String,
+    pub file: PathBuf,
+    pub kind: ModuleKind,
+    pub is_test: bool,
+    pub items: ItemInventory,
+    pub unsafe_code: UnsafeStats,
+    pub children: Vec<ModuleNode>,
+}
+
+impl ModuleNode {
+    /// This module followed by all of its descendants, depth first.
+    pub fn iter(&self) -> Vec<&ModuleNode> {
+        let mut nodes = vec![self];
+        for child in &self.children {
+            nodes.extend(child.iter());
+        }
+        nodes
+    }
+}
+
+/// Module counts of a tree; the crate roots themselves are not counted.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "json-output", derive(schemars::JsonSchema))]
+pub struct ModuleStats {
+    pub inline: usize,
+    pub file: usize,
+    pub test: usize,
+}
+
+impl ModuleStats {
+    /// Every module, inline and file based alike. Test modules are already part of those two.
+    pub fn total(&self) -> usize {
+        self.inline + self.file
+    }
+}
+
+/// The module tree of one compilation target, plus whatever went wrong while resolving it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CrateModules {
+    pub target: CrateTarget,
+    pub root: ModuleNode,
+    /// `mod foo;` declarations for which no file could be found, as module paths.
+    pub unresolved: Vec<String>,
+    /// Files that exist but could not be read or parsed as Rust, with the reason.
+    pub parse_errors: Vec<(PathBuf, String)>,
+}
+
+impl CrateModules {
+    pub fn stats(&self) -> ModuleStats {
+        let mut stats = ModuleStats::default();
+        for node in self.root.iter() {
+            match node.kind {
+                ModuleKind::Root => continue,
+                ModuleKind::Inline => stats.inline += 1,
+                ModuleKind::File => stats.file += 1,
+            }
+            if node.is_test {
+                stats.test += 1;
+            }
+        }
+        stats
+    }
+
+    /// Every file that is compiled as part of this target.
+    pub fn files(&self) -> BTreeSet<PathBuf> {
+        self.root.iter().into_iter().map(|node| node.file.clone()).collect()
+    }
+
+    /// The items of every module of this target together.
+    pub fn items(&self) -> ItemInventory {
+        self.root.iter().into_iter().fold(ItemInventory::default(), |acc, node| acc + node.items)
+    }
+
+    /// The unsafe code of every module of this target together.
+    pub fn unsafe_code(&self) -> UnsafeStats {
+        self.root.iter().into_iter().map(|node| node.unsafe_code).sum()
+    }
+
+    /// Whether the crate root declares `#![forbid(unsafe_code)]`, which rules unsafe out for
+    /// the whole target.
+    pub fn forbids_unsafe_code(&self) -> bool {
+        self.root.unsafe_code.forbid_unsafe_code > 0
+    }
+}
+
+/// `ModuleTree` 🌳 - The real module structure of a package, resolved the way rustc does it.
+///
+/// Starting from every crate root cargo knows about (`src/lib.rs`, `src/main.rs`, `src/bin/*`,
+/// `tests/*`, `examples/*`, `benches/*` and `build.rs`, or whatever `Cargo.toml` points at),
+/// each file is parsed with `syn`, and `mod foo;` declarations are followed to `foo.rs` or
+/// `foo/mod.rs`, honouring `#[path = "..."]` attributes. Inline modules, file modules and
+/// `#[cfg(test)]` modules are told apart, and text that merely looks like `mod ` in comments,
+/// strings or docs is no longer counted.
+///
+/// ## Example Usage
+/// ```rust
+/// use countroo::prelude::*;
+/// let tree = ModuleTree::from_manifest_dir(env!("CARGO_MANIFEST_DIR")).unwrap();
+/// println!("{} modules", tree.stats().total());
+/// ```
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct ModuleTree {
+    pub crates: Vec<CrateModules>,
+}
+
+impl ModuleTree {
+    /// Resolves the module trees of every target of the package whose `Cargo.toml` lives in
+    /// `manifest_dir`.
+    pub fn from_manifest_dir(manifest_dir: impl AsRef<Path>) -> Result<Self, LocCounterError> {
+        let manifest_dir = manifest_dir.as_ref();
+        let targets = crate_targets(manifest_dir)?;
+        Ok(ModuleTree { crates: targets.into_iter().map(resolve_crate).collect() })
+    }
+
+    /// Resolves the module trees of every member of a workspace, one after the other.
+    pub fn from_workspace(workspace: &Workspace) -> Result<Self, LocCounterError> {
+        let mut tree = ModuleTree::default();
+        for member in &workspace.members {
+            tree.crates.extend(Self::from_manifest_dir(&member.manifest_dir)?.crates);
+        }
+        Ok(tree)
+    }
+
+    pub fn stats(&self) -> ModuleStats {
+        self.crates.iter().map(CrateModules::stats).fold(ModuleStats::default(), |acc, s| ModuleStats {
+            inline: acc.inline + s.inline,
+            file: acc.file + s.file,
+            test: acc.test + s.test,
+        })
+    }
+
+    /// Every file that is compiled as part of at least one target.
+    pub fn files(&self) -> BTreeSet<PathBuf> {
+        self.crates.iter().flat_map(CrateModules::files).collect()
+    }
+
+    /// Files that are compiled for tests only: the roots of `tests/` and `benches/` targets and
+    /// the files of `#[cfg(test)] mod foo;` modules, along with everything they include.
+    pub fn test_files(&self) -> BTreeSet<PathBuf> {
+        self.crates.iter()
+            .flat_map(|crate_modules| crate_modules.root.iter())
+            .filter(|node| node.is_test && node.kind != ModuleKind::Inline)
+            .map(|node| node.file.clone())
+            .collect()
+    }
+
+    /// The items of every target together.
+    pub fn items(&self) -> ItemInventory {
+        self.crates.iter().fold(ItemInventory::default(), |acc, crate_modules| acc + crate_modules.items())
+    }
+
+    /// The unsafe code of every target together.
+    pub fn unsafe_code(&self) -> UnsafeStats {
+        self.crates.iter().map(CrateModules::unsafe_code).sum()
+    }
+
+    /// The `.rs` files among `candidates` that no target compiles: left behind by a refactor,
+    /// never declared with `mod`, or only reachable through a module that was removed.
+    pub fn orphans(&self, candidates: &[PathBuf]) -> Vec<PathBuf> {
+        let compiled: BTreeSet<PathBuf> = self.files().into_iter().map(|file| canonical(&file)).collect();
+        candidates.iter()
+            .filter(|path| path.extension().is_some_and(|ext| ext == "rs"))
+            .filter(|path| !compiled.contains(&canonical(path)))
+            .cloned()
+            .collect()
+    }
+}
+
+/// The canonical form of a path so that `src/../src/a.rs` and `src/a.rs` compare equal.
+fn canonical(path: &Path) -> PathBuf {
+    fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf())
+}
+
+/// Lists the compilation targets of a package, using cargo's own target auto-discovery.
+pub fn crate_targets(manifest_dir: &Path) -> Result<Vec<CrateTarget>, LocCounterError> {
+    let manifest_path = manifest_dir.join("Cargo.toml");
+    let manifest = cargo_toml::Manifest::from_path(&manifest_path)
+        .map_err(|err| LocCounterError::TomlError(format!("{}: {}", manifest_path.display(), err)))?;
+    let Some(package) = &manifest.package else {
+        // A virtual workspace manifest has no targets of its own.
+        return Ok(Vec::new());
+    };
+    let package_name = package.name.replace('-', "_");
+
+    let mut targets = Vec::new();
+    let mut add = |kind: TargetKind, product: &Product, default_path: Option<&str>| {
+        let path = product.path.as_deref().or(default_path).map(|p| manifest_dir.join(p));
+        if let Some(root_file) = path.filter(|p| p.is_file()) {
+            let name = product.name.clone().unwrap_or_else(|| package_name.clone());
+            targets.push(CrateTarget { name, kind, root_file });
+        }
+    };
+
+    if let Some(lib) = &manifest.lib {
+        add(TargetKind::Lib, lib, Some("src/lib.rs"));
+    }
+    manifest.bin.iter().for_each(|p| add(TargetKind::Bin, p, None));
+    manifest.test.iter().for_each(|p| add(TargetKind::Test, p, None));
+    manifest.example.iter().for_each(|p| add(TargetKind::Example, p, None));
+    manifest.bench.iter().for_each(|p| add(TargetKind::Bench, p, None));
+    if let Some(build) = package.build.as_ref().and_then(|b| b.as_path()) {
+        let build_script = Product { name: Some("build-script-build".to_string()), ..Default::default() };
+        add(TargetKind::BuildScript, &build_script, build.to_str());
+    }
+
+    Ok(targets)
+}
+
+fn resolve_crate(target: CrateTarget) -> CrateModules {
+    let mut crate_modules = CrateModules {
+        root: ModuleNode {
+            name: target.name.clone(),
+            path: target.name.clone(),
+            file: target.root_file.clone(),
+            kind: ModuleKind::Root,
+            is_test: matches!(target.kind, TargetKind::Test | TargetKind::Bench),
+            items: ItemInventory::default(),
+            unsafe_code: UnsafeStats::default(),
+            children: Vec::new(),
+        },
+        target,
+        unresolved: Vec::new(),
+        parse_errors: Vec::new(),
+    };
+
+    let mut visited = BTreeSet::new();
+    let root_file = crate_modules.target.root_file.clone();
+    let module_dir = root_file.parent().map(Path::to_path_buf).unwrap_or_default();
+    let mut root = crate_modules.root.clone();
+    if let Some(file) = parse_file(&root_file, &mut crate_modules, &mut visited) {
+        let location = Location { file: &root_file, module_dir, path_dir: root_file.parent().map(Path::to_path_buf).unwrap_or_default() };
+        collect_modules(&file.attrs, &file.items, &location, &mut root, &mut crate_modules, &mut visited);
+    }
+    crate_modules.root = root;
+    crate_modules
+}
+
+fn parse_file(file: &Path, crate_modules: &mut CrateModules, visited: &mut BTreeSet<PathBuf>) -> Option<syn::File> {
+    if !visited.insert(file.to_path_buf()) {
+        return None;
+    }
+    let parsed = fs::read_to_string(file)
+        .map_err(|err| err.to_string())
+        .and_then(|source| syn::parse_file(&source).map_err(|err| err.to_string()));
+    match parsed {
+        Ok(parsed) => Some(parsed),
+        Err(err) => {
+            crate_modules.parse_errors.push((file.to_path_buf(), err));
+            None
+        }
+    }
+}
+
+/// Where the items currently being walked live.
+struct Location<'a> {
+    /// The file the items were parsed from.
+    file: &'a Path,
+    /// The directory `mod foo;` looks for `foo.rs` and `foo/mod.rs` in.
+    module_dir: PathBuf,
+    /// The directory `#[path = "..."]` attributes are relative to.
+    path_dir: PathBuf,
+}
+
+fn collect_modules(attrs: &[Attribute], items: &[Item], location: &Location, parent: &mut ModuleNode, crate_modules: &mut CrateModules, visited: &mut BTreeSet<PathBuf>) {
+    parent.items = ItemInventory::from_items(items);
+    parent.unsafe_code = UnsafeStats::from_items(attrs, items);
+    for item in items {
+        let Item::Mod(item_mod) = item else { continue };
+        let name = item_mod.ident.unraw().to_string();
+        let mut node = ModuleNode {
+            path: format!("{}::{}", parent.path, name),
+            file: location.file.to_path_buf(),
+            kind: ModuleKind::Inline,
+            is_test: parent.is_test || is_cfg_test(&item_mod.attrs),
+            items: ItemInventory::default(),
+            unsafe_code: UnsafeStats::default(),
+            children: Vec::new(),
+            name,
+        };
+
+        match &item_mod.content {
+            Some((_, inline_items)) => {
+                let nested = match path_attribute(item_mod) {
+                    Some(path) => location.path_dir.join(path),
+                    None => location.module_dir.join(&node.name),
+                };
+                let inner = Location { file: location.file, module_dir: nested.clone(), path_dir: nested };
+                collect_modules(&item_mod.attrs, inline_items, &inner, &mut node, crate_modules, visited);
+            }
+            None => {
+                node.kind = ModuleKind::File;
+                match module_file(item_mod, &node.name, location) {
+                    Some(file) => {
+                        node.file = file.clone();
+                        if let Some(parsed) = parse_file(&file, crate_modules, visited) {
+                            let module_dir = child_module_dir(&file, path_attribute(item_mod).is_some());
+                            let dir = file.parent().map(Path::to_path_buf).unwrap_or_default();
+                            let inner = Location { file: &file, module_dir, path_dir: dir };
+                            collect_modules(&parsed.attrs, &parsed.items, &inner, &mut node, crate_modules, visited);
+                        }
+                    }
+                    None => crate_modules.unresolved.push(node.path.clone()),
+                }
+            }
+        }
+        parent.children.push(node);
+    }
+}
+
+/// Finds the file behind `mod name;`: the `#[path]` target, `name.rs` or `name/mod.rs`.
+fn module_file(item_mod: &ItemMod, name: &str, location: &Location) -> Option<PathBuf> {
+    if let Some(path) = path_attribute(item_mod) {
+        return Some(location.path_dir.join(path)).filter(|p| p.is_file());
+    }
+    [location.module_dir.join(format!("{}.rs", name)), location.module_dir.join(name).join("mod.rs")]
+        .into_iter()
+        .find(|p| p.is_file())
+}
+
+/// `mod.rs` files, crate roots and files loaded through `#[path]` keep their children next to
+/// them; any other `foo.rs` keeps them in a `foo/` folder.
+fn child_module_dir(file: &Path, from_path_attribute: bool) -> PathBuf {
+    let dir = file.parent().map(Path::to_path_buf).unwrap_or_default();
+    if from_path_attribute || file.file_name().is_some_and(|n| n == "mod.rs") {
+        dir
+    } else {
+        dir.join(file.file_stem().unwrap_or_default())
+    }
+}
+
+fn path_attribute(item_mod: &ItemMod) -> Option<String> {
+    item_mod.attrs.iter().find(|attr| attr.path().is_ident("path")).and_then(|attr| match &attr.meta {
+        Meta::NameValue(name_value) => match &name_value.value {
+            Expr::Lit(expr) => match &expr.lit {
+                Lit::Str(path) => Some(path.value()),
+                _ => None,
+            },
+            _ => None,
+        },
+        _ => None,
+    })
+}
+
+/// Whether the attributes contain a `#[cfg(...)]` that requires `test`, such as `#[cfg(test)]`
+/// or `#[cfg(all(test, feature = "x"))]`. `#[cfg(not(test))]` does not count.
+pub fn is_cfg_test(attrs: &[Attribute]) -> bool {
+    attrs.iter()
+        .filter(|attr| attr.path().is_ident("cfg"))
+        .filter_map(|attr| attr.parse_args::<Meta>().ok())
+        .any(|meta| requires_test(&meta))
+}
+
+fn requires_test(meta: &Meta) -> bool {
+    match meta {
+        Meta::Path(path) => path.is_ident("test"),
+        Meta::List(list) if list.path.is_ident("all") || list.path.is_ident("any") => {
+            let nested = list.parse_args_with(Punctuated::<Meta, Token![,]>::parse_terminated);
+            let mut nested = nested.into_iter().flatten();
+            if list.path.is_ident("all") {
+                nested.any(|m| requires_test(&m))
+            } else {
+                nested.all(|m| requires_test(&m))
+            }
+        }
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn scratch_crate(name: &str, files: &[(&str, &str)]) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("countroo-modules-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join("Cargo.toml"), format!("[package]\nname = \"{}\"\nversion = \"0.1.0\"\nedition = \"2021\"\n", name)).unwrap();
+        for (path, contents) in files {
+            let path = dir.join(path);
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(path, contents).unwrap();
+        }
+        dir
+    }
+
+    #[test]
+    fn test_module_tree_resolves_file_inline_and_test_modules() {
+        let dir = scratch_crate("tree", &[
+            ("src/lib.rs", "//! Talks about mod foo in docs\n#![forbid(unsafe_code)]\nmod a;\n#[path = \"other/x.rs\"]\nmod b;\nmod c { mod d; }\n#[cfg(test)]\nmod tests { const S: &str = \"mod fake\"; }\nmod missing;\n"),
+            ("src/a.rs", "pub mod nested;\n"),
+            ("src/a/nested.rs", "fn f() { unsafe {} }\n"),
+            ("src/other/x.rs", "mod y;\n"),
+            ("src/other/y.rs", ""),
+            ("src/c/d.rs", ""),
+            ("src/main.rs", "fn main() {}\n"),
+            ("tests/smoke.rs", "mod common;\n"),
+            ("tests/common/mod.rs", ""),
+        ]);
+
+        let tree = ModuleTree::from_manifest_dir(&dir).unwrap();
+        let kinds: Vec<TargetKind> = tree.crates.iter().map(|c| c.target.kind).collect();
+        assert_eq!(kinds, vec![TargetKind::Lib, TargetKind::Bin, TargetKind::Test]);
+
+        let lib = &tree.crates[0];
+        let paths: Vec<&str> = lib.root.iter().iter().map(|n| n.path.as_str()).collect();
+        assert_eq!(paths, vec!["tree", "tree::a", "tree::a::nested", "tree::b", "tree::b::y", "tree::c", "tree::c::d", "tree::tests", "tree::missing"]);
+        assert_eq!(lib.unresolved, vec!["tree::missing".to_string()]);
+        assert_eq!(lib.stats(), ModuleStats { inline: 2, file: 6, test: 1 });
+        assert!(lib.files().contains(&dir.join("src/other/y.rs")));
+        assert_eq!(lib.root.children[0].children[0].items.functions.private, 1);
+        assert_eq!(lib.items().constants.private, 1);
+        assert!(lib.forbids_unsafe_code());
+        assert_eq!(lib.root.children[0].children[0].unsafe_code.unsafe_blocks, 1);
+        assert_eq!(lib.unsafe_code().unsafe_blocks, 1);
+
+        assert_eq!(tree.stats(), ModuleStats { inline: 2, file: 7, test: 2 });
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_orphaned_files_are_not_part_of_any_target() {
+        let dir = scratch_crate("orphans", &[
+            ("src/lib.rs", "mod a;\n"),
+            ("src/a.rs", "// mod b;\n"),
+            ("src/b.rs", ""),
+            ("src/old/mod.rs", "mod c;\n"),
+            ("src/old/c.rs", ""),
+            ("examples/demo.rs", "fn main() {}\n"),
+            ("build.rs", "fn main() {}\n"),
+        ]);
+
+        let tree = ModuleTree::from_manifest_dir(&dir).unwrap();
+        let candidates = crate::walker::FileWalker::default().files(&dir);
+        let orphans: Vec<PathBuf> = tree.orphans(&candidates).iter().map(|p| p.strip_prefix(&dir).unwrap().to_path_buf()).collect();
+        assert_eq!(orphans, vec![PathBuf::from("src/b.rs"), PathBuf::from("src/old/c.rs"), PathBuf::from("src/old/mod.rs")]);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_cfg_test_detection() {
+        let file: syn::File = syn::parse_str("#[cfg(test)] mod a {} #[cfg(all(test, unix))] mod b {} #[cfg(not(test))] mod c {} #[cfg(any(test, unix))] mod d {}").unwrap();
+        let flags: Vec<bool> = file.items.iter().map(|item| match item {
+            Item::Mod(m) => is_cfg_test(&m.attrs),
+            _ => unreachable!(),
+        }).collect();
+        assert_eq!(flags, vec![true, true, false, false]);
+    }
+}
@@ -1,10 +1,19 @@
+#[cfg(feature = "default")]
+use crate::report::Report;
 
-#[cfg( feature = "tabular-output")]
-
+/// Writes a `Report` somewhere, in some format. The text writers print the table the report
+/// `Display`s as, the structured ones serialize the report itself.
 #[cfg(feature = "default")]
 pub trait OutputWriter {
-    fn write(&self, data: &str) -> Result<(), std::io::Error>;
+    fn write(&self, report: &Report) -> Result<(), std::io::Error>;
+}
+
+/// Turns a serializer error into the `io::Error` writers return.
+#[cfg(any(feature = "json-output", feature = "xml-output", feature = "toml-output", feature = "yaml-output"))]
+fn invalid_data(err: impl std::fmt::Display) -> std::io::Error {
+    std::io::Error::new(std::io::ErrorKind::InvalidData, err.to_string())
 }
+
 #[cfg(feature = "default")]
 pub struct StdoutWriter {}
 
@@ -24,8 +33,8 @@ impl Default for StdoutWriter {
 
 #[cfg(feature = "default")]
 impl OutputWriter for StdoutWriter {
-    fn write(&self, data: &str) -> Result<(), std::io::Error> {
-        println!("{}", data);
+    fn write(&self, report: &Report) -> Result<(), std::io::Error> {
+        println!("{}", report);
         Ok(())
     }
 }
@@ -35,10 +44,17 @@ pub struct FileWriter {
     file_path: String,
 }
 
+#[cfg(feature = "default")]
+impl FileWriter {
+    pub fn new(file_path: &str) -> Self {
+        FileWriter { file_path: file_path.to_string() }
+    }
+}
+
 #[cfg(feature = "default")]
 impl OutputWriter for FileWriter {
-    fn write(&self, data: &str) -> Result<(), std::io::Error> {
-        std::fs::write(&self.file_path, data)
+    fn write(&self, report: &Report) -> Result<(), std::io::Error> {
+        std::fs::write(&self.file_path, report.to_string())
     }
 }
 
@@ -57,15 +73,13 @@ impl JsonWriter {
 
 #[cfg(feature = "json-output")]
 impl OutputWriter for JsonWriter {
-    fn write(&self, data: &str) -> Result<(), std::io::Error> {
-        let json: serde_json::Value = serde_json::from_str(data).unwrap();
-        let pretty_json = serde_json::to_string_pretty(&json).unwrap();
+    fn write(&self, report: &Report) -> Result<(), std::io::Error> {
+        let pretty_json = serde_json::to_string_pretty(report).map_err(invalid_data)?;
         std::fs::write(&self.file_path, pretty_json)
     }
 }
 
 #[cfg(feature = "xml-output")]
-
 pub struct XmlWriter{
     file_path: String,
 }
@@ -79,32 +93,9 @@ impl XmlWriter {
 
 #[cfg(feature = "xml-output")]
 impl OutputWriter for XmlWriter {
-    fn write(&self, data: &str) -> Result<(), std::io::Error> {
-        // let mut writer = std::fs::File::create(&self.file_path)?;
-        // let mut writer = quick_xml::Writer::new(writer);
-        // let reader = quick_xml::Reader::from_str(data);
-        // let mut buf = Vec::new();
-        // for result in reader.into_iter() {
-        //     match result {
-        //         Ok(quick_xml::events::Event::Start(ref e)) => {
-        //             writer.write_event(quick_xml::events::Event::Start(e.clone()))?;
-        //         }
-        //         Ok(quick_xml::events::Event::End(ref e)) => {
-        //             writer.write_event(quick_xml::events::Event::End(e.clone()))?;
-        //         }
-        //         Ok(quick_xml::events::Event::Text(e)) => {
-        //             writer.write_event(quick_xml::events::Event::Text(e))?;
-        //         }
-        //         Ok(quick_xml::events::Event::Eof) => {
-        //             writer.write_event(quick_xml::events::Event::Eof)?;
-        //         }
-        //         Err(e) => {
-        //             return Err(std::io::Error::new(std::io::ErrorKind::Other, e));
-        //         }
-        //         _ => {}
-        //     }
-        // }
-        Ok(())
+    fn write(&self, report: &Report) -> Result<(), std::io::Error> {
+        let xml = report.to_xml().map_err(invalid_data)?;
+        std::fs::write(&self.file_path, xml)
     }
 }
 
@@ -122,8 +113,9 @@ impl TomlWriter {
 
 #[cfg(feature = "toml-output")]
 impl OutputWriter for TomlWriter {
-    fn write(&self, data: &str) -> Result<(), std::io::Error> {
-        std::fs::write(&self.file_path, data)
+    fn write(&self, report: &Report) -> Result<(), std::io::Error> {
+        let toml = report.to_toml().map_err(invalid_data)?;
+        std::fs::write(&self.file_path, toml)
     }
 }
 
@@ -140,10 +132,180 @@ impl YamlWriter {
 }
 #[cfg(feature = "yaml-output")]
 impl OutputWriter for YamlWriter {
-    fn write(&self, data: &str) -> Result<(), std::io::Error> {
-        let yaml: serde_yaml::Value = serde_yaml::from_str(data).unwrap();
-        let pretty_yaml = serde_yaml::to_string(&yaml).unwrap();
-        std::fs::write(&self.file_path, pretty_yaml)
+    fn write(&self, report: &Report) -> Result<(), std::io::Error> {
+        let yaml = report.to_yaml().map_err(invalid_data)?;
+        std::fs::write(&self.file_path, yaml)
+    }
+}
+
+/// The table a `CsvWriter` writes.
+#[cfg(feature = "csv-output")]
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum CsvTable {
+    /// One row per language, as `Display` shows them: language, code, comment, blank and the
+    /// share of `total_lines` in whole percents (`ProjectReport::percentage`).
+    #[default]
+    Languages,
+    /// One row per file: path, language, code, comment, blank and bytes.
+    Files,
+}
+
+/// `CsvWriter` 📈 - The report as a spreadsheet-ready table, comma or tab separated.
+///
+/// ## Example Usage
+/// ```rust
+/// use countroo::prelude::*;
+/// let mut report = Report::default();
+/// report.files.push(FileReport { path: "src/main.rs".to_string(), language: Some("Rust".to_string()), code: 10, comment: 2, blank: 1, bytes: 256 });
+/// let writer = CsvWriter::tsv("stats.tsv").table(CsvTable::Files).header(false);
+/// assert_eq!(writer.render(&report), "src/main.rs\tRust\t10\t2\t1\t256\n");
+/// ```
+#[cfg(feature = "csv-output")]
+pub struct CsvWriter {
+    file_path: String,
+    table: CsvTable,
+    delimiter: char,
+    header: bool,
+}
+
+#[cfg(feature = "csv-output")]
+impl CsvWriter {
+    /// Comma separated languages, with a header row.
+    pub fn new(file_path: &str) -> Self {
+        CsvWriter { file_path: file_path.to_string(), table: CsvTable::default(), delimiter: ',', header: true }
+    }
+
+    /// Tab separated languages, with a header row.
+    pub fn tsv(file_path: &str) -> Self {
+        CsvWriter::new(file_path).delimiter('\t')
+    }
+
+    pub fn table(mut self, table: CsvTable) -> Self {
+        self.table = table;
+        self
+    }
+
+    pub fn delimiter(mut self, delimiter: char) -> Self {
+        self.delimiter = delimiter;
+        self
+    }
+
+    pub fn header(mut self, header: bool) -> Self {
+        self.header = header;
+        self
+    }
+
+    /// The table as text. Fields holding the delimiter, a quote or a line break are quoted,
+    /// with quotes doubled.
+    pub fn render(&self, report: &Report) -> String {
+        let mut rows: Vec<Vec<String>> = Vec::new();
+        match self.table {
+            CsvTable::Languages => {
+                if self.header {
+                    rows.push(["language", "code", "comment", "blank", "percentage"].map(String::from).to_vec());
+                }
+                for language in &report.languages {
+                    rows.push(vec![
+                        language.name.clone(),
+                        language.code.to_string(),
+                        language.comment.to_string(),
+                        language.blank.to_string(),
+                        report.project.percentage(language).to_string(),
+                    ]);
+                }
+            }
+            CsvTable::Files => {
+                if self.header {
+                    rows.push(["path", "language", "code", "comment", "blank", "bytes"].map(String::from).to_vec());
+                }
+                for file in &report.files {
+                    rows.push(vec![
+                        file.path.clone(),
+                        file.language.clone().unwrap_or_default(),
+                        file.code.to_string(),
+                        file.comment.to_string(),
+                        file.blank.to_string(),
+                        file.bytes.to_string(),
+                    ]);
+                }
+            }
+        }
+
+        let delimiter = self.delimiter.to_string();
+        rows.iter()
+            .map(|row| row.iter().map(|field| self.quote(field)).collect::<Vec<_>>().join(&delimiter) + "\n")
+            .collect()
+    }
+
+    fn quote(&self, field: &str) -> String {
+        if field.contains([self.delimiter, '"', '\n', '\r']) {
+            format!("\"{}\"", field.replace('"', "\"\""))
+        } else {
+            field.to_string()
+        }
     }
 }
 
+#[cfg(feature = "csv-output")]
+impl OutputWriter for CsvWriter {
+    fn write(&self, report: &Report) -> Result<(), std::io::Error> {
+        std::fs::write(&self.file_path, self.render(report))
+    }
+}
+
+#[cfg(all(test, any(all(feature = "json-output", feature = "yaml-output", feature = "toml-output"), feature = "csv-output")))]
+mod tests {
+    use super::*;
+    use crate::report::LanguageReport;
+
+    #[test]
+    #[cfg(all(feature = "json-output", feature = "yaml-output", feature = "toml-output"))]
+    fn test_structured_writers_serialize_the_report() {
+        let mut report = Report::default();
+        report.project.name = Some("countroo".to_string());
+        report.languages.push(LanguageReport { name: "Rust".to_string(), files: 1, code: 10, comment: 2, blank: 1 });
+
+        let path = |extension| std::env::temp_dir().join(format!("countroo-structured-writers-{}.{}", std::process::id(), extension));
+        let writers: Vec<(&str, Box<dyn OutputWriter>)> = vec![
+            ("json", Box::new(JsonWriter::new(path("json").to_str().unwrap()))),
+            ("yaml", Box::new(YamlWriter::new(path("yaml").to_str().unwrap()))),
+            ("toml", Box::new(TomlWriter::new(path("toml").to_str().unwrap()))),
+        ];
+        for (extension, writer) in writers {
+            writer.write(&report).unwrap();
+            let contents = std::fs::read_to_string(path(extension)).unwrap();
+            assert!(!contents.contains("+---"), "{}", extension);
+            assert_eq!(Report::load(path(extension)).unwrap(), report, "{}", extension);
+            std::fs::remove_file(path(extension)).unwrap();
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "csv-output")]
+    fn test_csv_tables() {
+        let mut report = Report::default();
+        report.project.total_lines = 200;
+        report.project.code_lines = 150;
+        report.project.comment_lines = 50;
+        report.project.blank_lines = 40;
+        report.languages.push(LanguageReport { name: "Rust".to_string(), files: 2, code: 140, comment: 40, blank: 30 });
+        report.languages.push(LanguageReport { name: "Shell, sort of".to_string(), files: 1, code: 10, comment: 10, blank: 10 });
+        report.files.push(crate::report::FileReport { path: "src/\"odd\".rs".to_string(), language: Some("Rust".to_string()), code: 140, comment: 40, blank: 30, bytes: 4096 });
+        report.files.push(crate::report::FileReport { path: "LICENSE".to_string(), language: None, ..Default::default() });
+
+        let path = std::env::temp_dir().join(format!("countroo-csv-tables-{}.csv", std::process::id()));
+        CsvWriter::new(path.to_str().unwrap()).write(&report).unwrap();
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "language,code,comment,blank,percentage\nRust,140,40,30,90\n\"Shell, sort of\",10,10,10,10\n");
+        std::fs::remove_file(&path).unwrap();
+
+        let files = CsvWriter::tsv("unused.tsv").table(CsvTable::Files);
+        assert_eq!(files.render(&report), "path\tlanguage\tcode\tcomment\tblank\tbytes\n\"src/\"\"odd\"\".rs\"\tRust\t140\t40\t30\t4096\nLICENSE\t\t0\t0\t0\t0\n");
+        assert_eq!(CsvWriter::new("unused.csv").delimiter(';').header(false).render(&report).lines().next(), Some("Rust;140;40;30;90"));
+
+        // Blank lines count as soon as the report says so, even when a language has none.
+        report.project.count_empty_lines = true;
+        report.project.total_lines = 230;
+        report.languages[1].blank = 0;
+        assert_eq!(CsvWriter::new("unused.csv").header(false).render(&report), "Rust,140,40,30,91\n\"Shell, sort of\",10,10,0,9\n");
+    }
+}
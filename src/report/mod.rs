@@ -0,0 +1,2081 @@
+#[cfg(feature = "tabular-output")]
+use std::fmt;
+
+#[cfg(feature = "tabular-output")]
+use num_format::{Locale, ToFormattedString};
+#[cfg(feature = "tabular-output")]
+use prettytable::{row, Table};
+use serde::{Deserialize, Serialize};
+
+#[cfg(feature = "xml-output")]
+use crate::baseline::Metric;
+use crate::baseline::Regression;
+use crate::budgets::BudgetViolation;
+use crate::dependencies::{DependencyKind, DependencyKinds, DuplicateCrate, SourceCounts};
+use crate::function_size::Bucket;
+#[cfg(feature = "tabular-output")]
+use crate::function_size::LONG_FUNCTION_LINES;
+#[cfg(feature = "xml-output")]
+use crate::items::ItemCount;
+use crate::items::ItemInventory;
+#[cfg(feature = "xml-output")]
+use crate::line_classifier::LineCounts;
+use crate::module_tree::{ModuleStats, TargetKind};
+use crate::test_code::TestSplit;
+use crate::unsafe_code::UnsafeStats;
+#[cfg(any(feature = "json-output", feature = "yaml-output", feature = "toml-output", feature = "xml-output"))]
+use crate::LocCounterError;
+
+/// The version of the `Report` layout, written into every report as `schema_version`. Fields are
+/// only ever added within a version; renaming or removing one, or changing what it means, bumps it.
+pub const REPORT_SCHEMA_VERSION: u32 = 1;
+
+/// The JSON Schema of `Report`, as published in `schema/report.schema.json`.
+#[cfg(feature = "json-output")]
+pub const REPORT_SCHEMA_PATH: &str = "schema/report.schema.json";
+
+/// `Report` 🧾 - Everything an analysis found, as plain data every `OutputWriter` can serialize.
+///
+/// - **project**: The totals of the whole project.
+/// - **languages**: Lines per language, most code first.
+/// - **files**: Lines and size of every counted file, by path.
+/// - **crates**: Every crate target (library, binaries, tests, examples, benches, build script).
+/// - **modules**, **items** and **test_split**: Module counts, the item inventory and the lines of
+///   production versus test code, over every crate target.
+/// - **workspace**: The numbers of every workspace member, when the project is a workspace.
+/// - **dependencies**: The declared dependencies and the graph locked in `Cargo.lock`.
+/// - **dependency_lines**: The lines of every locked dependency, when `count_dependency_lines`
+///   is on.
+/// - **unsafe_code**: The unsafe surface of the project, per crate and module, and of the
+///   dependencies.
+/// - **complexity**: Averages and maxima, and the most complex files and functions.
+/// - **function_sizes**: How long and how deeply nested the functions are.
+/// - **orphaned_files**: `.rs` files no crate root reaches.
+/// - **budget_violations** and **regressions**: What went over a `Budgets` limit, and what got
+///   worse since the `Baseline`.
+/// - **toolchain**: The Rust edition and compiler, and the CountRoo version that did the counting.
+/// - **timings**: How long the analysis took.
+///
+/// `Display` prints the report as a table, which is what `StdoutWriter` and `FileWriter` write, so
+/// a report loaded back from a file prints the same as a fresh one. `schema_version` is
+/// `REPORT_SCHEMA_VERSION` for every report CountRoo produces, and `Report::json_schema`
+/// describes the layout for tools that ingest it.
+///
+/// ## Example Usage
+/// ```rust
+/// use countroo::prelude::*;
+/// let mut report = Report::default();
+/// report.languages.push(LanguageReport { name: "Rust".to_string(), files: 2, code: 120, comment: 30, blank: 10 });
+/// let json = serde_json::to_string(&report).unwrap();
+/// assert!(json.contains("\"languages\":[{\"name\":\"Rust\""));
+/// assert!(report.to_string().contains("> Rust"));
+/// assert!(json.starts_with("{\"schema_version\":1,"));
+/// ```
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "json-output", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "json-output", schemars(description = "Everything a CountRoo analysis found: project totals, languages, files, crates, modules, items, the production/test split, workspace members, dependencies, dependency lines, unsafe code, complexity, function sizes, orphaned files, budget violations, regressions, toolchain and timings."))]
+pub struct Report {
+    pub schema_version: u32,
+    pub project: ProjectReport,
+    pub languages: Vec<LanguageReport>,
+    pub files: Vec<FileReport>,
+    pub crates: Vec<CrateReport>,
+    #[serde(default)]
+    pub modules: ModuleStats,
+    #[serde(default)]
+    pub items: ItemInventory,
+    #[serde(default)]
+    pub test_split: TestSplit,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub workspace: Option<WorkspaceSummary>,
+    #[serde(default)]
+    pub dependencies: DependencySummary,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub dependency_lines: Option<Vec<DependencyLinesReport>>,
+    #[serde(default)]
+    pub unsafe_code: UnsafeReport,
+    #[serde(default)]
+    pub complexity: ComplexitySummary,
+    #[serde(default)]
+    pub function_sizes: FunctionSizesReport,
+    #[serde(default)]
+    pub orphaned_files: OrphanedFilesReport,
+    #[serde(default)]
+    pub budget_violations: Vec<BudgetViolation>,
+    #[serde(default)]
+    pub regressions: Vec<Regression>,
+    pub toolchain: ToolchainReport,
+    pub timings: Timings,
+}
+
+impl Default for Report {
+    fn default() -> Self {
+        Report {
+            schema_version: REPORT_SCHEMA_VERSION,
+            project: ProjectReport::default(),
+            languages: Vec::new(),
+            files: Vec::new(),
+            crates: Vec::new(),
+            modules: ModuleStats::default(),
+            items: ItemInventory::default(),
+            test_split: TestSplit::default(),
+            workspace: None,
+            dependencies: DependencySummary::default(),
+            dependency_lines: None,
+            unsafe_code: UnsafeReport::default(),
+            complexity: ComplexitySummary::default(),
+            function_sizes: FunctionSizesReport::default(),
+            orphaned_files: OrphanedFilesReport::default(),
+            budget_violations: Vec::new(),
+            regressions: Vec::new(),
+            toolchain: ToolchainReport::default(),
+            timings: Timings::default(),
+        }
+    }
+}
+
+impl Report {
+    /// The JSON Schema (draft 2020-12) of the report, generated from these types.
+    #[cfg(feature = "json-output")]
+    pub fn json_schema() -> String {
+        let schema = schemars::schema_for!(Report);
+        serde_json::to_string_pretty(&schema).expect("A JSON Schema always serializes") + "\n"
+    }
+
+    /// Reads a report back from a file written by `JsonWriter`, `YamlWriter`, `TomlWriter` or
+    /// `XmlWriter`, telling them apart by extension. Formats whose feature is off are a
+    /// `ConfigError`, like unknown extensions.
+    #[cfg(any(feature = "json-output", feature = "yaml-output", feature = "toml-output", feature = "xml-output"))]
+    pub fn load(path: impl AsRef<std::path::Path>) -> Result<Self, LocCounterError> {
+        let path = path.as_ref();
+        let contents = std::fs::read_to_string(path).map_err(LocCounterError::IoError)?;
+        let extension = path.extension().and_then(|ext| ext.to_str()).unwrap_or_default().to_lowercase();
+        match extension.as_str() {
+            #[cfg(feature = "json-output")]
+            "json" => serde_json::from_str(&contents).map_err(|err| LocCounterError::JsonError(format!("{}: {}", path.display(), err))),
+            #[cfg(feature = "yaml-output")]
+            "yaml" | "yml" => Self::from_yaml(&contents),
+            #[cfg(feature = "toml-output")]
+            "toml" => Self::from_toml(&contents),
+            #[cfg(feature = "xml-output")]
+            "xml" => Self::from_xml(&contents),
+            _ => Err(LocCounterError::ConfigError(format!("Cannot load a report from {}; is the output feature for it on?", path.display()))),
+        }
+    }
+
+    /// The report as a TOML document, with `[project]`, `[toolchain]` and `[timings]` tables
+    /// and `[[languages]]`, `[[files]]` and `[[crates]]` arrays.
+    #[cfg(feature = "toml-output")]
+    pub fn to_toml(&self) -> Result<String, LocCounterError> {
+        toml::to_string_pretty(self).map_err(|err| LocCounterError::TomlError(err.to_string()))
+    }
+
+    #[cfg(feature = "toml-output")]
+    pub fn from_toml(contents: &str) -> Result<Self, LocCounterError> {
+        toml::from_str(contents).map_err(|err| LocCounterError::TomlError(err.to_string()))
+    }
+
+    #[cfg(feature = "yaml-output")]
+    pub fn to_yaml(&self) -> Result<String, LocCounterError> {
+        serde_yaml::to_string(self).map_err(|err| LocCounterError::YamlError(err.to_string()))
+    }
+
+    #[cfg(feature = "yaml-output")]
+    pub fn from_yaml(contents: &str) -> Result<Self, LocCounterError> {
+        serde_yaml::from_str(contents).map_err(|err| LocCounterError::YamlError(err.to_string()))
+    }
+}
+
+#[cfg(feature = "tabular-output")]
+impl fmt::Display for Report {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let lines = |count: usize| count.to_formatted_string(&Locale::en);
+        let project = &self.project;
+        let toolchain = &self.toolchain;
+        let mut tbl = Table::new();
+        let modules = format!("{} ({} file, {} inline, {} test)", project.modules, self.modules.file, self.modules.inline, self.modules.test);
+        tbl.add_row(row!["Project", "File #", "Module #", "Dependency #", "Function #", "Unsafe #"]);
+        tbl.add_row(row![
+            project.name.as_deref().unwrap_or("Unknown"),
+            project.files,
+            modules,
+            project.dependencies,
+            project.functions,
+            project.unsafe_blocks
+        ]);
+        tbl.add_row(row!["Language", "File #", "Code", "Comment", "Blank", "Percentage %"]);
+        for language in &self.languages {
+            let percentage = format!("{}%", project.percentage(language));
+            tbl.add_row(row![format!("> {}", language.name), language.files, lines(language.code), lines(language.comment), lines(language.blank), percentage]);
+        }
+        tbl.add_row(row!["Crate", "File #", "Code", "Comment", "Blank", "Module #"]);
+        for krate in &self.crates {
+            tbl.add_row(row![format!("> {} ({:?})", krate.name, krate.kind), krate.files, lines(krate.code), lines(krate.comment), lines(krate.blank), krate.modules]);
+        }
+        tbl.add_row(row!["Total Lines", "", lines(project.code_lines), lines(project.comment_lines), lines(project.blank_lines), lines(project.total_lines)]);
+        if let Some(workspace) = &self.workspace {
+            tbl.add_row(row!["Member", "File #", "Code", "Comment", "Blank", "Module #"]);
+            for member in workspace.members.iter().chain(std::iter::once(&workspace.rollup())) {
+                tbl.add_row(row![format!("> {}", member.name), member.files, lines(member.code), lines(member.comment), lines(member.blank), member.modules.total()]);
+            }
+        }
+
+        let dependencies = &self.dependencies;
+        tbl.add_row(row!["Dependencies", "Direct", "Transitive", "Max Depth", "Duplicates"]);
+        tbl.add_row(row![
+            "> Cargo.lock",
+            dependencies.direct,
+            dependencies.transitive,
+            dependencies.max_depth,
+            dependencies.duplicates.iter().map(|d| format!("{} ({})", d.name, d.versions.join(", "))).collect::<Vec<_>>().join("\n")
+        ]);
+        let kinds = &dependencies.kinds;
+        tbl.add_row(row!["> Declared", format!("{} normal", kinds.normal), format!("{} dev", kinds.dev), format!("{} build", kinds.build), format!("{} target specific", kinds.target_specific)]);
+        let sources = &dependencies.sources;
+        tbl.add_row(row!["> Sources", format!("{} registry", sources.registry), format!("{} git", sources.git), format!("{} path", sources.path), format!("{} workspace", sources.workspace_inherited)]);
+        let non_registry = dependencies.declared.iter()
+            .filter(|dependency| dependency.source_kind != SourceKind::Registry)
+            .map(|dependency| format!("{} ({}): {}", dependency.name, dependency.member, dependency.source))
+            .collect::<Vec<_>>();
+        if !non_registry.is_empty() {
+            tbl.add_row(row!["> Non-Registry", non_registry.len(), H3 -> non_registry.join("\n")]);
+        }
+        if let Some(dependency_lines) = &self.dependency_lines {
+            let code = dependency_lines.iter().map(|krate| krate.code).sum::<usize>();
+            let missing = dependency_lines.iter().filter(|krate| krate.source_dir.is_none()).count();
+            let ratio = if project.code_lines == 0 { 0.0 } else { code as f64 / project.code_lines as f64 };
+            tbl.add_row(row![
+                "> Lines Pulled In",
+                format!("{} code", lines(code)),
+                format!("{:.1}x the project", ratio),
+                format!("{} crates found", dependency_lines.len() - missing),
+                format!("{} not on disk", missing)
+            ]);
+            tbl.add_row(row!["Dependency Lines", "Code", "Comment", "Blank", "Unsafe"]);
+            let mut crates = dependency_lines.iter().collect::<Vec<_>>();
+            crates.sort_by(|a, b| b.code.cmp(&a.code).then_with(|| a.name.cmp(&b.name)));
+            for krate in crates {
+                let name = format!("> {} {}", krate.name, krate.version);
+                if krate.source_dir.is_none() {
+                    tbl.add_row(row![name, H4 -> "not on disk"]);
+                    continue;
+                }
+                tbl.add_row(row![name, lines(krate.code), lines(krate.comment), lines(krate.blank), krate.unsafe_code.total()]);
+            }
+        }
+
+        let unsafe_code = &self.unsafe_code;
+        let unsafe_row = |label: &str, stats: &UnsafeStats| row![label, stats.unsafe_blocks, stats.unsafe_fns, stats.unsafe_impls + stats.unsafe_traits, stats.extern_blocks];
+        tbl.add_row(row!["Unsafe Code", "Blocks", "Functions", "Impls & Traits", "Extern Blocks"]);
+        tbl.add_row(unsafe_row("> Project", &unsafe_code.project));
+        let unsafe_modules = unsafe_code.modules.iter()
+            .filter(|module| module.unsafe_code.total() > 0)
+            .map(|module| format!("{} ({})", module.path, module.unsafe_code.total()))
+            .collect::<Vec<_>>();
+        if !unsafe_modules.is_empty() {
+            tbl.add_row(row!["> Unsafe Modules", unsafe_modules.len(), H3 -> unsafe_modules.join("\n")]);
+        }
+        let forbidding = unsafe_code.crates.iter().filter(|krate| krate.forbids_unsafe_code).map(|krate| krate.name.clone()).collect::<Vec<_>>();
+        tbl.add_row(row!["> #![forbid(unsafe_code)]", format!("{} of {} targets", forbidding.len(), unsafe_code.crates.len()), H3 -> forbidding.join("\n")]);
+        if let Some(dependencies) = &unsafe_code.dependencies {
+            tbl.add_row(unsafe_row("> Dependencies", dependencies));
+        }
+
+        let complexity = &self.complexity;
+        tbl.add_row(row!["Complexity", "Cyclomatic", "Cognitive", "Functions"]);
+        tbl.add_row(row!["> Average", format!("{:.2}", complexity.average_cyclomatic), format!("{:.2}", complexity.average_cognitive), complexity.functions]);
+        tbl.add_row(row!["> Max", complexity.max_cyclomatic, complexity.max_cognitive, ""]);
+        let hotspots = complexity.files.iter()
+            .map(|file| format!(
+                "{}: max {}/{}, avg {:.1}/{:.1}",
+                file.path, file.max_cyclomatic, file.max_cognitive, file.average_cyclomatic, file.average_cognitive
+            ))
+            .collect::<Vec<_>>();
+        if !hotspots.is_empty() {
+            tbl.add_row(row!["> Files (cyclomatic/cognitive)", hotspots.len(), H2 -> hotspots.join("\n")]);
+        }
+        for function in &complexity.most_complex {
+            tbl.add_row(row![format!("> {}", function.name), function.cyclomatic, function.cognitive, format!("{}:{}", function.file, function.line)]);
+        }
+
+        let sizes = &self.function_sizes;
+        let summary = |distribution: &DistributionReport| format!("p50 {} / p90 {} / max {}", distribution.p50, distribution.p90, distribution.max);
+        tbl.add_row(row!["Function Sizes", "Lines", "Nesting Depth", format!("Over {} Lines", LONG_FUNCTION_LINES)]);
+        tbl.add_row(row!["> Project", summary(&sizes.lengths), summary(&sizes.nesting), sizes.long_functions.len()]);
+        for crate_sizes in &sizes.crates {
+            tbl.add_row(row![
+                format!("> {} ({:?})", crate_sizes.name, crate_sizes.kind),
+                summary(&crate_sizes.lengths),
+                summary(&crate_sizes.nesting),
+                crate_sizes.long_functions
+            ]);
+        }
+        let histogram = |distribution: &DistributionReport| distribution.histogram.iter()
+            .map(|bucket| format!("{}: {}", bucket.label(), bucket.count))
+            .collect::<Vec<_>>()
+            .join("\n");
+        tbl.add_row(row!["> Histogram", histogram(&sizes.lengths), histogram(&sizes.nesting), ""]);
+        if !sizes.long_functions.is_empty() {
+            let long_functions = sizes.long_functions.iter()
+                .map(|function| format!("{} ({} lines, {}:{})", function.name, function.lines, function.file, function.line))
+                .collect::<Vec<_>>();
+            tbl.add_row(row!["> Long Functions", long_functions.len(), H2 -> long_functions.join("\n")]);
+        }
+        if !self.budget_violations.is_empty() {
+            let violations = self.budget_violations.iter().map(ToString::to_string).collect::<Vec<_>>();
+            tbl.add_row(row!["Budgets Exceeded", violations.len(), H3 -> violations.join("\n")]);
+        }
+        if !self.regressions.is_empty() {
+            let regressions = self.regressions.iter().map(ToString::to_string).collect::<Vec<_>>();
+            tbl.add_row(row!["Regressions", regressions.len(), H3 -> regressions.join("\n")]);
+        }
+
+        let split = &self.test_split;
+        tbl.add_row(row!["Rust Code", "Production", "Test", "Test/Code Ratio"]);
+        tbl.add_row(row!["> Lines", lines(split.production.code), lines(split.test.code), format!("{:.2}", split.ratio())]);
+        tbl.add_row(row!["Item", "Public", "Private", "Total"]);
+        for (kind, split, total) in self.items.rows() {
+            let (public, private) = split
+                .map(|count| (lines(count.public), lines(count.private)))
+                .unwrap_or_else(|| ("-".to_string(), "-".to_string()));
+            tbl.add_row(row![format!("> {}", kind), public, private, lines(total)]);
+        }
+        let orphans = &self.orphaned_files;
+        if !orphans.files.is_empty() {
+            let status = if orphans.excluded { "Excluded" } else { "Counted" };
+            tbl.add_row(row!["Orphaned Files", orphans.files.len(), status, H2 -> orphans.files.join("\n")]);
+        }
+
+        tbl.add_row(row!["Rust Edition", "Rustc Version", "Countroo Version", "Analysis", "", ""]);
+        tbl.add_row(row![
+            toolchain.edition.as_deref().unwrap_or("None"),
+            toolchain.rustc_version.as_deref().and_then(|version| version.lines().next()).unwrap_or("None"),
+            toolchain.countroo_version,
+            format!("{} ms", self.timings.analysis_ms),
+            "",
+            ""
+        ]);
+        write!(f, "{}", tbl)
+    }
+}
+
+/// The totals of the whole project.
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "json-output", derive(schemars::JsonSchema))]
+pub struct ProjectReport {
+    pub name: Option<String>,
+    /// The folder that was analysed.
+    pub path: String,
+    /// Code and comment lines, plus blank lines when `count_empty_lines` is on.
+    pub total_lines: usize,
+    /// Whether blank lines count towards `total_lines` and the language percentages.
+    #[serde(default)]
+    pub count_empty_lines: bool,
+    pub code_lines: usize,
+    pub comment_lines: usize,
+    pub blank_lines: usize,
+    pub files: usize,
+    pub modules: usize,
+    /// Crates the project depends on, transitive ones included.
+    pub dependencies: usize,
+    pub functions: usize,
+    pub unsafe_blocks: usize,
+}
+
+impl ProjectReport {
+    /// The share of `total_lines` a language takes up, rounded to a whole percent, as every
+    /// table shows it. 📊
+    pub fn percentage(&self, language: &LanguageReport) -> usize {
+        if self.total_lines == 0 {
+            return 0;
+        }
+        (language.counted(self.count_empty_lines) as f64 * 100.0 / self.total_lines as f64).round() as usize
+    }
+}
+
+/// The lines of every file of one language.
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "json-output", derive(schemars::JsonSchema))]
+pub struct LanguageReport {
+    /// The language name, or the file extension when no language claims the file.
+    pub name: String,
+    pub files: usize,
+    pub code: usize,
+    pub comment: usize,
+    pub blank: usize,
+}
+
+impl LanguageReport {
+    /// Code and comment lines, plus blank lines when `count_empty_lines` is on.
+    pub fn counted(&self, count_empty_lines: bool) -> usize {
+        self.code + self.comment + if count_empty_lines { self.blank } else { 0 }
+    }
+}
+
+/// The lines and size of one counted file.
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "json-output", derive(schemars::JsonSchema))]
+pub struct FileReport {
+    /// Relative to the project, with `/` separators.
+    pub path: String,
+    pub language: Option<String>,
+    pub code: usize,
+    pub comment: usize,
+    pub blank: usize,
+    pub bytes: u64,
+}
+
+impl FileReport {
+    /// Code and comment lines, plus blank lines when `count_empty_lines` is on.
+    pub fn counted(&self, count_empty_lines: bool) -> usize {
+        self.code + self.comment + if count_empty_lines { self.blank } else { 0 }
+    }
+}
+
+/// The numbers of one crate target, over the files its module tree reaches.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "json-output", derive(schemars::JsonSchema))]
+pub struct CrateReport {
+    pub name: String,
+    pub kind: TargetKind,
+    pub root_file: String,
+    pub files: usize,
+    pub modules: usize,
+    pub code: usize,
+    pub comment: usize,
+    pub blank: usize,
+    pub functions: usize,
+    pub unsafe_blocks: usize,
+}
+
+/// `WorkspaceSummary` 🏘️ - The numbers of every member of a workspace, see `WorkspaceReport`.
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "json-output", derive(schemars::JsonSchema))]
+pub struct WorkspaceSummary {
+    pub root: String,
+    /// A virtual manifest has a `[workspace]` but no `[package]`.
+    pub is_virtual: bool,
+    pub members: Vec<WorkspaceMemberReport>,
+}
+
+impl WorkspaceSummary {
+    /// All members added up into one row, named after the workspace folder.
+    pub fn rollup(&self) -> WorkspaceMemberReport {
+        let name = std::path::Path::new(&self.root).file_name().map(|name| name.to_string_lossy().to_string()).unwrap_or_default();
+        self.members.iter().fold(WorkspaceMemberReport { name, manifest_dir: ".".to_string(), ..Default::default() }, |mut total, member| {
+            total.files += member.files;
+            total.code += member.code;
+            total.comment += member.comment;
+            total.blank += member.blank;
+            total.modules = ModuleStats {
+                inline: total.modules.inline + member.modules.inline,
+                file: total.modules.file + member.modules.file,
+                test: total.modules.test + member.modules.test,
+            };
+            total.items += member.items;
+            total.test_split += member.test_split;
+            total
+        })
+    }
+}
+
+/// The numbers of one workspace member. Members nested in the folder of another member are left
+/// out of that member's numbers.
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "json-output", derive(schemars::JsonSchema))]
+pub struct WorkspaceMemberReport {
+    pub name: String,
+    /// Relative to the workspace root, with `/` separators; `.` for the root package.
+    pub manifest_dir: String,
+    pub files: usize,
+    pub code: usize,
+    pub comment: usize,
+    pub blank: usize,
+    pub modules: ModuleStats,
+    pub items: ItemInventory,
+    pub test_split: TestSplit,
+}
+
+/// `DependencySummary` 📦 - What the project depends on, see `DependencyReport`.
+///
+/// `direct`, `transitive`, `max_depth`, `duplicates` and `resolved` come from `Cargo.lock`, and
+/// are empty without one; `kinds`, `sources` and `declared` come from the manifests.
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "json-output", derive(schemars::JsonSchema))]
+pub struct DependencySummary {
+    pub has_lockfile: bool,
+    pub direct: usize,
+    pub transitive: usize,
+    pub max_depth: usize,
+    pub duplicates: Vec<DuplicateCrate>,
+    pub kinds: DependencyKinds,
+    pub sources: SourceCounts,
+    pub declared: Vec<DeclaredDependencyReport>,
+    /// Every locked package except the project's own, in `Cargo.lock` order.
+    pub resolved: Vec<LockedPackageReport>,
+}
+
+/// Where a declared dependency comes from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "json-output", derive(schemars::JsonSchema))]
+#[serde(rename_all = "snake_case")]
+pub enum SourceKind {
+    Registry,
+    Git,
+    Path,
+    /// `workspace = true` without a matching `[workspace.dependencies]` entry.
+    Unresolved,
+}
+
+/// One entry of a `[dependencies]`-like table of one of the project's manifests.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "json-output", derive(schemars::JsonSchema))]
+pub struct DeclaredDependencyReport {
+    pub name: String,
+    /// The package that declares the dependency.
+    pub member: String,
+    pub kind: DependencyKind,
+    /// The `cfg(...)` or target triple of a `[target.*]` table.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub target: Option<String>,
+    pub source_kind: SourceKind,
+    /// The version, git repository or folder, e.g. `1.0 (mirror)` or `git https://... @ v1`.
+    pub source: String,
+    pub workspace_inherited: bool,
+}
+
+/// One locked package.
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "json-output", derive(schemars::JsonSchema))]
+pub struct LockedPackageReport {
+    pub name: String,
+    pub version: String,
+    /// `registry+https://...` or `git+https://...`; absent for path packages.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub source: Option<String>,
+}
+
+/// The lines and unsafe code of one locked dependency. Dependencies whose sources are not on
+/// disk have no `source_dir`, and count zero everywhere.
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "json-output", derive(schemars::JsonSchema))]
+pub struct DependencyLinesReport {
+    pub name: String,
+    pub version: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub source_dir: Option<String>,
+    pub code: usize,
+    pub comment: usize,
+    pub blank: usize,
+    pub unsafe_code: UnsafeStats,
+}
+
+/// `UnsafeReport` ☢️ - Where the unsafe code is, see `UnsafeStats`.
+///
+/// - **project**: Every crate of the project together.
+/// - **crates**: Every crate target, and whether its root forbids unsafe code.
+/// - **modules**: Every module with unsafe code or a `#![forbid(unsafe_code)]`, by module path.
+/// - **dependencies**: Every dependency found on disk together; only there when
+///   `count_dependency_lines` is on.
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "json-output", derive(schemars::JsonSchema))]
+pub struct UnsafeReport {
+    pub project: UnsafeStats,
+    pub crates: Vec<CrateUnsafeReport>,
+    pub modules: Vec<ModuleUnsafeReport>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub dependencies: Option<UnsafeStats>,
+}
+
+/// The unsafe code of one crate target.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "json-output", derive(schemars::JsonSchema))]
+pub struct CrateUnsafeReport {
+    pub name: String,
+    pub kind: TargetKind,
+    pub forbids_unsafe_code: bool,
+    pub unsafe_code: UnsafeStats,
+}
+
+/// The unsafe code written directly in one module.
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "json-output", derive(schemars::JsonSchema))]
+pub struct ModuleUnsafeReport {
+    /// The module path, starting with the crate name.
+    pub path: String,
+    /// Relative to the project, with `/` separators.
+    pub file: String,
+    pub unsafe_code: UnsafeStats,
+}
+
+/// `ComplexitySummary` 🌀 - How hard the functions of the project are to follow, see
+/// `FunctionComplexity`.
+///
+/// The averages and maxima cover every function; `files` and `most_complex` only list the
+/// `Config::most_complex_functions` most complex ones, most complex first.
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "json-output", derive(schemars::JsonSchema))]
+pub struct ComplexitySummary {
+    pub functions: usize,
+    pub average_cyclomatic: f64,
+    pub average_cognitive: f64,
+    pub max_cyclomatic: usize,
+    pub max_cognitive: usize,
+    pub files: Vec<FileComplexityReport>,
+    pub most_complex: Vec<FunctionComplexityReport>,
+}
+
+/// The complexity of the functions of one file.
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "json-output", derive(schemars::JsonSchema))]
+pub struct FileComplexityReport {
+    /// Relative to the project, with `/` separators.
+    pub path: String,
+    pub functions: usize,
+    pub max_cyclomatic: usize,
+    pub max_cognitive: usize,
+    pub average_cyclomatic: f64,
+    pub average_cognitive: f64,
+}
+
+/// The complexity of one function or method.
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "json-output", derive(schemars::JsonSchema))]
+pub struct FunctionComplexityReport {
+    /// Qualified with the type, trait and inline modules it sits in (`Parser::next_token`).
+    pub name: String,
+    /// Relative to the project, with `/` separators.
+    pub file: String,
+    /// The line of the `fn` keyword.
+    pub line: usize,
+    pub lines: usize,
+    pub max_nesting: usize,
+    pub cyclomatic: usize,
+    pub cognitive: usize,
+}
+
+/// `FunctionSizesReport` 📏 - How long and how deeply nested the functions are, see
+/// `FunctionSizes`.
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "json-output", derive(schemars::JsonSchema))]
+pub struct FunctionSizesReport {
+    pub lengths: DistributionReport,
+    pub nesting: DistributionReport,
+    /// Functions spanning more than `LONG_FUNCTION_LINES` lines, longest first.
+    pub long_functions: Vec<FunctionComplexityReport>,
+    pub crates: Vec<CrateFunctionSizesReport>,
+}
+
+/// The function lengths and nesting depths of one crate target.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "json-output", derive(schemars::JsonSchema))]
+pub struct CrateFunctionSizesReport {
+    pub name: String,
+    pub kind: TargetKind,
+    pub lengths: DistributionReport,
+    pub nesting: DistributionReport,
+    /// How many of its functions span more than `LONG_FUNCTION_LINES` lines.
+    pub long_functions: usize,
+}
+
+/// The percentiles and histogram of a `Distribution`.
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "json-output", derive(schemars::JsonSchema))]
+pub struct DistributionReport {
+    pub count: usize,
+    pub max: usize,
+    pub mean: f64,
+    pub p50: usize,
+    pub p90: usize,
+    pub histogram: Vec<Bucket>,
+}
+
+/// `.rs` files below the project that no crate root reaches through its `mod` declarations.
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "json-output", derive(schemars::JsonSchema))]
+pub struct OrphanedFilesReport {
+    /// Whether they were left out of the counts, see `Config::exclude_orphaned_files`.
+    pub excluded: bool,
+    /// Relative to the project, with `/` separators.
+    pub files: Vec<String>,
+}
+
+/// The Rust toolchain of the project and the CountRoo that counted it.
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "json-output", derive(schemars::JsonSchema))]
+pub struct ToolchainReport {
+    pub edition: Option<String>,
+    pub rustc_version: Option<String>,
+    pub countroo_version: String,
+}
+
+/// How long the analysis took, in milliseconds.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "json-output", derive(schemars::JsonSchema))]
+pub struct Timings {
+    /// Resolving modules, items, complexity, dependencies and the rest in `analyze_code_base`.
+    pub analysis_ms: u64,
+    /// Counting the files into this report.
+    pub report_ms: u64,
+}
+
+#[cfg(feature = "xml-output")]
+impl Report {
+    /// The report as an XML document: totals and metrics as attributes, one element per
+    /// language, file and crate.
+    ///
+    /// ```xml
+    /// <report schema_version="1">
+    ///   <project name="countroo" path="." total_lines="6181" code_lines="4908" .../>
+    ///   <languages>
+    ///     <language name="Rust" files="18" code="4908" comment="1273" blank="624"/>
+    ///   </languages>
+    ///   ...
+    /// </report>
+    /// ```
+    pub fn to_xml(&self) -> Result<String, LocCounterError> {
+        let mut xml = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+        let mut serializer = quick_xml::se::Serializer::new(&mut xml);
+        serializer.indent(' ', 2);
+        XmlReport::from(self).serialize(serializer).map_err(|err| LocCounterError::XmlError(err.to_string()))?;
+        xml.push('\n');
+        Ok(xml)
+    }
+
+    /// Reads back a report written by `to_xml`.
+    pub fn from_xml(xml: &str) -> Result<Self, LocCounterError> {
+        quick_xml::de::from_str::<XmlReport>(xml)
+            .map(Report::from)
+            .map_err(|err| LocCounterError::XmlError(err.to_string()))
+    }
+}
+
+// The XML layout of `Report`. It mirrors the report type by type, renamed so that numbers and
+// names become attributes, which the serde names of `Report` itself cannot do without changing
+// the JSON, YAML and TOML output.
+
+#[cfg(feature = "xml-output")]
+#[derive(Serialize, Deserialize)]
+#[serde(rename = "report")]
+struct XmlReport {
+    #[serde(rename = "@schema_version")]
+    schema_version: u32,
+    project: XmlProject,
+    toolchain: XmlToolchain,
+    timings: XmlTimings,
+    languages: XmlLanguages,
+    files: XmlFiles,
+    crates: XmlCrates,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    dependency_lines: Option<XmlDependencyLines>,
+    #[serde(default)]
+    unsafe_code: XmlUnsafe,
+    #[serde(default)]
+    modules: XmlModuleStats,
+    #[serde(default)]
+    items: XmlItems,
+    #[serde(default)]
+    test_split: XmlTestSplit,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    workspace: Option<XmlWorkspace>,
+    #[serde(default)]
+    dependencies: XmlDependencies,
+    #[serde(default)]
+    complexity: XmlComplexity,
+    #[serde(default)]
+    function_sizes: XmlFunctionSizes,
+    #[serde(default)]
+    orphaned_files: XmlOrphanedFiles,
+    #[serde(default)]
+    budget_violations: XmlBudgetViolations,
+    #[serde(default)]
+    regressions: XmlRegressions,
+}
+
+#[cfg(feature = "xml-output")]
+#[derive(Serialize, Deserialize)]
+struct XmlLanguages {
+    #[serde(default)]
+    language: Vec<XmlLanguage>,
+}
+
+#[cfg(feature = "xml-output")]
+#[derive(Serialize, Deserialize)]
+struct XmlFiles {
+    #[serde(default)]
+    file: Vec<XmlFile>,
+}
+
+#[cfg(feature = "xml-output")]
+#[derive(Serialize, Deserialize)]
+struct XmlCrates {
+    #[serde(default, rename = "crate")]
+    krate: Vec<XmlCrate>,
+}
+
+#[cfg(feature = "xml-output")]
+#[derive(Serialize, Deserialize)]
+struct XmlProject {
+    #[serde(rename = "@name", default, skip_serializing_if = "Option::is_none")]
+    name: Option<String>,
+    #[serde(rename = "@path")]
+    path: String,
+    #[serde(rename = "@total_lines")]
+    total_lines: usize,
+    #[serde(rename = "@count_empty_lines", default)]
+    count_empty_lines: bool,
+    #[serde(rename = "@code_lines")]
+    code_lines: usize,
+    #[serde(rename = "@comment_lines")]
+    comment_lines: usize,
+    #[serde(rename = "@blank_lines")]
+    blank_lines: usize,
+    #[serde(rename = "@files")]
+    files: usize,
+    #[serde(rename = "@modules")]
+    modules: usize,
+    #[serde(rename = "@dependencies")]
+    dependencies: usize,
+    #[serde(rename = "@functions")]
+    functions: usize,
+    #[serde(rename = "@unsafe_blocks")]
+    unsafe_blocks: usize,
+}
+
+#[cfg(feature = "xml-output")]
+#[derive(Serialize, Deserialize)]
+struct XmlLanguage {
+    #[serde(rename = "@name")]
+    name: String,
+    #[serde(rename = "@files")]
+    files: usize,
+    #[serde(rename = "@code")]
+    code: usize,
+    #[serde(rename = "@comment")]
+    comment: usize,
+    #[serde(rename = "@blank")]
+    blank: usize,
+}
+
+#[cfg(feature = "xml-output")]
+#[derive(Serialize, Deserialize)]
+struct XmlFile {
+    #[serde(rename = "@path")]
+    path: String,
+    #[serde(rename = "@language", default, skip_serializing_if = "Option::is_none")]
+    language: Option<String>,
+    #[serde(rename = "@code")]
+    code: usize,
+    #[serde(rename = "@comment")]
+    comment: usize,
+    #[serde(rename = "@blank")]
+    blank: usize,
+    #[serde(rename = "@bytes")]
+    bytes: u64,
+}
+
+#[cfg(feature = "xml-output")]
+#[derive(Serialize, Deserialize)]
+struct XmlCrate {
+    #[serde(rename = "@name")]
+    name: String,
+    #[serde(rename = "@kind")]
+    kind: TargetKind,
+    #[serde(rename = "@root_file")]
+    root_file: String,
+    #[serde(rename = "@files")]
+    files: usize,
+    #[serde(rename = "@modules")]
+    modules: usize,
+    #[serde(rename = "@code")]
+    code: usize,
+    #[serde(rename = "@comment")]
+    comment: usize,
+    #[serde(rename = "@blank")]
+    blank: usize,
+    #[serde(rename = "@functions")]
+    functions: usize,
+    #[serde(rename = "@unsafe_blocks")]
+    unsafe_blocks: usize,
+}
+
+#[cfg(feature = "xml-output")]
+#[derive(Default, Serialize, Deserialize)]
+struct XmlModuleStats {
+    #[serde(rename = "@inline")]
+    inline: usize,
+    #[serde(rename = "@file")]
+    file: usize,
+    #[serde(rename = "@test")]
+    test: usize,
+}
+
+#[cfg(feature = "xml-output")]
+impl From<ModuleStats> for XmlModuleStats {
+    fn from(stats: ModuleStats) -> Self {
+        XmlModuleStats { inline: stats.inline, file: stats.file, test: stats.test }
+    }
+}
+
+#[cfg(feature = "xml-output")]
+impl From<XmlModuleStats> for ModuleStats {
+    fn from(xml: XmlModuleStats) -> Self {
+        ModuleStats { inline: xml.inline, file: xml.file, test: xml.test }
+    }
+}
+
+#[cfg(feature = "xml-output")]
+#[derive(Default, Serialize, Deserialize)]
+struct XmlItemCount {
+    #[serde(rename = "@public")]
+    public: usize,
+    #[serde(rename = "@private")]
+    private: usize,
+}
+
+#[cfg(feature = "xml-output")]
+#[derive(Default, Serialize, Deserialize)]
+struct XmlItems {
+    #[serde(rename = "@impls")]
+    impls: usize,
+    functions: XmlItemCount,
+    methods: XmlItemCount,
+    structs: XmlItemCount,
+    enums: XmlItemCount,
+    traits: XmlItemCount,
+    macros: XmlItemCount,
+    constants: XmlItemCount,
+    type_aliases: XmlItemCount,
+}
+
+#[cfg(feature = "xml-output")]
+impl From<ItemInventory> for XmlItems {
+    fn from(items: ItemInventory) -> Self {
+        let count = |count: ItemCount| XmlItemCount { public: count.public, private: count.private };
+        XmlItems {
+            impls: items.impls,
+            functions: count(items.functions),
+            methods: count(items.methods),
+            structs: count(items.structs),
+            enums: count(items.enums),
+            traits: count(items.traits),
+            macros: count(items.macros),
+            constants: count(items.constants),
+            type_aliases: count(items.type_aliases),
+        }
+    }
+}
+
+#[cfg(feature = "xml-output")]
+impl From<XmlItems> for ItemInventory {
+    fn from(xml: XmlItems) -> Self {
+        let count = |count: XmlItemCount| ItemCount { public: count.public, private: count.private };
+        ItemInventory {
+            impls: xml.impls,
+            functions: count(xml.functions),
+            methods: count(xml.methods),
+            structs: count(xml.structs),
+            enums: count(xml.enums),
+            traits: count(xml.traits),
+            macros: count(xml.macros),
+            constants: count(xml.constants),
+            type_aliases: count(xml.type_aliases),
+        }
+    }
+}
+
+#[cfg(feature = "xml-output")]
+#[derive(Default, Serialize, Deserialize)]
+struct XmlLineCounts {
+    #[serde(rename = "@code")]
+    code: usize,
+    #[serde(rename = "@comment")]
+    comment: usize,
+    #[serde(rename = "@blank")]
+    blank: usize,
+}
+
+#[cfg(feature = "xml-output")]
+#[derive(Default, Serialize, Deserialize)]
+struct XmlTestSplit {
+    production: XmlLineCounts,
+    test: XmlLineCounts,
+}
+
+#[cfg(feature = "xml-output")]
+impl From<TestSplit> for XmlTestSplit {
+    fn from(split: TestSplit) -> Self {
+        let lines = |lines: LineCounts| XmlLineCounts { code: lines.code, comment: lines.comment, blank: lines.blank };
+        XmlTestSplit { production: lines(split.production), test: lines(split.test) }
+    }
+}
+
+#[cfg(feature = "xml-output")]
+impl From<XmlTestSplit> for TestSplit {
+    fn from(xml: XmlTestSplit) -> Self {
+        let lines = |lines: XmlLineCounts| LineCounts { code: lines.code, comment: lines.comment, blank: lines.blank };
+        TestSplit { production: lines(xml.production), test: lines(xml.test) }
+    }
+}
+
+#[cfg(feature = "xml-output")]
+#[derive(Default, Serialize, Deserialize)]
+struct XmlWorkspace {
+    #[serde(rename = "@root")]
+    root: String,
+    #[serde(rename = "@is_virtual")]
+    is_virtual: bool,
+    #[serde(default)]
+    member: Vec<XmlMember>,
+}
+
+#[cfg(feature = "xml-output")]
+#[derive(Serialize, Deserialize)]
+struct XmlMember {
+    #[serde(rename = "@name")]
+    name: String,
+    #[serde(rename = "@manifest_dir")]
+    manifest_dir: String,
+    #[serde(rename = "@files")]
+    files: usize,
+    #[serde(rename = "@code")]
+    code: usize,
+    #[serde(rename = "@comment")]
+    comment: usize,
+    #[serde(rename = "@blank")]
+    blank: usize,
+    modules: XmlModuleStats,
+    items: XmlItems,
+    test_split: XmlTestSplit,
+}
+
+#[cfg(feature = "xml-output")]
+#[derive(Default, Serialize, Deserialize)]
+struct XmlDependencies {
+    #[serde(rename = "@has_lockfile")]
+    has_lockfile: bool,
+    #[serde(rename = "@direct")]
+    direct: usize,
+    #[serde(rename = "@transitive")]
+    transitive: usize,
+    #[serde(rename = "@max_depth")]
+    max_depth: usize,
+    kinds: XmlDependencyKinds,
+    sources: XmlSourceCounts,
+    duplicates: XmlDuplicates,
+    declared: XmlDeclared,
+    resolved: XmlResolved,
+}
+
+#[cfg(feature = "xml-output")]
+#[derive(Default, Serialize, Deserialize)]
+struct XmlDependencyKinds {
+    #[serde(rename = "@normal")]
+    normal: usize,
+    #[serde(rename = "@dev")]
+    dev: usize,
+    #[serde(rename = "@build")]
+    build: usize,
+    #[serde(rename = "@target_specific")]
+    target_specific: usize,
+}
+
+#[cfg(feature = "xml-output")]
+#[derive(Default, Serialize, Deserialize)]
+struct XmlSourceCounts {
+    #[serde(rename = "@registry")]
+    registry: usize,
+    #[serde(rename = "@git")]
+    git: usize,
+    #[serde(rename = "@path")]
+    path: usize,
+    #[serde(rename = "@workspace_inherited")]
+    workspace_inherited: usize,
+}
+
+#[cfg(feature = "xml-output")]
+#[derive(Default, Serialize, Deserialize)]
+struct XmlDuplicates {
+    #[serde(default, rename = "crate")]
+    krate: Vec<XmlDuplicate>,
+}
+
+#[cfg(feature = "xml-output")]
+#[derive(Serialize, Deserialize)]
+struct XmlDuplicate {
+    #[serde(rename = "@name")]
+    name: String,
+    #[serde(default)]
+    version: Vec<String>,
+}
+
+#[cfg(feature = "xml-output")]
+#[derive(Default, Serialize, Deserialize)]
+struct XmlDeclared {
+    #[serde(default)]
+    dependency: Vec<XmlDeclaredDependency>,
+}
+
+#[cfg(feature = "xml-output")]
+#[derive(Serialize, Deserialize)]
+struct XmlDeclaredDependency {
+    #[serde(rename = "@name")]
+    name: String,
+    #[serde(rename = "@member")]
+    member: String,
+    #[serde(rename = "@kind")]
+    kind: DependencyKind,
+    #[serde(rename = "@target", default, skip_serializing_if = "Option::is_none")]
+    target: Option<String>,
+    #[serde(rename = "@source_kind")]
+    source_kind: SourceKind,
+    #[serde(rename = "@source")]
+    source: String,
+    #[serde(rename = "@workspace_inherited")]
+    workspace_inherited: bool,
+}
+
+#[cfg(feature = "xml-output")]
+#[derive(Default, Serialize, Deserialize)]
+struct XmlResolved {
+    #[serde(default)]
+    package: Vec<XmlLockedPackage>,
+}
+
+#[cfg(feature = "xml-output")]
+#[derive(Serialize, Deserialize)]
+struct XmlLockedPackage {
+    #[serde(rename = "@name")]
+    name: String,
+    #[serde(rename = "@version")]
+    version: String,
+    #[serde(rename = "@source", default, skip_serializing_if = "Option::is_none")]
+    source: Option<String>,
+}
+
+#[cfg(feature = "xml-output")]
+#[derive(Serialize, Deserialize)]
+struct XmlDependencyLines {
+    #[serde(default, rename = "crate")]
+    krate: Vec<XmlDependencyCrate>,
+}
+
+#[cfg(feature = "xml-output")]
+#[derive(Serialize, Deserialize)]
+struct XmlDependencyCrate {
+    #[serde(rename = "@name")]
+    name: String,
+    #[serde(rename = "@version")]
+    version: String,
+    #[serde(rename = "@source_dir", default, skip_serializing_if = "Option::is_none")]
+    source_dir: Option<String>,
+    #[serde(rename = "@code")]
+    code: usize,
+    #[serde(rename = "@comment")]
+    comment: usize,
+    #[serde(rename = "@blank")]
+    blank: usize,
+    unsafe_code: XmlUnsafeStats,
+}
+
+#[cfg(feature = "xml-output")]
+#[derive(Default, Serialize, Deserialize)]
+struct XmlUnsafe {
+    project: XmlUnsafeStats,
+    crates: XmlCrateUnsafes,
+    modules: XmlModuleUnsafes,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    dependencies: Option<XmlUnsafeStats>,
+}
+
+#[cfg(feature = "xml-output")]
+#[derive(Default, Serialize, Deserialize)]
+struct XmlCrateUnsafes {
+    #[serde(default, rename = "crate")]
+    krate: Vec<XmlCrateUnsafe>,
+}
+
+#[cfg(feature = "xml-output")]
+#[derive(Default, Serialize, Deserialize)]
+struct XmlModuleUnsafes {
+    #[serde(default)]
+    module: Vec<XmlModuleUnsafe>,
+}
+
+#[cfg(feature = "xml-output")]
+#[derive(Serialize, Deserialize)]
+struct XmlCrateUnsafe {
+    #[serde(rename = "@name")]
+    name: String,
+    #[serde(rename = "@kind")]
+    kind: TargetKind,
+    #[serde(rename = "@forbids_unsafe_code")]
+    forbids_unsafe_code: bool,
+    unsafe_code: XmlUnsafeStats,
+}
+
+#[cfg(feature = "xml-output")]
+#[derive(Serialize, Deserialize)]
+struct XmlModuleUnsafe {
+    #[serde(rename = "@path")]
+    path: String,
+    #[serde(rename = "@file")]
+    file: String,
+    unsafe_code: XmlUnsafeStats,
+}
+
+#[cfg(feature = "xml-output")]
+#[derive(Default, Serialize, Deserialize)]
+struct XmlUnsafeStats {
+    #[serde(rename = "@unsafe_blocks")]
+    unsafe_blocks: usize,
+    #[serde(rename = "@unsafe_fns")]
+    unsafe_fns: usize,
+    #[serde(rename = "@unsafe_impls")]
+    unsafe_impls: usize,
+    #[serde(rename = "@unsafe_traits")]
+    unsafe_traits: usize,
+    #[serde(rename = "@extern_blocks")]
+    extern_blocks: usize,
+    #[serde(rename = "@forbid_unsafe_code")]
+    forbid_unsafe_code: usize,
+}
+
+#[cfg(feature = "xml-output")]
+impl From<UnsafeStats> for XmlUnsafeStats {
+    fn from(stats: UnsafeStats) -> Self {
+        XmlUnsafeStats {
+            unsafe_blocks: stats.unsafe_blocks,
+            unsafe_fns: stats.unsafe_fns,
+            unsafe_impls: stats.unsafe_impls,
+            unsafe_traits: stats.unsafe_traits,
+            extern_blocks: stats.extern_blocks,
+            forbid_unsafe_code: stats.forbid_unsafe_code,
+        }
+    }
+}
+
+#[cfg(feature = "xml-output")]
+impl From<XmlUnsafeStats> for UnsafeStats {
+    fn from(xml: XmlUnsafeStats) -> Self {
+        UnsafeStats {
+            unsafe_blocks: xml.unsafe_blocks,
+            unsafe_fns: xml.unsafe_fns,
+            unsafe_impls: xml.unsafe_impls,
+            unsafe_traits: xml.unsafe_traits,
+            extern_blocks: xml.extern_blocks,
+            forbid_unsafe_code: xml.forbid_unsafe_code,
+        }
+    }
+}
+
+#[cfg(feature = "xml-output")]
+#[derive(Default, Serialize, Deserialize)]
+struct XmlComplexity {
+    #[serde(rename = "@functions")]
+    functions: usize,
+    #[serde(rename = "@average_cyclomatic")]
+    average_cyclomatic: f64,
+    #[serde(rename = "@average_cognitive")]
+    average_cognitive: f64,
+    #[serde(rename = "@max_cyclomatic")]
+    max_cyclomatic: usize,
+    #[serde(rename = "@max_cognitive")]
+    max_cognitive: usize,
+    files: XmlComplexFiles,
+    most_complex: XmlComplexFunctions,
+}
+
+#[cfg(feature = "xml-output")]
+#[derive(Default, Serialize, Deserialize)]
+struct XmlComplexFiles {
+    #[serde(default)]
+    file: Vec<XmlComplexFile>,
+}
+
+#[cfg(feature = "xml-output")]
+#[derive(Default, Serialize, Deserialize)]
+struct XmlComplexFunctions {
+    #[serde(default)]
+    function: Vec<XmlComplexFunction>,
+}
+
+#[cfg(feature = "xml-output")]
+#[derive(Serialize, Deserialize)]
+struct XmlComplexFile {
+    #[serde(rename = "@path")]
+    path: String,
+    #[serde(rename = "@functions")]
+    functions: usize,
+    #[serde(rename = "@max_cyclomatic")]
+    max_cyclomatic: usize,
+    #[serde(rename = "@max_cognitive")]
+    max_cognitive: usize,
+    #[serde(rename = "@average_cyclomatic")]
+    average_cyclomatic: f64,
+    #[serde(rename = "@average_cognitive")]
+    average_cognitive: f64,
+}
+
+#[cfg(feature = "xml-output")]
+#[derive(Serialize, Deserialize)]
+struct XmlComplexFunction {
+    #[serde(rename = "@name")]
+    name: String,
+    #[serde(rename = "@file")]
+    file: String,
+    #[serde(rename = "@line")]
+    line: usize,
+    #[serde(rename = "@lines")]
+    lines: usize,
+    #[serde(rename = "@max_nesting")]
+    max_nesting: usize,
+    #[serde(rename = "@cyclomatic")]
+    cyclomatic: usize,
+    #[serde(rename = "@cognitive")]
+    cognitive: usize,
+}
+
+#[cfg(feature = "xml-output")]
+impl From<&FunctionComplexityReport> for XmlComplexFunction {
+    fn from(function: &FunctionComplexityReport) -> Self {
+        XmlComplexFunction {
+            name: function.name.clone(),
+            file: function.file.clone(),
+            line: function.line,
+            lines: function.lines,
+            max_nesting: function.max_nesting,
+            cyclomatic: function.cyclomatic,
+            cognitive: function.cognitive,
+        }
+    }
+}
+
+#[cfg(feature = "xml-output")]
+impl From<XmlComplexFunction> for FunctionComplexityReport {
+    fn from(xml: XmlComplexFunction) -> Self {
+        FunctionComplexityReport {
+            name: xml.name,
+            file: xml.file,
+            line: xml.line,
+            lines: xml.lines,
+            max_nesting: xml.max_nesting,
+            cyclomatic: xml.cyclomatic,
+            cognitive: xml.cognitive,
+        }
+    }
+}
+
+#[cfg(feature = "xml-output")]
+#[derive(Default, Serialize, Deserialize)]
+struct XmlFunctionSizes {
+    lengths: XmlDistribution,
+    nesting: XmlDistribution,
+    long_functions: XmlComplexFunctions,
+    crates: XmlCrateSizes,
+}
+
+#[cfg(feature = "xml-output")]
+#[derive(Default, Serialize, Deserialize)]
+struct XmlDistribution {
+    #[serde(rename = "@count")]
+    count: usize,
+    #[serde(rename = "@max")]
+    max: usize,
+    #[serde(rename = "@mean")]
+    mean: f64,
+    #[serde(rename = "@p50")]
+    p50: usize,
+    #[serde(rename = "@p90")]
+    p90: usize,
+    #[serde(default)]
+    bucket: Vec<XmlBucket>,
+}
+
+#[cfg(feature = "xml-output")]
+impl From<&DistributionReport> for XmlDistribution {
+    fn from(distribution: &DistributionReport) -> Self {
+        XmlDistribution {
+            count: distribution.count,
+            max: distribution.max,
+            mean: distribution.mean,
+            p50: distribution.p50,
+            p90: distribution.p90,
+            bucket: distribution.histogram.iter().map(|bucket| XmlBucket { from: bucket.from, to: bucket.to, count: bucket.count }).collect(),
+        }
+    }
+}
+
+#[cfg(feature = "xml-output")]
+impl From<XmlDistribution> for DistributionReport {
+    fn from(xml: XmlDistribution) -> Self {
+        DistributionReport {
+            count: xml.count,
+            max: xml.max,
+            mean: xml.mean,
+            p50: xml.p50,
+            p90: xml.p90,
+            histogram: xml.bucket.into_iter().map(|bucket| Bucket { from: bucket.from, to: bucket.to, count: bucket.count }).collect(),
+        }
+    }
+}
+
+#[cfg(feature = "xml-output")]
+#[derive(Serialize, Deserialize)]
+struct XmlBucket {
+    #[serde(rename = "@from")]
+    from: usize,
+    #[serde(rename = "@to", default, skip_serializing_if = "Option::is_none")]
+    to: Option<usize>,
+    #[serde(rename = "@count")]
+    count: usize,
+}
+
+#[cfg(feature = "xml-output")]
+#[derive(Default, Serialize, Deserialize)]
+struct XmlCrateSizes {
+    #[serde(default, rename = "crate")]
+    krate: Vec<XmlCrateSize>,
+}
+
+#[cfg(feature = "xml-output")]
+#[derive(Serialize, Deserialize)]
+struct XmlCrateSize {
+    #[serde(rename = "@name")]
+    name: String,
+    #[serde(rename = "@kind")]
+    kind: TargetKind,
+    #[serde(rename = "@long_functions")]
+    long_functions: usize,
+    lengths: XmlDistribution,
+    nesting: XmlDistribution,
+}
+
+#[cfg(feature = "xml-output")]
+#[derive(Default, Serialize, Deserialize)]
+struct XmlOrphanedFiles {
+    #[serde(rename = "@excluded")]
+    excluded: bool,
+    #[serde(default)]
+    file: Vec<String>,
+}
+
+#[cfg(feature = "xml-output")]
+#[derive(Default, Serialize, Deserialize)]
+struct XmlBudgetViolations {
+    #[serde(default)]
+    violation: Vec<XmlBudgetViolation>,
+}
+
+#[cfg(feature = "xml-output")]
+#[derive(Serialize, Deserialize)]
+struct XmlBudgetViolation {
+    #[serde(rename = "@budget")]
+    budget: String,
+    #[serde(rename = "@offender")]
+    offender: String,
+    #[serde(rename = "@actual")]
+    actual: String,
+    #[serde(rename = "@limit")]
+    limit: String,
+}
+
+#[cfg(feature = "xml-output")]
+#[derive(Default, Serialize, Deserialize)]
+struct XmlRegressions {
+    #[serde(default)]
+    regression: Vec<XmlRegression>,
+}
+
+#[cfg(feature = "xml-output")]
+#[derive(Serialize, Deserialize)]
+struct XmlRegression {
+    #[serde(rename = "@metric")]
+    metric: Metric,
+    #[serde(rename = "@subject")]
+    subject: String,
+    #[serde(rename = "@baseline")]
+    baseline: usize,
+    #[serde(rename = "@current")]
+    current: usize,
+}
+
+#[cfg(feature = "xml-output")]
+#[derive(Serialize, Deserialize)]
+struct XmlToolchain {
+    #[serde(rename = "@edition", default, skip_serializing_if = "Option::is_none")]
+    edition: Option<String>,
+    #[serde(rename = "@rustc_version", default, skip_serializing_if = "Option::is_none")]
+    rustc_version: Option<String>,
+    #[serde(rename = "@countroo_version")]
+    countroo_version: String,
+}
+
+#[cfg(feature = "xml-output")]
+#[derive(Serialize, Deserialize)]
+struct XmlTimings {
+    #[serde(rename = "@analysis_ms")]
+    analysis_ms: u64,
+    #[serde(rename = "@report_ms")]
+    report_ms: u64,
+}
+
+#[cfg(feature = "xml-output")]
+impl From<&Report> for XmlReport {
+    fn from(report: &Report) -> Self {
+        let project = &report.project;
+        XmlReport {
+            schema_version: report.schema_version,
+            project: XmlProject {
+                name: project.name.clone(),
+                path: project.path.clone(),
+                total_lines: project.total_lines,
+                count_empty_lines: project.count_empty_lines,
+                code_lines: project.code_lines,
+                comment_lines: project.comment_lines,
+                blank_lines: project.blank_lines,
+                files: project.files,
+                modules: project.modules,
+                dependencies: project.dependencies,
+                functions: project.functions,
+                unsafe_blocks: project.unsafe_blocks,
+            },
+            toolchain: XmlToolchain {
+                edition: report.toolchain.edition.clone(),
+                rustc_version: report.toolchain.rustc_version.clone(),
+                countroo_version: report.toolchain.countroo_version.clone(),
+            },
+            timings: XmlTimings { analysis_ms: report.timings.analysis_ms, report_ms: report.timings.report_ms },
+            languages: XmlLanguages {
+                language: report.languages.iter()
+                    .map(|language| XmlLanguage { name: language.name.clone(), files: language.files, code: language.code, comment: language.comment, blank: language.blank })
+                    .collect(),
+            },
+            files: XmlFiles {
+                file: report.files.iter()
+                    .map(|file| XmlFile { path: file.path.clone(), language: file.language.clone(), code: file.code, comment: file.comment, blank: file.blank, bytes: file.bytes })
+                    .collect(),
+            },
+            crates: XmlCrates {
+                krate: report.crates.iter()
+                    .map(|krate| XmlCrate {
+                        name: krate.name.clone(),
+                        kind: krate.kind,
+                        root_file: krate.root_file.clone(),
+                        files: krate.files,
+                        modules: krate.modules,
+                        code: krate.code,
+                        comment: krate.comment,
+                        blank: krate.blank,
+                        functions: krate.functions,
+                        unsafe_blocks: krate.unsafe_blocks,
+                    })
+                    .collect(),
+            },
+            dependency_lines: report.dependency_lines.as_ref().map(|crates| XmlDependencyLines {
+                krate: crates.iter()
+                    .map(|krate| XmlDependencyCrate {
+                        name: krate.name.clone(),
+                        version: krate.version.clone(),
+                        source_dir: krate.source_dir.clone(),
+                        code: krate.code,
+                        comment: krate.comment,
+                        blank: krate.blank,
+                        unsafe_code: krate.unsafe_code.into(),
+                    })
+                    .collect(),
+            }),
+            unsafe_code: XmlUnsafe {
+                project: report.unsafe_code.project.into(),
+                crates: XmlCrateUnsafes {
+                    krate: report.unsafe_code.crates.iter()
+                        .map(|krate| XmlCrateUnsafe { name: krate.name.clone(), kind: krate.kind, forbids_unsafe_code: krate.forbids_unsafe_code, unsafe_code: krate.unsafe_code.into() })
+                        .collect(),
+                },
+                modules: XmlModuleUnsafes {
+                    module: report.unsafe_code.modules.iter()
+                        .map(|module| XmlModuleUnsafe { path: module.path.clone(), file: module.file.clone(), unsafe_code: module.unsafe_code.into() })
+                        .collect(),
+                },
+                dependencies: report.unsafe_code.dependencies.map(XmlUnsafeStats::from),
+            },
+            complexity: XmlComplexity {
+                functions: report.complexity.functions,
+                average_cyclomatic: report.complexity.average_cyclomatic,
+                average_cognitive: report.complexity.average_cognitive,
+                max_cyclomatic: report.complexity.max_cyclomatic,
+                max_cognitive: report.complexity.max_cognitive,
+                files: XmlComplexFiles {
+                    file: report.complexity.files.iter()
+                        .map(|file| XmlComplexFile {
+                            path: file.path.clone(),
+                            functions: file.functions,
+                            max_cyclomatic: file.max_cyclomatic,
+                            max_cognitive: file.max_cognitive,
+                            average_cyclomatic: file.average_cyclomatic,
+                            average_cognitive: file.average_cognitive,
+                        })
+                        .collect(),
+                },
+                most_complex: XmlComplexFunctions { function: report.complexity.most_complex.iter().map(XmlComplexFunction::from).collect() },
+            },
+            modules: report.modules.into(),
+            items: report.items.into(),
+            test_split: report.test_split.into(),
+            workspace: report.workspace.as_ref().map(|workspace| XmlWorkspace {
+                root: workspace.root.clone(),
+                is_virtual: workspace.is_virtual,
+                member: workspace.members.iter()
+                    .map(|member| XmlMember {
+                        name: member.name.clone(),
+                        manifest_dir: member.manifest_dir.clone(),
+                        files: member.files,
+                        code: member.code,
+                        comment: member.comment,
+                        blank: member.blank,
+                        modules: member.modules.into(),
+                        items: member.items.into(),
+                        test_split: member.test_split.into(),
+                    })
+                    .collect(),
+            }),
+            dependencies: {
+                let dependencies = &report.dependencies;
+                XmlDependencies {
+                    has_lockfile: dependencies.has_lockfile,
+                    direct: dependencies.direct,
+                    transitive: dependencies.transitive,
+                    max_depth: dependencies.max_depth,
+                    kinds: XmlDependencyKinds {
+                        normal: dependencies.kinds.normal,
+                        dev: dependencies.kinds.dev,
+                        build: dependencies.kinds.build,
+                        target_specific: dependencies.kinds.target_specific,
+                    },
+                    sources: XmlSourceCounts {
+                        registry: dependencies.sources.registry,
+                        git: dependencies.sources.git,
+                        path: dependencies.sources.path,
+                        workspace_inherited: dependencies.sources.workspace_inherited,
+                    },
+                    duplicates: XmlDuplicates {
+                        krate: dependencies.duplicates.iter()
+                            .map(|duplicate| XmlDuplicate { name: duplicate.name.clone(), version: duplicate.versions.clone() })
+                            .collect(),
+                    },
+                    declared: XmlDeclared {
+                        dependency: dependencies.declared.iter()
+                            .map(|declared| XmlDeclaredDependency {
+                                name: declared.name.clone(),
+                                member: declared.member.clone(),
+                                kind: declared.kind,
+                                target: declared.target.clone(),
+                                source_kind: declared.source_kind,
+                                source: declared.source.clone(),
+                                workspace_inherited: declared.workspace_inherited,
+                            })
+                            .collect(),
+                    },
+                    resolved: XmlResolved {
+                        package: dependencies.resolved.iter()
+                            .map(|package| XmlLockedPackage { name: package.name.clone(), version: package.version.clone(), source: package.source.clone() })
+                            .collect(),
+                    },
+                }
+            },
+            function_sizes: XmlFunctionSizes {
+                lengths: (&report.function_sizes.lengths).into(),
+                nesting: (&report.function_sizes.nesting).into(),
+                long_functions: XmlComplexFunctions { function: report.function_sizes.long_functions.iter().map(XmlComplexFunction::from).collect() },
+                crates: XmlCrateSizes {
+                    krate: report.function_sizes.crates.iter()
+                        .map(|krate| XmlCrateSize {
+                            name: krate.name.clone(),
+                            kind: krate.kind,
+                            long_functions: krate.long_functions,
+                            lengths: (&krate.lengths).into(),
+                            nesting: (&krate.nesting).into(),
+                        })
+                        .collect(),
+                },
+            },
+            orphaned_files: XmlOrphanedFiles { excluded: report.orphaned_files.excluded, file: report.orphaned_files.files.clone() },
+            budget_violations: XmlBudgetViolations {
+                violation: report.budget_violations.iter()
+                    .map(|violation| XmlBudgetViolation {
+                        budget: violation.budget.clone(),
+                        offender: violation.offender.clone(),
+                        actual: violation.actual.clone(),
+                        limit: violation.limit.clone(),
+                    })
+                    .collect(),
+            },
+            regressions: XmlRegressions {
+                regression: report.regressions.iter()
+                    .map(|regression| XmlRegression {
+                        metric: regression.metric,
+                        subject: regression.subject.clone(),
+                        baseline: regression.baseline,
+                        current: regression.current,
+                    })
+                    .collect(),
+            },
+        }
+    }
+}
+
+#[cfg(feature = "xml-output")]
+impl From<XmlReport> for Report {
+    fn from(xml: XmlReport) -> Self {
+        let project = xml.project;
+        Report {
+            schema_version: xml.schema_version,
+            project: ProjectReport {
+                name: project.name,
+                path: project.path,
+                total_lines: project.total_lines,
+                count_empty_lines: project.count_empty_lines,
+                code_lines: project.code_lines,
+                comment_lines: project.comment_lines,
+                blank_lines: project.blank_lines,
+                files: project.files,
+                modules: project.modules,
+                dependencies: project.dependencies,
+                functions: project.functions,
+                unsafe_blocks: project.unsafe_blocks,
+            },
+            languages: xml.languages.language.into_iter()
+                .map(|language| LanguageReport { name: language.name, files: language.files, code: language.code, comment: language.comment, blank: language.blank })
+                .collect(),
+            files: xml.files.file.into_iter()
+                .map(|file| FileReport { path: file.path, language: file.language, code: file.code, comment: file.comment, blank: file.blank, bytes: file.bytes })
+                .collect(),
+            crates: xml.crates.krate.into_iter()
+                .map(|krate| CrateReport {
+                    name: krate.name,
+                    kind: krate.kind,
+                    root_file: krate.root_file,
+                    files: krate.files,
+                    modules: krate.modules,
+                    code: krate.code,
+                    comment: krate.comment,
+                    blank: krate.blank,
+                    functions: krate.functions,
+                    unsafe_blocks: krate.unsafe_blocks,
+                })
+                .collect(),
+            dependency_lines: xml.dependency_lines.map(|dependency_lines| {
+                dependency_lines.krate.into_iter()
+                    .map(|krate| DependencyLinesReport {
+                        name: krate.name,
+                        version: krate.version,
+                        source_dir: krate.source_dir,
+                        code: krate.code,
+                        comment: krate.comment,
+                        blank: krate.blank,
+                        unsafe_code: krate.unsafe_code.into(),
+                    })
+                    .collect()
+            }),
+            unsafe_code: UnsafeReport {
+                project: xml.unsafe_code.project.into(),
+                crates: xml.unsafe_code.crates.krate.into_iter()
+                    .map(|krate| CrateUnsafeReport { name: krate.name, kind: krate.kind, forbids_unsafe_code: krate.forbids_unsafe_code, unsafe_code: krate.unsafe_code.into() })
+                    .collect(),
+                modules: xml.unsafe_code.modules.module.into_iter()
+                    .map(|module| ModuleUnsafeReport { path: module.path, file: module.file, unsafe_code: module.unsafe_code.into() })
+                    .collect(),
+                dependencies: xml.unsafe_code.dependencies.map(UnsafeStats::from),
+            },
+            complexity: ComplexitySummary {
+                functions: xml.complexity.functions,
+                average_cyclomatic: xml.complexity.average_cyclomatic,
+                average_cognitive: xml.complexity.average_cognitive,
+                max_cyclomatic: xml.complexity.max_cyclomatic,
+                max_cognitive: xml.complexity.max_cognitive,
+                files: xml.complexity.files.file.into_iter()
+                    .map(|file| FileComplexityReport {
+                        path: file.path,
+                        functions: file.functions,
+                        max_cyclomatic: file.max_cyclomatic,
+                        max_cognitive: file.max_cognitive,
+                        average_cyclomatic: file.average_cyclomatic,
+                        average_cognitive: file.average_cognitive,
+                    })
+                    .collect(),
+                most_complex: xml.complexity.most_complex.function.into_iter().map(FunctionComplexityReport::from).collect(),
+            },
+            modules: xml.modules.into(),
+            items: xml.items.into(),
+            test_split: xml.test_split.into(),
+            workspace: xml.workspace.map(|workspace| WorkspaceSummary {
+                root: workspace.root,
+                is_virtual: workspace.is_virtual,
+                members: workspace.member.into_iter()
+                    .map(|member| WorkspaceMemberReport {
+                        name: member.name,
+                        manifest_dir: member.manifest_dir,
+                        files: member.files,
+                        code: member.code,
+                        comment: member.comment,
+                        blank: member.blank,
+                        modules: member.modules.into(),
+                        items: member.items.into(),
+                        test_split: member.test_split.into(),
+                    })
+                    .collect(),
+            }),
+            dependencies: {
+                let dependencies = xml.dependencies;
+                DependencySummary {
+                    has_lockfile: dependencies.has_lockfile,
+                    direct: dependencies.direct,
+                    transitive: dependencies.transitive,
+                    max_depth: dependencies.max_depth,
+                    duplicates: dependencies.duplicates.krate.into_iter()
+                        .map(|duplicate| DuplicateCrate { name: duplicate.name, versions: duplicate.version })
+                        .collect(),
+                    kinds: DependencyKinds {
+                        normal: dependencies.kinds.normal,
+                        dev: dependencies.kinds.dev,
+                        build: dependencies.kinds.build,
+                        target_specific: dependencies.kinds.target_specific,
+                    },
+                    sources: SourceCounts {
+                        registry: dependencies.sources.registry,
+                        git: dependencies.sources.git,
+                        path: dependencies.sources.path,
+                        workspace_inherited: dependencies.sources.workspace_inherited,
+                    },
+                    declared: dependencies.declared.dependency.into_iter()
+                        .map(|declared| DeclaredDependencyReport {
+                            name: declared.name,
+                            member: declared.member,
+                            kind: declared.kind,
+                            target: declared.target,
+                            source_kind: declared.source_kind,
+                            source: declared.source,
+                            workspace_inherited: declared.workspace_inherited,
+                        })
+                        .collect(),
+                    resolved: dependencies.resolved.package.into_iter()
+                        .map(|package| LockedPackageReport { name: package.name, version: package.version, source: package.source })
+                        .collect(),
+                }
+            },
+            function_sizes: FunctionSizesReport {
+                lengths: xml.function_sizes.lengths.into(),
+                nesting: xml.function_sizes.nesting.into(),
+                long_functions: xml.function_sizes.long_functions.function.into_iter().map(FunctionComplexityReport::from).collect(),
+                crates: xml.function_sizes.crates.krate.into_iter()
+                    .map(|krate| CrateFunctionSizesReport {
+                        name: krate.name,
+                        kind: krate.kind,
+                        lengths: krate.lengths.into(),
+                        nesting: krate.nesting.into(),
+                        long_functions: krate.long_functions,
+                    })
+                    .collect(),
+            },
+            orphaned_files: OrphanedFilesReport { excluded: xml.orphaned_files.excluded, files: xml.orphaned_files.file },
+            budget_violations: xml.budget_violations.violation.into_iter()
+                .map(|violation| BudgetViolation { budget: violation.budget, offender: violation.offender, actual: violation.actual, limit: violation.limit })
+                .collect(),
+            regressions: xml.regressions.regression.into_iter()
+                .map(|regression| Regression { metric: regression.metric, subject: regression.subject, baseline: regression.baseline, current: regression.current })
+                .collect(),
+            toolchain: ToolchainReport {
+                edition: xml.toolchain.edition,
+                rustc_version: xml.toolchain.rustc_version,
+                countroo_version: xml.toolchain.countroo_version,
+            },
+            timings: Timings { analysis_ms: xml.timings.analysis_ms, report_ms: xml.timings.report_ms },
+        }
+    }
+}
+
+#[cfg(all(test, any(feature = "json-output", feature = "yaml-output", feature = "toml-output", feature = "xml-output")))]
+mod tests {
+    use super::*;
+    use crate::baseline::Metric;
+    use crate::items::ItemCount;
+    use crate::line_classifier::LineCounts;
+
+    #[test]
+    #[cfg(feature = "json-output")]
+    fn test_published_schema_is_up_to_date() {
+        let schema = Report::json_schema();
+        let published = std::fs::read_to_string(REPORT_SCHEMA_PATH).unwrap_or_default();
+        if std::env::var_os("COUNTROO_UPDATE_SCHEMA").is_some() {
+            std::fs::write(REPORT_SCHEMA_PATH, &schema).unwrap();
+        } else {
+            assert!(published == schema, "{} is out of date; rerun with COUNTROO_UPDATE_SCHEMA=1", REPORT_SCHEMA_PATH);
+        }
+
+        let value: serde_json::Value = serde_json::from_str(&schema).unwrap();
+        let required = value["required"].as_array().unwrap();
+        assert!(required.contains(&"schema_version".into()));
+        assert!(value["$defs"]["TargetKind"]["enum"].as_array().unwrap().contains(&"build_script".into()));
+        assert!(value["$defs"]["UnsafeStats"]["properties"]["extern_blocks"].is_object());
+        assert!(value["$defs"]["DeclaredDependencyReport"]["properties"]["source_kind"].is_object());
+        assert!(value["$defs"]["Metric"]["enum"].as_array().unwrap().contains(&"cognitive_complexity".into()));
+        for section in ["modules", "items", "test_split", "workspace", "dependencies", "function_sizes", "orphaned_files", "budget_violations", "regressions"] {
+            assert!(value["properties"][section].is_object(), "{}", section);
+        }
+    }
+
+    fn sample_report() -> Report {
+        Report {
+            project: ProjectReport { name: Some("countroo".to_string()), path: ".".to_string(), code_lines: 10, files: 2, ..Default::default() },
+            languages: vec![LanguageReport { name: "Rust".to_string(), files: 1, code: 8, comment: 2, blank: 1 }],
+            files: vec![
+                FileReport { path: "src/lib.rs".to_string(), language: Some("Rust".to_string()), code: 8, comment: 2, blank: 1, bytes: 300 },
+                FileReport { path: "notes.weird".to_string(), language: None, code: 2, ..Default::default() },
+            ],
+            crates: vec![CrateReport {
+                name: "build-script-build".to_string(), kind: TargetKind::BuildScript, root_file: "build.rs".to_string(),
+                files: 1, modules: 1, code: 2, comment: 0, blank: 0, functions: 1, unsafe_blocks: 0,
+            }],
+            dependency_lines: Some(vec![
+                DependencyLinesReport { name: "serde".to_string(), version: "1.0.0".to_string(), source_dir: Some("vendor/serde".to_string()), code: 900, comment: 100, blank: 50, unsafe_code: UnsafeStats { unsafe_fns: 7, ..Default::default() } },
+                DependencyLinesReport { name: "forked".to_string(), version: "0.1.0".to_string(), ..Default::default() },
+            ]),
+            unsafe_code: UnsafeReport {
+                project: UnsafeStats { unsafe_blocks: 2, extern_blocks: 1, ..Default::default() },
+                crates: vec![CrateUnsafeReport { name: "countroo".to_string(), kind: TargetKind::Lib, forbids_unsafe_code: false, unsafe_code: UnsafeStats { unsafe_blocks: 2, ..Default::default() } }],
+                modules: vec![ModuleUnsafeReport { path: "countroo::ffi".to_string(), file: "src/ffi.rs".to_string(), unsafe_code: UnsafeStats { unsafe_blocks: 2, ..Default::default() } }],
+                dependencies: Some(UnsafeStats { unsafe_fns: 7, ..Default::default() }),
+            },
+            complexity: ComplexitySummary {
+                functions: 3,
+                average_cyclomatic: 7.0 / 3.0,
+                average_cognitive: 1.5,
+                max_cyclomatic: 4,
+                max_cognitive: 3,
+                files: vec![FileComplexityReport { path: "src/lib.rs".to_string(), functions: 3, max_cyclomatic: 4, max_cognitive: 3, average_cyclomatic: 7.0 / 3.0, average_cognitive: 1.5 }],
+                most_complex: vec![FunctionComplexityReport {
+                    name: "Parser::next_token".to_string(), file: "src/lib.rs".to_string(), line: 12, lines: 20, max_nesting: 2, cyclomatic: 4, cognitive: 3,
+                }],
+            },
+            modules: ModuleStats { inline: 1, file: 2, test: 1 },
+            items: ItemInventory { functions: ItemCount { public: 2, private: 1 }, impls: 1, ..Default::default() },
+            test_split: TestSplit { production: LineCounts { code: 6, comment: 2, blank: 1 }, test: LineCounts { code: 2, ..Default::default() } },
+            workspace: Some(WorkspaceSummary {
+                root: "/work/countroo".to_string(),
+                is_virtual: false,
+                members: vec![WorkspaceMemberReport {
+                    name: "countroo".to_string(), manifest_dir: ".".to_string(), files: 1, code: 8, comment: 2, blank: 1,
+                    modules: ModuleStats { inline: 1, file: 2, test: 1 }, ..Default::default()
+                }],
+            }),
+            dependencies: DependencySummary {
+                has_lockfile: true,
+                direct: 1,
+                transitive: 2,
+                max_depth: 2,
+                duplicates: vec![DuplicateCrate { name: "syn".to_string(), versions: vec!["1.0.109".to_string(), "2.0.48".to_string()] }],
+                kinds: DependencyKinds { normal: 1, dev: 1, ..Default::default() },
+                sources: SourceCounts { registry: 1, path: 1, ..Default::default() },
+                declared: vec![
+                    DeclaredDependencyReport {
+                        name: "serde".to_string(), member: "countroo".to_string(), kind: DependencyKind::Normal, target: None,
+                        source_kind: SourceKind::Registry, source: "1.0".to_string(), workspace_inherited: false,
+                    },
+                    DeclaredDependencyReport {
+                        name: "forked".to_string(), member: "countroo".to_string(), kind: DependencyKind::Dev, target: Some("cfg(unix)".to_string()),
+                        source_kind: SourceKind::Path, source: "path ../forked".to_string(), workspace_inherited: true,
+                    },
+                ],
+                resolved: vec![
+                    LockedPackageReport { name: "serde".to_string(), version: "1.0.0".to_string(), source: Some("registry+https://github.com/rust-lang/crates.io-index".to_string()) },
+                    LockedPackageReport { name: "forked".to_string(), version: "0.1.0".to_string(), source: None },
+                ],
+            },
+            function_sizes: FunctionSizesReport {
+                lengths: DistributionReport {
+                    count: 3, max: 20, mean: 9.0, p50: 5, p90: 20,
+                    histogram: vec![Bucket { from: 1, to: Some(10), count: 2 }, Bucket { from: 11, to: None, count: 1 }],
+                },
+                nesting: DistributionReport { count: 3, max: 2, mean: 1.0, p50: 1, p90: 2, histogram: vec![Bucket { from: 0, to: Some(2), count: 3 }] },
+                long_functions: vec![FunctionComplexityReport {
+                    name: "Parser::next_token".to_string(), file: "src/lib.rs".to_string(), line: 12, lines: 20, max_nesting: 2, cyclomatic: 4, cognitive: 3,
+                }],
+                crates: vec![CrateFunctionSizesReport {
+                    name: "countroo".to_string(), kind: TargetKind::Lib, long_functions: 1,
+                    lengths: DistributionReport { count: 3, max: 20, mean: 9.0, p50: 5, p90: 20, histogram: Vec::new() },
+                    nesting: DistributionReport::default(),
+                }],
+            },
+            orphaned_files: OrphanedFilesReport { excluded: true, files: vec!["src/old.rs".to_string()] },
+            budget_violations: vec![BudgetViolation {
+                budget: "max_file_lines".to_string(), offender: "src/lib.rs".to_string(), actual: "8".to_string(), limit: "5".to_string(),
+            }],
+            regressions: vec![Regression { metric: Metric::UnsafeBlocks, subject: "src/ffi.rs".to_string(), baseline: 1, current: 2 }],
+            toolchain: ToolchainReport { edition: Some("2021".to_string()), rustc_version: None, countroo_version: "0.1.7".to_string() },
+            timings: Timings { analysis_ms: 12, report_ms: 3 },
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_reports_load_back_from_every_format() {
+        let report = sample_report();
+        let dir = std::env::temp_dir();
+        let written: Vec<(&str, String)> = vec![
+            #[cfg(feature = "json-output")]
+            ("json", serde_json::to_string_pretty(&report).unwrap()),
+            #[cfg(feature = "yaml-output")]
+            ("yml", report.to_yaml().unwrap()),
+            #[cfg(feature = "toml-output")]
+            ("toml", report.to_toml().unwrap()),
+            #[cfg(feature = "xml-output")]
+            ("xml", report.to_xml().unwrap()),
+        ];
+        for (extension, contents) in written {
+            let path = dir.join(format!("countroo-load-back-{}.{}", std::process::id(), extension));
+            std::fs::write(&path, contents).unwrap();
+            let loaded = Report::load(&path).unwrap();
+            assert_eq!(loaded, report, "{}", extension);
+            #[cfg(feature = "tabular-output")]
+            assert_eq!(loaded.to_string(), report.to_string(), "{}", extension);
+            std::fs::remove_file(&path).unwrap();
+        }
+
+        let unknown = dir.join(format!("countroo-load-unknown-{}.txt", std::process::id()));
+        std::fs::write(&unknown, "").unwrap();
+        assert!(matches!(Report::load(&unknown), Err(LocCounterError::ConfigError(_))));
+        std::fs::remove_file(&unknown).unwrap();
+    }
+
+    #[test]
+    #[cfg(feature = "toml-output")]
+    fn test_toml_report_is_valid_toml() {
+        let toml = sample_report().to_toml().unwrap();
+        assert!(toml.starts_with("schema_version = 1\n"));
+        assert!(toml.contains("[project]\nname = \"countroo\""));
+        assert!(toml.contains("[[crates]]\nname = \"build-script-build\"\nkind = \"build_script\""));
+        assert!(!toml.contains("rustc_version"));
+        let empty = Report::default().to_toml().unwrap();
+        assert_eq!(Report::from_toml(&empty).unwrap(), Report::default());
+        assert!(Report::from_toml("+-----+\n| Project |").is_err());
+    }
+
+    #[test]
+    #[cfg(feature = "xml-output")]
+    fn test_xml_round_trips() {
+        let report = sample_report();
+        let xml = report.to_xml().unwrap();
+        assert!(xml.contains("<report schema_version=\"1\">"));
+        assert!(xml.contains("<language name=\"Rust\" files=\"1\" code=\"8\" comment=\"2\" blank=\"1\"/>"));
+        assert!(xml.contains("<crate name=\"build-script-build\" kind=\"build_script\""));
+        assert!(xml.contains("<module path=\"countroo::ffi\" file=\"src/ffi.rs\">"));
+        assert!(xml.contains("<function name=\"Parser::next_token\" file=\"src/lib.rs\" line=\"12\""));
+        assert!(xml.contains("<version>2.0.48</version>"));
+        assert!(xml.contains("<bucket from=\"11\" count=\"1\"/>"));
+        assert!(xml.contains("<regression metric=\"unsafe_blocks\" subject=\"src/ffi.rs\" baseline=\"1\" current=\"2\"/>"));
+        assert!(!xml.contains("rustc_version"));
+        assert_eq!(Report::from_xml(&xml).unwrap(), report);
+        assert_eq!(Report::from_xml(&Report::default().to_xml().unwrap()).unwrap(), Report::default());
+        assert!(Report::from_xml("<report/>").is_err());
+    }
+}
@@ -0,0 +1,214 @@
+use std::fs;
+use std::ops::{Add, AddAssign};
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+use syn::spanned::Spanned;
+use syn::{Attribute, Item};
+
+use crate::languages::Language;
+use crate::line_classifier::{self, LineCounts};
+use crate::module_tree::is_cfg_test;
+use crate::LocCounterError;
+
+/// `TestSplit` 🧪 - Lines of production code versus lines of test code.
+///
+/// Test code is everything inside `#[cfg(test)]` items (usually `mod tests`), `#[test]`
+/// functions, the examples in doc comments that `cargo test` runs as doc-tests, and whole files
+/// that only exist for tests, such as those under `tests/` and `benches/`.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "json-output", derive(schemars::JsonSchema))]
+pub struct TestSplit {
+    pub production: LineCounts,
+    pub test: LineCounts,
+}
+
+impl TestSplit {
+    /// Lines of test code per line of production code, looking at code lines only.
+    pub fn ratio(&self) -> f64 {
+        if self.production.code == 0 {
+            return 0.0;
+        }
+        self.test.code as f64 / self.production.code as f64
+    }
+}
+
+impl Add for TestSplit {
+    type Output = TestSplit;
+
+    fn add(self, other: TestSplit) -> TestSplit {
+        TestSplit { production: self.production + other.production, test: self.test + other.test }
+    }
+}
+
+impl AddAssign for TestSplit {
+    fn add_assign(&mut self, other: TestSplit) {
+        *self = *self + other;
+    }
+}
+
+/// Splits a Rust source into production and test lines. Sources that do not parse only have
+/// their doc-test examples recognised.
+pub fn split(contents: &str, language: &Language) -> TestSplit {
+    let is_test = test_lines(contents);
+    let mut split = TestSplit::default();
+    for (index, kind) in line_classifier::classify_lines(contents, language).into_iter().enumerate() {
+        if is_test.get(index).copied().unwrap_or(false) {
+            split.test.record(kind);
+        } else {
+            split.production.record(kind);
+        }
+    }
+    split
+}
+
+/// Reads a file and splits it into production and test lines. Files that only exist for tests
+/// (`whole_file_is_test`) and files in other languages are not looked into.
+pub fn split_file(path: &Path, language: Option<&Language>, whole_file_is_test: bool) -> Result<TestSplit, LocCounterError> {
+    let contents = fs::read_to_string(path).map_err(LocCounterError::IoError)?;
+    let plain = Language::plain("");
+    let language = language.unwrap_or(&plain);
+    Ok(if whole_file_is_test {
+        TestSplit { production: LineCounts::default(), test: line_classifier::classify(&contents, language) }
+    } else if language.name == "Rust" {
+        split(&contents, language)
+    } else {
+        TestSplit { production: line_classifier::classify(&contents, language), test: LineCounts::default() }
+    })
+}
+
+/// For every line of `contents` (the first entry is line 1), whether it is test code.
+pub fn test_lines(contents: &str) -> Vec<bool> {
+    let mut is_test = vec![false; contents.lines().count()];
+    if let Ok(file) = syn::parse_file(contents) {
+        mark_test_items(&file.items, &mut is_test);
+    }
+    mark_doc_tests(contents, &mut is_test);
+    is_test
+}
+
+fn mark_test_items(items: &[Item], is_test: &mut [bool]) {
+    for item in items {
+        let attrs = item_attributes(item);
+        if is_cfg_test(attrs) || attrs.iter().any(is_test_attribute) {
+            // The span of an item starts at its first attribute and ends at its closing brace.
+            let span = item.span();
+            let (start, end) = (span.start().line, span.end().line);
+            is_test.iter_mut().take(end).skip(start.saturating_sub(1)).for_each(|line| *line = true);
+        } else if let Item::Mod(item_mod) = item {
+            if let Some((_, nested)) = &item_mod.content {
+                mark_test_items(nested, is_test);
+            }
+        }
+    }
+}
+
+/// `#[test]`, as well as the attributes of test frameworks such as `#[tokio::test]`.
+fn is_test_attribute(attr: &Attribute) -> bool {
+    attr.path().segments.last().is_some_and(|segment| segment.ident == "test" || segment.ident == "bench")
+}
+
+fn item_attributes(item: &Item) -> &[Attribute] {
+    match item {
+        Item::Const(item) => &item.attrs,
+        Item::Enum(item) => &item.attrs,
+        Item::ExternCrate(item) => &item.attrs,
+        Item::Fn(item) => &item.attrs,
+        Item::ForeignMod(item) => &item.attrs,
+        Item::Impl(item) => &item.attrs,
+        Item::Macro(item) => &item.attrs,
+        Item::Mod(item) => &item.attrs,
+        Item::Static(item) => &item.attrs,
+        Item::Struct(item) => &item.attrs,
+        Item::Trait(item) => &item.attrs,
+        Item::TraitAlias(item) => &item.attrs,
+        Item::Type(item) => &item.attrs,
+        Item::Union(item) => &item.attrs,
+        Item::Use(item) => &item.attrs,
+        _ => &[],
+    }
+}
+
+/// Marks the fenced code blocks of `///` and `//!` doc comments that rustdoc runs as doc-tests,
+/// fences included. Blocks tagged with another language (`text`, `toml`, `sh`...) are left alone.
+fn mark_doc_tests(contents: &str, is_test: &mut [bool]) {
+    let mut in_doc_test = false;
+    let mut in_other_block = false;
+    for (index, line) in contents.lines().enumerate() {
+        let trimmed = line.trim_start();
+        let Some(doc) = trimmed.strip_prefix("///").or_else(|| trimmed.strip_prefix("//!")) else {
+            in_doc_test = false;
+            in_other_block = false;
+            continue;
+        };
+        let doc = doc.trim();
+        let is_fence = doc.starts_with("```") || doc.starts_with("~~~");
+        if in_doc_test {
+            is_test[index] = true;
+            in_doc_test = !is_fence;
+        } else if in_other_block {
+            in_other_block = !is_fence;
+        } else if is_fence {
+            if is_rust_fence(&doc[3..]) {
+                is_test[index] = true;
+                in_doc_test = true;
+            } else {
+                in_other_block = true;
+            }
+        }
+    }
+}
+
+/// Rustdoc treats a fence as Rust unless its info string names something else.
+fn is_rust_fence(info: &str) -> bool {
+    info.split(|c: char| c == ',' || c.is_whitespace())
+        .filter(|attribute| !attribute.is_empty())
+        .all(|attribute| {
+            matches!(attribute, "rust" | "ignore" | "should_panic" | "no_run" | "compile_fail" | "test_harness" | "standalone_crate")
+                || attribute.starts_with("edition")
+                || attribute.starts_with('{')
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::languages::LanguageRegistry;
+
+    #[test]
+    fn test_test_code_is_split_from_production_code() {
+        let source = r#"/// Adds one.
+///
+/// ```
+/// assert_eq!(countroo::add_one(1), 2);
+/// ```
+///
+/// ```text
+/// not a doc-test
+/// ```
+pub fn add_one(x: i32) -> i32 {
+    x + 1
+}
+
+#[test]
+fn top_level() {
+    assert!(true);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn adds() {
+        assert_eq!(add_one(1), 2);
+    }
+}
+"#;
+        let rust = LanguageRegistry::builtin().get("Rust").unwrap().clone();
+        let split = split(source, &rust);
+        assert_eq!(split.production, LineCounts { code: 3, comment: 6, blank: 2 });
+        assert_eq!(split.test, LineCounts { code: 12, comment: 3, blank: 1 });
+        assert_eq!(split.ratio(), 4.0);
+    }
+}
@@ -0,0 +1,197 @@
+use std::fs;
+use std::iter::Sum;
+use std::ops::{Add, AddAssign};
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+use syn::visit::{self, Visit};
+use syn::{Attribute, ExprUnsafe, ImplItemFn, Item, ItemFn, ItemForeignMod, ItemImpl, ItemMod, ItemTrait, TraitItemFn};
+
+use crate::LocCounterError;
+
+/// `UnsafeStats` ☢️ - The unsafe surface of a module, a crate or a dependency.
+///
+/// - **unsafe_blocks**: `unsafe { ... }` blocks, wherever they appear.
+/// - **unsafe_fns**: `unsafe fn` declarations, free functions, methods and trait methods alike.
+/// - **unsafe_impls** and **unsafe_traits**: `unsafe impl` blocks and `unsafe trait` definitions.
+/// - **extern_blocks**: `extern "C" { ... }` blocks, whose items are unsafe to call.
+/// - **forbid_unsafe_code**: Modules that declare `#![forbid(unsafe_code)]`; for a crate, its
+///   root doing so is what keeps unsafe out of the whole crate.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "json-output", derive(schemars::JsonSchema))]
+pub struct UnsafeStats {
+    pub unsafe_blocks: usize,
+    pub unsafe_fns: usize,
+    pub unsafe_impls: usize,
+    pub unsafe_traits: usize,
+    pub extern_blocks: usize,
+    pub forbid_unsafe_code: usize,
+}
+
+impl UnsafeStats {
+    /// Counts the unsafe code of a module: its inner attributes and its items, leaving inline
+    /// modules nested in it to their own count.
+    pub fn from_items(attrs: &[Attribute], items: &[Item]) -> Self {
+        let mut visitor = UnsafeVisitor { stats: UnsafeStats::default(), nested_modules: false };
+        visitor.record_attributes(attrs);
+        items.iter().for_each(|item| visitor.visit_item(item));
+        visitor.stats
+    }
+
+    /// Counts the unsafe code of a whole source file, inline modules included. Sources that do
+    /// not parse count as having none.
+    pub fn from_source(contents: &str) -> Self {
+        let Ok(file) = syn::parse_file(contents) else { return UnsafeStats::default() };
+        let mut visitor = UnsafeVisitor { stats: UnsafeStats::default(), nested_modules: true };
+        visitor.record_attributes(&file.attrs);
+        visitor.visit_file(&file);
+        visitor.stats
+    }
+
+    pub fn from_file(path: &Path) -> Result<Self, LocCounterError> {
+        Ok(Self::from_source(&fs::read_to_string(path).map_err(LocCounterError::IoError)?))
+    }
+
+    /// Every unsafe construct together; `forbid_unsafe_code` is not part of the surface.
+    pub fn total(&self) -> usize {
+        self.unsafe_blocks + self.unsafe_fns + self.unsafe_impls + self.unsafe_traits + self.extern_blocks
+    }
+}
+
+impl Add for UnsafeStats {
+    type Output = UnsafeStats;
+
+    fn add(self, other: UnsafeStats) -> UnsafeStats {
+        UnsafeStats {
+            unsafe_blocks: self.unsafe_blocks + other.unsafe_blocks,
+            unsafe_fns: self.unsafe_fns + other.unsafe_fns,
+            unsafe_impls: self.unsafe_impls + other.unsafe_impls,
+            unsafe_traits: self.unsafe_traits + other.unsafe_traits,
+            extern_blocks: self.extern_blocks + other.extern_blocks,
+            forbid_unsafe_code: self.forbid_unsafe_code + other.forbid_unsafe_code,
+        }
+    }
+}
+
+impl AddAssign for UnsafeStats {
+    fn add_assign(&mut self, other: UnsafeStats) {
+        *self = *self + other;
+    }
+}
+
+impl Sum for UnsafeStats {
+    fn sum<I: Iterator<Item = UnsafeStats>>(iter: I) -> Self {
+        iter.fold(UnsafeStats::default(), Add::add)
+    }
+}
+
+/// Whether the attributes hold `#![forbid(unsafe_code)]`, possibly among other lints.
+pub fn forbids_unsafe_code(attrs: &[Attribute]) -> bool {
+    attrs.iter()
+        .filter(|attr| matches!(attr.style, syn::AttrStyle::Inner(_)) && attr.path().is_ident("forbid"))
+        .any(|attr| {
+            let mut found = false;
+            let _ = attr.parse_nested_meta(|meta| {
+                found |= meta.path.is_ident("unsafe_code");
+                Ok(())
+            });
+            found
+        })
+}
+
+struct UnsafeVisitor {
+    stats: UnsafeStats,
+    /// Whether inline modules are counted along, or left to their own `ModuleNode`.
+    nested_modules: bool,
+}
+
+impl UnsafeVisitor {
+    fn record_attributes(&mut self, attrs: &[Attribute]) {
+        if forbids_unsafe_code(attrs) {
+            self.stats.forbid_unsafe_code += 1;
+        }
+    }
+}
+
+impl<'ast> Visit<'ast> for UnsafeVisitor {
+    fn visit_expr_unsafe(&mut self, node: &'ast ExprUnsafe) {
+        self.stats.unsafe_blocks += 1;
+        visit::visit_expr_unsafe(self, node);
+    }
+
+    fn visit_item_fn(&mut self, node: &'ast ItemFn) {
+        if node.sig.unsafety.is_some() {
+            self.stats.unsafe_fns += 1;
+        }
+        visit::visit_item_fn(self, node);
+    }
+
+    fn visit_impl_item_fn(&mut self, node: &'ast ImplItemFn) {
+        if node.sig.unsafety.is_some() {
+            self.stats.unsafe_fns += 1;
+        }
+        visit::visit_impl_item_fn(self, node);
+    }
+
+    fn visit_trait_item_fn(&mut self, node: &'ast TraitItemFn) {
+        if node.sig.unsafety.is_some() {
+            self.stats.unsafe_fns += 1;
+        }
+        visit::visit_trait_item_fn(self, node);
+    }
+
+    fn visit_item_impl(&mut self, node: &'ast ItemImpl) {
+        if node.unsafety.is_some() {
+            self.stats.unsafe_impls += 1;
+        }
+        visit::visit_item_impl(self, node);
+    }
+
+    fn visit_item_trait(&mut self, node: &'ast ItemTrait) {
+        if node.unsafety.is_some() {
+            self.stats.unsafe_traits += 1;
+        }
+        visit::visit_item_trait(self, node);
+    }
+
+    fn visit_item_foreign_mod(&mut self, node: &'ast ItemForeignMod) {
+        self.stats.extern_blocks += 1;
+        visit::visit_item_foreign_mod(self, node);
+    }
+
+    fn visit_item_mod(&mut self, node: &'ast ItemMod) {
+        if self.nested_modules {
+            self.record_attributes(&node.attrs);
+            visit::visit_item_mod(self, node);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_unsafe_code_is_counted() {
+        let source = r#"
+            #![forbid(unsafe_code, missing_docs)]
+            pub unsafe fn raw() {}
+            fn safe() { unsafe { raw() }; let _ = || unsafe { raw() }; }
+            pub unsafe trait Marker { unsafe fn method(&self); }
+            unsafe impl Marker for u8 { unsafe fn method(&self) {} }
+            extern "C" { fn abs(x: i32) -> i32; }
+            mod inner {
+                #![forbid(unsafe_code)]
+                fn f() { unsafe {} }
+            }
+        "#;
+        let whole = UnsafeStats::from_source(source);
+        assert_eq!(whole, UnsafeStats { unsafe_blocks: 3, unsafe_fns: 3, unsafe_impls: 1, unsafe_traits: 1, extern_blocks: 1, forbid_unsafe_code: 2 });
+        assert_eq!(whole.total(), 9);
+
+        let file = syn::parse_file(source).unwrap();
+        let module = UnsafeStats::from_items(&file.attrs, &file.items);
+        assert_eq!(module.unsafe_blocks, 2);
+        assert_eq!(module.forbid_unsafe_code, 1);
+    }
+}
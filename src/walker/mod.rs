@@ -0,0 +1,222 @@
+use std::collections::BTreeSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use globset::{GlobBuilder, GlobSet, GlobSetBuilder};
+use ignore::WalkBuilder;
+
+use crate::{Config, LocCounterError};
+
+/// The tool specific ignore file, read with the same rules as a `.gitignore`.
+pub const COUNTROO_IGNORE_FILENAME: &str = ".countrooignore";
+
+/// `FileWalker` 🚶 - The one place where `CountRoo` decides which files it gets to see.
+///
+/// Every analysis walks the project through a `FileWalker`, so they all agree on what is part of
+/// the project. When `respect_ignore_files` is on (the default), the walk follows gitignore
+/// semantics: `.gitignore` files (nested ones included, negations and all), `.ignore` files,
+/// `.git/info/exclude`, the global git excludes file and `.countrooignore` files. The `.git`
+/// folder itself is skipped as well. Turn it off to walk every single file, as `CountRoo` used to.
+///
+/// On top of that, the `include` and `exclude` globs of the `Config` narrow the walk down. They
+/// are matched against paths relative to the anchor set with `anchored_at` (the project base,
+/// for `CountRoo`), or to the folder being walked when there is none, using `/` as the separator.
+/// A pattern without a `/` matches a file name at any depth (`*.pb.rs`); a pattern with one is
+/// anchored (`crates/*/src/**`), where `*` stays within a single folder and `**` crosses any
+/// number of them. When `include` is empty everything is included. With an anchor, walking a
+/// sub folder such as `crates/a` selects exactly the files a walk of the whole project would.
+///
+/// Individual files can be left out as well with `skipping`, which is how orphaned `.rs` files
+/// are kept out of the counts when `Config::exclude_orphaned_files` is set.
+///
+/// ## Example Usage
+/// ```rust
+/// use countroo::prelude::*;
+/// let walker = FileWalker::default();
+/// let files = walker.files("src");
+/// assert!(files.iter().any(|f| f.ends_with("lib.rs")));
+/// ```
+#[derive(Debug, Clone)]
+pub struct FileWalker {
+    pub respect_ignore_files: bool,
+    include: Option<GlobSet>,
+    exclude: Option<GlobSet>,
+    anchor: Option<PathBuf>,
+    skipped: BTreeSet<PathBuf>,
+}
+
+impl Default for FileWalker {
+    fn default() -> Self {
+        FileWalker { respect_ignore_files: true, include: None, exclude: None, anchor: None, skipped: BTreeSet::new() }
+    }
+}
+
+impl FileWalker {
+    /// Builds the walker a `Config` asks for, failing on malformed `include`/`exclude` globs.
+    pub fn new(config: &Config) -> Result<Self, LocCounterError> {
+        Ok(FileWalker {
+            respect_ignore_files: config.respect_ignore_files,
+            include: Self::glob_set(&config.include)?,
+            exclude: Self::glob_set(&config.exclude)?,
+            anchor: None,
+            skipped: BTreeSet::new(),
+        })
+    }
+
+    /// Matches the globs against paths relative to `anchor`, whichever folder below it is walked.
+    /// Folders outside of the anchor are matched relative to themselves.
+    pub fn anchored_at(mut self, anchor: impl AsRef<Path>) -> Self {
+        let anchor = anchor.as_ref();
+        self.anchor = Some(fs::canonicalize(anchor).unwrap_or_else(|_| anchor.to_path_buf()));
+        self
+    }
+
+    /// Leaves the given files out of every walk, wherever they are reached from.
+    pub fn skipping(mut self, files: impl IntoIterator<Item = PathBuf>) -> Self {
+        self.skipped.extend(files.into_iter().map(|file| fs::canonicalize(&file).unwrap_or(file)));
+        self
+    }
+
+    fn is_skipped(&self, path: &Path) -> bool {
+        !self.skipped.is_empty() && self.skipped.contains(&fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf()))
+    }
+
+    fn glob_set(patterns: &[String]) -> Result<Option<GlobSet>, LocCounterError> {
+        if patterns.is_empty() {
+            return Ok(None);
+        }
+        let mut builder = GlobSetBuilder::new();
+        for pattern in patterns {
+            let anchored = match pattern.trim_start_matches("./") {
+                p if p.contains('/') => p.trim_start_matches('/').to_string(),
+                p => format!("**/{}", p),
+            };
+            let glob = GlobBuilder::new(&anchored)
+                .literal_separator(true)
+                .build()
+                .map_err(|err| LocCounterError::ConfigError(format!("Invalid glob pattern `{}`: {}", pattern, err)))?;
+            builder.add(glob);
+        }
+        builder.build()
+            .map(Some)
+            .map_err(|err| LocCounterError::ConfigError(err.to_string()))
+    }
+
+    /// Whether a path, relative to the anchor (or the walked folder), passes the `include`/`exclude` globs.
+    pub fn is_selected(&self, relative_path: &Path) -> bool {
+        self.include.as_ref().is_none_or(|include| include.is_match(relative_path))
+            && !self.exclude.as_ref().is_some_and(|exclude| exclude.is_match(relative_path))
+    }
+
+    /// Every file below `root` that survives the ignore rules, in a stable order.
+    pub fn files(&self, root: impl AsRef<Path>) -> Vec<PathBuf> {
+        let root = root.as_ref();
+        // Where `root` sits below the anchor, so that globs see paths relative to the anchor.
+        let prefix = self.anchor.as_ref()
+            .and_then(|anchor| fs::canonicalize(root).ok()?.strip_prefix(anchor).ok().map(Path::to_path_buf))
+            .unwrap_or_default();
+        let mut builder = WalkBuilder::new(root);
+        builder
+            .standard_filters(self.respect_ignore_files)
+            .hidden(false)
+            .require_git(false)
+            .sort_by_file_path(|a, b| a.cmp(b));
+
+        if self.respect_ignore_files {
+            builder
+                .add_custom_ignore_filename(COUNTROO_IGNORE_FILENAME)
+                .filter_entry(|entry| entry.file_name() != ".git");
+        }
+
+        builder.build()
+            .filter_map(Result::ok)
+            .filter(|entry| entry.file_type().is_some_and(|t| t.is_file()))
+            .map(|entry| entry.into_path())
+            .filter(|path| self.is_selected(&prefix.join(path.strip_prefix(root).unwrap_or(path))))
+            .filter(|path| !self.is_skipped(path))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    fn scratch_project(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("countroo-walker-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        for folder in ["src/generated", "target/debug", "node_modules/pkg", ".git"] {
+            fs::create_dir_all(dir.join(folder)).unwrap();
+        }
+        for file in ["src/lib.rs", "src/generated/api.rs", "src/generated/keep.rs", "target/debug/out.rs", "node_modules/pkg/index.js", ".git/config", "notes.md"] {
+            fs::write(dir.join(file), "line\n").unwrap();
+        }
+        fs::write(dir.join(".gitignore"), "target/\nnode_modules/\n").unwrap();
+        fs::write(dir.join("src/.gitignore"), "generated/*\n!generated/keep.rs\n").unwrap();
+        fs::write(dir.join(COUNTROO_IGNORE_FILENAME), "*.md\n").unwrap();
+        dir
+    }
+
+    fn config_with_globs(include: &[&str], exclude: &[&str]) -> Config {
+        let mut config = Config::from_str_vec(vec!["rs".to_string()], false, String::new()).unwrap();
+        config.include = include.iter().map(|p| p.to_string()).collect();
+        config.exclude = exclude.iter().map(|p| p.to_string()).collect();
+        config
+    }
+
+    fn relative(root: &Path, files: Vec<PathBuf>) -> Vec<String> {
+        files.iter().map(|f| f.strip_prefix(root).unwrap().to_string_lossy().replace('\\', "/")).collect()
+    }
+
+    #[test]
+    fn test_ignore_files_are_respected() {
+        let root = scratch_project("respect");
+        let files = relative(&root, FileWalker::default().files(&root));
+        assert_eq!(files, vec![".countrooignore", ".gitignore", "src/.gitignore", "src/generated/keep.rs", "src/lib.rs"]);
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn test_ignore_files_can_be_disabled() {
+        let root = scratch_project("disabled");
+        let files = relative(&root, FileWalker { respect_ignore_files: false, ..Default::default() }.files(&root));
+        assert!(files.contains(&"target/debug/out.rs".to_string()));
+        assert!(files.contains(&"node_modules/pkg/index.js".to_string()));
+        assert!(files.contains(&"src/generated/api.rs".to_string()));
+        assert!(files.contains(&"notes.md".to_string()));
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn test_include_and_exclude_globs() {
+        let root = std::env::temp_dir().join(format!("countroo-walker-globs-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&root);
+        for folder in ["crates/a/src/generated", "crates/b/src", "crates/b/benches", "tools"] {
+            fs::create_dir_all(root.join(folder)).unwrap();
+        }
+        for file in ["crates/a/src/lib.rs", "crates/a/src/api.pb.rs", "crates/a/src/generated/types.rs", "crates/b/src/main.rs", "crates/b/benches/bench.rs", "tools/gen.rs"] {
+            fs::write(root.join(file), "line\n").unwrap();
+        }
+
+        let walker = FileWalker::new(&config_with_globs(&["crates/*/src/**"], &["**/generated/**", "*.pb.rs"])).unwrap();
+        let files = relative(&root, walker.files(&root));
+        assert_eq!(files, vec!["crates/a/src/lib.rs", "crates/b/src/main.rs"]);
+
+        let walker = FileWalker::new(&config_with_globs(&[], &["crates/**"])).unwrap();
+        assert_eq!(relative(&root, walker.files(&root)), vec!["tools/gen.rs"]);
+
+        // Anchored at the root, a sub folder walk agrees with the walk of the whole root.
+        let walker = FileWalker::new(&config_with_globs(&["crates/*/src/**"], &["crates/a/src/generated/**"])).unwrap().anchored_at(&root);
+        assert_eq!(relative(&root, walker.files(root.join("crates/a"))), vec!["crates/a/src/api.pb.rs", "crates/a/src/lib.rs"]);
+        assert_eq!(relative(&root, walker.files(root.join("crates/b/src"))), vec!["crates/b/src/main.rs"]);
+        assert!(walker.files(root.join("tools")).is_empty());
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn test_invalid_glob_is_a_config_error() {
+        let result = FileWalker::new(&config_with_globs(&["src/[unclosed"], &[]));
+        assert!(matches!(result, Err(LocCounterError::ConfigError(_))));
+    }
+}
@@ -0,0 +1,206 @@
+use std::collections::BTreeSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use cargo_toml_workspace::cargo_toml::Manifest;
+
+use crate::items::ItemInventory;
+use crate::line_classifier::LineCounts;
+use crate::module_tree::ModuleStats;
+use crate::test_code::TestSplit;
+use crate::LocCounterError;
+
+/// A package that belongs to a workspace.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WorkspaceMember {
+    pub name: String,
+    pub manifest_dir: PathBuf,
+    /// The workspace the member was found through; differs from the root for nested workspaces.
+    pub workspace_root: PathBuf,
+}
+
+/// `Workspace` 🏘️ - A Cargo workspace and every package that belongs to it.
+///
+/// Members are enumerated the way cargo does it: the `members` globs of the `[workspace]` table
+/// (`crates/*`), minus anything under an `exclude` path, plus the root package itself when the
+/// root manifest is not virtual. A member that declares a `[workspace]` of its own is treated as
+/// a nested workspace, and its members are enumerated as well.
+///
+/// ## Example Usage
+/// ```rust
+/// use countroo::prelude::*;
+/// // CountRoo is a single package, so it does not find a workspace above itself.
+/// assert!(Workspace::from_root(env!("CARGO_MANIFEST_DIR")).unwrap().is_none());
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Workspace {
+    pub root: PathBuf,
+    /// A virtual manifest has a `[workspace]` but no `[package]`.
+    pub is_virtual: bool,
+    pub members: Vec<WorkspaceMember>,
+}
+
+impl Workspace {
+    /// Reads the workspace whose root manifest lives in `root`, or `None` when that manifest has
+    /// no `[workspace]` table.
+    pub fn from_root(root: impl AsRef<Path>) -> Result<Option<Self>, LocCounterError> {
+        let root = root.as_ref();
+        let manifest = read_manifest(root)?;
+        if manifest.workspace.is_none() {
+            return Ok(None);
+        }
+
+        let mut members = Vec::new();
+        let mut seen = BTreeSet::new();
+        collect_members(root, &manifest, &mut members, &mut seen)?;
+        members.sort_by(|a: &WorkspaceMember, b| a.manifest_dir.cmp(&b.manifest_dir));
+        Ok(Some(Workspace { root: root.to_path_buf(), is_virtual: manifest.package.is_none(), members }))
+    }
+
+    /// Finds the workspace `dir` belongs to, like cargo does: the nearest `[workspace]` in `dir`
+    /// or its ancestors whose `members` (minus `exclude`) take `dir` in. A package with a
+    /// `[workspace]` of its own is the root of its workspace, even inside another one. `None`
+    /// when no workspace claims `dir`.
+    pub fn find(dir: impl AsRef<Path>) -> Result<Option<Self>, LocCounterError> {
+        let dir = fs::canonicalize(dir.as_ref()).unwrap_or_else(|_| dir.as_ref().to_path_buf());
+        for ancestor in dir.ancestors() {
+            if !ancestor.join("Cargo.toml").is_file() || read_manifest(ancestor)?.workspace.is_none() {
+                continue;
+            }
+            let Some(workspace) = Self::from_root(ancestor)? else { continue };
+            if workspace.members.iter().any(|member| dir.starts_with(&member.manifest_dir)) {
+                return Ok(Some(workspace));
+            }
+        }
+        Ok(None)
+    }
+
+    /// The folders of the members that sit inside `member`, such as `crates/*` under a root
+    /// package. Walking `member` should leave them out, since they are counted on their own.
+    pub fn nested_member_dirs(&self, member: &WorkspaceMember) -> Vec<&Path> {
+        self.members.iter()
+            .map(|other| other.manifest_dir.as_path())
+            .filter(|dir| *dir != member.manifest_dir && dir.starts_with(&member.manifest_dir))
+            .collect()
+    }
+}
+
+fn read_manifest(dir: &Path) -> Result<Manifest, LocCounterError> {
+    let manifest_path = dir.join("Cargo.toml");
+    Manifest::from_path(&manifest_path)
+        .map_err(|err| LocCounterError::TomlError(format!("{}: {}", manifest_path.display(), err)))
+}
+
+fn collect_members(root: &Path, manifest: &Manifest, members: &mut Vec<WorkspaceMember>, seen: &mut BTreeSet<PathBuf>) -> Result<(), LocCounterError> {
+    if let Some(package) = &manifest.package {
+        add_member(root, root, &package.name, members, seen);
+    }
+    let Some(workspace) = &manifest.workspace else { return Ok(()) };
+
+    let excluded = workspace.exclude.iter().map(|path| root.join(path)).collect::<Vec<_>>();
+    for pattern in &workspace.members {
+        let pattern = root.join(pattern).to_string_lossy().to_string();
+        let paths = glob::glob(&pattern)
+            .map_err(|err| LocCounterError::TomlError(format!("Invalid workspace member pattern `{}`: {}", pattern, err)))?;
+        for dir in paths.filter_map(Result::ok) {
+            if !dir.join("Cargo.toml").is_file() || excluded.iter().any(|path| dir.starts_with(path)) {
+                continue;
+            }
+            let member_manifest = read_manifest(&dir)?;
+            if member_manifest.workspace.is_some() && dir != root {
+                // A nested workspace brings its own members along.
+                collect_members(&dir, &member_manifest, members, seen)?;
+            } else if let Some(package) = &member_manifest.package {
+                add_member(&dir, root, &package.name, members, seen);
+            }
+        }
+    }
+    Ok(())
+}
+
+fn add_member(dir: &Path, workspace_root: &Path, name: &str, members: &mut Vec<WorkspaceMember>, seen: &mut BTreeSet<PathBuf>) {
+    if seen.insert(fs::canonicalize(dir).unwrap_or_else(|_| dir.to_path_buf())) {
+        members.push(WorkspaceMember {
+            name: name.to_string(),
+            manifest_dir: dir.to_path_buf(),
+            workspace_root: workspace_root.to_path_buf(),
+        });
+    }
+}
+
+/// The numbers of a single workspace member, or of the whole workspace for the rollup.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct MemberReport {
+    pub name: String,
+    pub manifest_dir: PathBuf,
+    pub num_files: usize,
+    pub line_counts: LineCounts,
+    pub modules: ModuleStats,
+    pub items: ItemInventory,
+    pub test_split: TestSplit,
+}
+
+/// `WorkspaceReport` 📋 - A per-member breakdown of a workspace.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct WorkspaceReport {
+    pub root: PathBuf,
+    pub is_virtual: bool,
+    pub members: Vec<MemberReport>,
+}
+
+impl WorkspaceReport {
+    /// All members added up into one report, named after the workspace folder.
+    pub fn rollup(&self) -> MemberReport {
+        let name = self.root.file_name().map(|name| name.to_string_lossy().to_string()).unwrap_or_default();
+        self.members.iter().fold(MemberReport { name, manifest_dir: self.root.clone(), ..Default::default() }, |mut total, member| {
+            total.num_files += member.num_files;
+            total.line_counts += member.line_counts;
+            total.modules = ModuleStats {
+                inline: total.modules.inline + member.modules.inline,
+                file: total.modules.file + member.modules.file,
+                test: total.modules.test + member.modules.test,
+            };
+            total.items += member.items;
+            total.test_split += member.test_split;
+            total
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_workspace_members_are_enumerated() {
+        let root = std::env::temp_dir().join(format!("countroo-workspace-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&root);
+        let manifests = [
+            ("", "[workspace]\nmembers = [\"crates/*\", \"tools/nested\"]\nexclude = [\"crates/skipped\"]\n"),
+            ("crates/a", "[package]\nname = \"a\"\nversion = \"0.1.0\"\n"),
+            ("crates/b", "[package]\nname = \"b\"\nversion = \"0.1.0\"\n"),
+            ("crates/skipped", "[package]\nname = \"skipped\"\nversion = \"0.1.0\"\n"),
+            ("tools/nested", "[workspace]\nmembers = [\"inner\"]\n"),
+            ("tools/nested/inner", "[package]\nname = \"inner\"\nversion = \"0.1.0\"\n"),
+        ];
+        for (dir, manifest) in manifests {
+            fs::create_dir_all(root.join(dir).join("src")).unwrap();
+            fs::write(root.join(dir).join("Cargo.toml"), manifest).unwrap();
+            fs::write(root.join(dir).join("src/lib.rs"), "").unwrap();
+        }
+        fs::create_dir_all(root.join("crates/not-a-crate")).unwrap();
+
+        let workspace = Workspace::find(root.join("crates/a/src")).unwrap().unwrap();
+        assert!(workspace.is_virtual);
+        let names: Vec<&str> = workspace.members.iter().map(|m| m.name.as_str()).collect();
+        assert_eq!(names, vec!["a", "b", "inner"]);
+        assert!(workspace.members[2].workspace_root.ends_with("tools/nested"));
+
+        // The nearest workspace that takes a package in is the one it belongs to.
+        let nested = Workspace::find(root.join("tools/nested/inner")).unwrap().unwrap();
+        assert!(nested.root.ends_with("tools/nested"));
+        assert_eq!(nested.members.iter().map(|m| m.name.as_str()).collect::<Vec<_>>(), vec!["inner"]);
+        assert!(Workspace::find(root.join("crates/skipped")).unwrap().is_none());
+        fs::remove_dir_all(&root).unwrap();
+    }
+}